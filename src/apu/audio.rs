@@ -5,7 +5,14 @@
  * @date    May 25, 2024
  */
 
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use egui_sdl2_gl::sdl2::{
     audio::{AudioCallback, AudioDevice, AudioSpecDesired},
@@ -22,65 +29,194 @@ use ringbuf::{
 pub const SAMPLING_RATE: u16 = 512;
 pub const SAMPLING_FREQUENCY: u16 = 44100;
 
+pub const SAMPLE_RATES: [u32; 4] = [22050, 44100, 48000, 96000];
+pub const BUFFER_SIZES: [u16; 5] = [128, 256, 512, 1024, 2048];
+
+#[derive(Clone, PartialEq)]
+pub struct AudioSettings {
+    // None opens the system default
+    pub device: Option<String>,
+    pub sample_rate: u32,
+    // Stereo frames the device requests per callback
+    pub buffer_size: u16,
+}
+
+impl AudioSettings {
+    // Eight device buffers of interleaved stereo samples
+    pub fn ring_buffer_capacity(&self) -> usize {
+        self.buffer_size as usize * 16
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            device: None,
+            sample_rate: SAMPLING_FREQUENCY as u32,
+            buffer_size: SAMPLING_RATE,
+        }
+    }
+}
+
 pub struct Audio<'a> {
-    left_master: &'a u8,
-    right_master: &'a u8,
     volume: &'a u8,
-    pub cons: Caching<Arc<SharedRb<Heap<u8>>>, false, true>,
+    pub cons: Caching<Arc<SharedRb<Heap<f32>>>, false, true>,
 }
 
 impl<'a> Audio<'a> {
-    pub fn new(
-        left_master: &'a u8,
-        right_master: &'a u8,
-        volume: &'a u8,
-        cons: Caching<Arc<SharedRb<Heap<u8>>>, false, true>,
-    ) -> Self {
-        Self {
-            left_master,
-            right_master,
-            volume,
-            cons,
-        }
+    pub fn new(volume: &'a u8, cons: Caching<Arc<SharedRb<Heap<f32>>>, false, true>) -> Self {
+        Self { volume, cons }
     }
 }
 
 impl AudioCallback for Audio<'_> {
-    type Channel = i16;
-
-    fn callback(&mut self, out: &mut [i16]) {
-        for (i, sample) in out.iter_mut().enumerate() {
-            let master_volume = if i % 2 == 0 {
-                self.left_master
-            } else {
-                self.right_master
-            };
+    type Channel = f32;
 
+    // Master volume is already applied by the APU, only the UI volume is left
+    fn callback(&mut self, out: &mut [f32]) {
+        for sample in out.iter_mut() {
             if let Some(s) = self.cons.try_pop() {
-                *sample = s as i16 * *self.volume as i16 * *master_volume as i16 + 1;
+                *sample = s * *self.volume as f32 / 100.0;
             } else {
-                *sample = 0;
+                *sample = 0.0;
             }
         }
     }
 }
 
-pub fn create_audio_device<'a>(
-    audio_subsystem: &AudioSubsystem,
-    left_volume: &'a u8,
-    right_volume: &'a u8,
+pub enum AudioOutput<'a> {
+    Device(AudioDevice<Audio<'a>>),
+    // Keeps the emulator running without a sound card, the samples go nowhere
+    Null(NullSink),
+}
+
+/*
+ * Consumes samples at the pace a device would, so the ring buffer fill
+ * level and the rate control behave the same without a sound card. The
+ * thread stops once the sink is dropped.
+ */
+pub struct NullSink {
+    sample_rate: u32,
+    running: Arc<AtomicBool>,
+}
+
+impl NullSink {
+    fn new(
+        settings: &AudioSettings,
+        mut cons: Caching<Arc<SharedRb<Heap<f32>>>, false, true>,
+    ) -> Self {
+        let sample_rate = settings.sample_rate;
+        let interval = Duration::from_secs_f64(settings.buffer_size as f64 / sample_rate as f64);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = Arc::clone(&running);
+
+        thread::spawn(move || {
+            let start = Instant::now();
+            let mut consumed = 0;
+
+            while thread_running.load(Ordering::Relaxed) {
+                thread::sleep(interval);
+
+                // Interleaved stereo samples a device would have played by now
+                let due = (start.elapsed().as_secs_f64() * sample_rate as f64) as usize * 2;
+                cons.skip(due - consumed);
+                consumed = due;
+            }
+        });
+
+        Self {
+            sample_rate,
+            running,
+        }
+    }
+}
+
+impl Drop for NullSink {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl AudioOutput<'_> {
+    // The device may run at a different rate than requested
+    pub fn sample_rate(&self) -> u32 {
+        match self {
+            AudioOutput::Device(device) => device.spec().freq as u32,
+            AudioOutput::Null(sink) => sink.sample_rate,
+        }
+    }
+
+    pub fn resume(&self) {
+        if let AudioOutput::Device(device) = self {
+            device.resume();
+        }
+    }
+}
+
+// Falls back to a null sink if the device can't be opened
+pub fn open_audio_output<'a>(
+    audio_subsystem: Option<&AudioSubsystem>,
+    settings: &AudioSettings,
     volume: &'a u8,
-    cons: Caching<Arc<SharedRb<Heap<u8>>>, false, true>,
-) -> AudioDevice<Audio<'a>> {
+    cons: Caching<Arc<SharedRb<Heap<f32>>>, false, true>,
+) -> AudioOutput<'a> {
+    let Some(audio_subsystem) = audio_subsystem else {
+        return AudioOutput::Null(NullSink::new(settings, cons));
+    };
+
     let device = AudioSpecDesired {
-        freq: Some(SAMPLING_FREQUENCY as i32),
-        samples: Some(SAMPLING_RATE),
+        freq: Some(settings.sample_rate as i32),
+        samples: Some(settings.buffer_size),
         channels: Some(2),
     };
 
-    let audio = Audio::new(left_volume, right_volume, volume, cons);
+    // The consumer moves into the callback only once the device is open
+    let mut cons = Some(cons);
+    let opened = audio_subsystem.open_playback(settings.device.as_deref(), &device, |_spec| {
+        Audio::new(volume, cons.take().unwrap())
+    });
+
+    match opened {
+        Ok(device) => AudioOutput::Device(device),
+        Err(e) => {
+            eprintln!("Error opening audio device: {e}.");
+            AudioOutput::Null(NullSink::new(settings, cons.take().unwrap()))
+        }
+    }
+}
+
+pub fn playback_devices(audio_subsystem: Option<&AudioSubsystem>) -> Vec<String> {
+    let Some(audio_subsystem) = audio_subsystem else {
+        return Vec::new();
+    };
+
+    (0..audio_subsystem.num_audio_playback_devices().unwrap_or(0))
+        .filter_map(|index| audio_subsystem.audio_playback_device_name(index).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod audio_tests {
+    use super::*;
+    use ringbuf::{
+        traits::{Observer, Producer, Split},
+        HeapRb,
+    };
 
-    audio_subsystem
-        .open_playback(None, &device, |_spec| audio)
-        .unwrap()
+    #[test]
+    fn null_sink_consumes_samples() {
+        let (mut prod, cons) = HeapRb::<f32>::new(4096).split();
+        prod.push_slice(&[0.0; 4096]);
+
+        let settings = AudioSettings {
+            device: None,
+            sample_rate: 22050,
+            buffer_size: 128,
+        };
+        let sink = NullSink::new(&settings, cons);
+        thread::sleep(Duration::from_millis(50));
+
+        assert!(prod.occupied_len() < 4096);
+        drop(sink);
+    }
 }
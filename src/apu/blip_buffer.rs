@@ -0,0 +1,154 @@
+/*
+ * @file    apu/blip_buffer.rs
+ * @brief   Band-limited synthesis of amplitude steps at an arbitrary output rate.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::f64::consts::PI;
+
+// Taps of the band-limited step, the output lags behind by half of them
+const KERNEL_WIDTH: usize = 16;
+// Sub-sample positions the kernel is precomputed for
+const PHASES: usize = 64;
+// Cutoff relative to the Nyquist frequency of the output rate
+const CUTOFF: f64 = 0.9;
+
+/*
+ * Instead of point-sampling the mixed channel output, every change in
+ * amplitude is recorded as a delta at the exact clock cycle it happened.
+ * Each delta is spread over a few output samples with a windowed sinc
+ * impulse, and the samples are obtained by integrating those deltas. The
+ * result contains no frequencies above the output Nyquist limit, so square
+ * waves with high frequencies don't alias back into the audible range.
+ * http://www.slack.net/~ant/bl-synth/
+ */
+pub struct BlipBuffer {
+    kernel: Vec<[f32; KERNEL_WIDTH]>,
+    // Output samples per clock cycle
+    factor: f64,
+    // Current position in output samples, relative to the start of the buffer
+    position: f64,
+    deltas: Vec<f32>,
+    integrator: f32,
+}
+
+impl BlipBuffer {
+    pub fn new(clock_rate: f64, sample_rate: f64) -> Self {
+        Self {
+            kernel: build_kernel(),
+            factor: sample_rate / clock_rate,
+            position: 0.0,
+            deltas: vec![0.0; KERNEL_WIDTH * 2],
+            integrator: 0.0,
+        }
+    }
+
+    pub fn set_rates(&mut self, clock_rate: f64, sample_rate: f64) {
+        self.factor = sample_rate / clock_rate;
+    }
+
+    pub fn add_delta(&mut self, delta: f32) {
+        if delta == 0.0 {
+            return;
+        }
+
+        let index = self.position as usize;
+        let phase = ((self.position - index as f64) * PHASES as f64) as usize;
+
+        if self.deltas.len() < index + KERNEL_WIDTH {
+            self.deltas.resize(index + KERNEL_WIDTH, 0.0);
+        }
+
+        for (sample, tap) in self.deltas[index..].iter_mut().zip(self.kernel[phase]) {
+            *sample += delta * tap;
+        }
+    }
+
+    pub fn advance(&mut self, clocks: u32) {
+        self.position += clocks as f64 * self.factor;
+    }
+
+    // Samples before the current position can't receive any more deltas
+    pub fn samples_available(&self) -> usize {
+        self.position as usize
+    }
+
+    pub fn read_samples(&mut self, count: usize, output: &mut Vec<f32>) {
+        let count = count.min(self.samples_available());
+        if self.deltas.len() < count + KERNEL_WIDTH {
+            self.deltas.resize(count + KERNEL_WIDTH, 0.0);
+        }
+
+        for delta in self.deltas.drain(..count) {
+            self.integrator += delta;
+            output.push(self.integrator);
+        }
+
+        self.deltas.resize(self.deltas.len() + count, 0.0);
+        self.position -= count as f64;
+    }
+}
+
+/*
+ * Every phase holds a Blackman-windowed sinc impulse, shifted by the
+ * fraction of a sample the step happened at. Each phase is normalized,
+ * so that a step of a given height always adds up to exactly that height.
+ */
+fn build_kernel() -> Vec<[f32; KERNEL_WIDTH]> {
+    let center = (KERNEL_WIDTH / 2 - 1) as f64;
+
+    (0..PHASES)
+        .map(|phase| {
+            let offset = phase as f64 / PHASES as f64;
+            let mut taps = [0.0; KERNEL_WIDTH];
+
+            for (tap, value) in taps.iter_mut().enumerate() {
+                let x = tap as f64 - center - offset;
+                let sinc = if x == 0.0 {
+                    CUTOFF
+                } else {
+                    (PI * CUTOFF * x).sin() / (PI * x)
+                };
+
+                let window_position = (x + center + 1.0) / KERNEL_WIDTH as f64;
+                let window = 0.42 - 0.5 * (2.0 * PI * window_position).cos()
+                    + 0.08 * (4.0 * PI * window_position).cos();
+
+                *value = sinc * window;
+            }
+
+            let sum = taps.iter().sum::<f64>();
+            taps.map(|value| (value / sum) as f32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod blip_buffer_tests {
+    use super::*;
+
+    #[test]
+    fn step_settles_at_delta() {
+        let mut blip = BlipBuffer::new(4_194_304.0, 44_100.0);
+        let mut output = Vec::new();
+
+        blip.add_delta(0.5);
+        blip.advance(70224);
+        blip.read_samples(usize::MAX, &mut output);
+
+        assert_eq!(output.len(), 738);
+        assert!((output.last().unwrap() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn constant_signal_produces_no_samples_without_time() {
+        let mut blip = BlipBuffer::new(4_194_304.0, 44_100.0);
+        let mut output = Vec::new();
+
+        blip.add_delta(1.0);
+        blip.read_samples(usize::MAX, &mut output);
+
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * @file    apu/capture.rs
+ * @brief   Collects 16-bit PCM samples of the mix and the channels for recordings.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use crate::apu::{
+    audio::SAMPLING_FREQUENCY,
+    output::{to_i16, OutputStage},
+};
+
+#[derive(Clone, Copy)]
+pub enum CaptureTarget {
+    Video,
+    Audio,
+}
+
+/*
+ * Every capture runs its own output stages at the native sampling frequency,
+ * independent of the host device and fast forward, so recordings follow
+ * emulated time. Stems hold a single channel each, panned and scaled like in
+ * the mix, so that they add up to the mix again.
+ */
+pub struct Capture {
+    mix: OutputStage,
+    stems: Option<[OutputStage; 4]>,
+    mix_samples: Vec<i16>,
+    stem_samples: [Vec<i16>; 4],
+}
+
+impl Capture {
+    pub fn new(stems: bool) -> Self {
+        Self {
+            mix: OutputStage::new(SAMPLING_FREQUENCY as u32),
+            stems: stems
+                .then(|| std::array::from_fn(|_| OutputStage::new(SAMPLING_FREQUENCY as u32))),
+            mix_samples: Vec::new(),
+            stem_samples: Default::default(),
+        }
+    }
+
+    pub fn has_stems(&self) -> bool {
+        self.stems.is_some()
+    }
+
+    pub fn record(&mut self, t_cycles: u32, mix: (f32, f32), stems: Option<[(f32, f32); 4]>) {
+        record_stage(&mut self.mix, t_cycles, mix, &mut self.mix_samples);
+
+        if let (Some(stages), Some(outputs)) = (&mut self.stems, stems) {
+            for ((stage, output), samples) in stages
+                .iter_mut()
+                .zip(outputs)
+                .zip(self.stem_samples.iter_mut())
+            {
+                record_stage(stage, t_cycles, output, samples);
+            }
+        }
+    }
+
+    // Interleaved stereo samples of the mix
+    pub fn take_samples(&mut self) -> Vec<i16> {
+        std::mem::take(&mut self.mix_samples)
+    }
+
+    // Interleaved stereo samples of each channel, empty without stems
+    pub fn take_stems(&mut self) -> [Vec<i16>; 4] {
+        std::mem::take(&mut self.stem_samples)
+    }
+}
+
+fn record_stage(
+    stage: &mut OutputStage,
+    t_cycles: u32,
+    output: (f32, f32),
+    samples: &mut Vec<i16>,
+) {
+    stage.update(output.0, output.1);
+    stage.advance(t_cycles);
+    stage.drain(|left, right| {
+        samples.push(to_i16(left));
+        samples.push(to_i16(right));
+    });
+}
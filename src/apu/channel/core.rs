@@ -36,6 +36,20 @@ impl ChannelCore {
         }
     }
 
+    /*
+     * https://gbdev.io/pandocs/Audio_details.html#dacs
+     * Each DAC maps the digital value 0 to an analog level of 1.0 and 15 to
+     * -1.0. A disabled channel still feeds 0 into an enabled DAC, only
+     * turning the DAC off pulls the analog output down to 0.0.
+     */
+    pub fn dac_output(&self) -> f32 {
+        if !self.dac_enabled {
+            return 0.0;
+        }
+
+        1.0 - self.get_output() as f32 / 7.5
+    }
+
     pub fn reset(&mut self) {
         self.enabled = false;
         self.dac_enabled = false;
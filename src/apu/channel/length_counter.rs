@@ -10,6 +10,8 @@ use super::square_channel::ChannelType;
 pub struct LengthCounter {
     pub enabled: bool,
     pub timer: u16,
+    // Set while the next frame sequencer step doesn't clock the length counters
+    pub extra_clocking: bool,
 }
 
 impl LengthCounter {
@@ -17,6 +19,7 @@ impl LengthCounter {
         Self {
             enabled: false,
             timer: 0,
+            extra_clocking: false,
         }
     }
 
@@ -33,6 +36,34 @@ impl LengthCounter {
         *channel_enabled = false;
     }
 
+    /*
+     * https://gbdev.gg8.se/wiki/articles/Gameboy_sound_hardware#Obscure_Behavior
+     * Enabling the length counter while the next frame sequencer step
+     * doesn't clock it, clocks it once right away. If that makes it reach
+     * zero, the channel is disabled.
+     */
+    pub fn set_enabled(&mut self, enabled: bool, channel_enabled: &mut bool) {
+        let was_enabled = self.enabled;
+        self.enabled = enabled;
+
+        if self.extra_clocking && !was_enabled {
+            self.tick(channel_enabled);
+        }
+    }
+
+    // Triggering reloads an expired counter, one less if it was clocked right away
+    pub fn trigger(&mut self, max: u16) {
+        if self.timer != 0 {
+            return;
+        }
+
+        self.timer = if self.enabled && self.extra_clocking {
+            max - 1
+        } else {
+            max
+        };
+    }
+
     pub fn reset(&mut self, channel: ChannelType) {
         self.enabled = false;
         if channel != ChannelType::CH4 {
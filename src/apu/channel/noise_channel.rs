@@ -103,11 +103,8 @@ impl NoiseChannel {
 
         self.core.timer = (DIVISORS[self.clock_divider as usize] as i32) << self.clock_shift;
         self.lfsr = 0xFFFF;
-        self.volume_envelope.counter = 0;
-
-        if self.core.length_counter.timer == 0 {
-            self.core.length_counter.timer = LENGTH_TIMER_MAX;
-        }
+        self.volume_envelope.trigger();
+        self.core.length_counter.trigger(LENGTH_TIMER_MAX);
     }
 
     fn set_length_timer(&mut self, value: u8) {
@@ -115,19 +112,15 @@ impl NoiseChannel {
     }
 
     fn set_volume_envelope(&mut self, value: u8) {
+        if self.core.enabled {
+            self.volume_envelope.apply_zombie_mode(value);
+        }
         self.volume_envelope.set(value);
 
         self.core.dac_enabled = value & 0xF8 != 0x00;
         if !self.core.dac_enabled {
             self.core.enabled = false;
         }
-
-        // Writes to this register while the channel is on
-        // require retriggering it afterwards. If the write
-        // turns the channel off, retriggering is not necessary
-        if self.core.enabled {
-            self.trigger();
-        }
     }
 
     fn get_frequency_randomness(&self) -> u8 {
@@ -156,7 +149,9 @@ impl NoiseChannel {
     }
 
     fn set_control(&mut self, value: u8) {
-        self.core.length_counter.enabled = value & 0x40 != 0;
+        self.core
+            .length_counter
+            .set_enabled(value & 0x40 != 0, &mut self.core.enabled);
         let triggered = value & 0x80 != 0;
         if triggered {
             self.trigger();
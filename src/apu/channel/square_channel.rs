@@ -73,7 +73,7 @@ impl MemoryAccess for SquareChannel {
         match address {
             SWEEP => {
                 if let Some(sweep) = &mut self.sweep {
-                    sweep.set(value);
+                    sweep.write(value, &mut self.core.enabled);
                 }
             }
             LENGTH_TIMER => self.set_length_timer(value),
@@ -148,14 +148,11 @@ impl SquareChannel {
         }
 
         self.core.timer = ((2048 - self.frequency) * 4) as i32;
-        self.volume_envelope.counter = 0;
+        self.volume_envelope.trigger();
+        self.core.length_counter.trigger(LENGTH_TIMER_MAX);
 
         if let Some(sweep) = &mut self.sweep {
-            sweep.sequence = 0;
-        }
-
-        if self.core.length_counter.timer == 0 {
-            self.core.length_counter.timer = LENGTH_TIMER_MAX;
+            sweep.trigger(self.frequency, &mut self.core.enabled);
         }
     }
 
@@ -172,6 +169,9 @@ impl SquareChannel {
     }
 
     fn set_volume_envelope(&mut self, value: u8) {
+        if self.core.enabled {
+            self.volume_envelope.apply_zombie_mode(value);
+        }
         self.volume_envelope.set(value);
 
         // https://gbdev.io/pandocs/Audio_details.html#dacs
@@ -180,13 +180,6 @@ impl SquareChannel {
         if !self.core.dac_enabled {
             self.core.enabled = false;
         }
-
-        // Writes to this register while the channel is on
-        // require retriggering it afterwards. If the write
-        // turns the channel off, retriggering is not necessary
-        if self.core.enabled {
-            self.trigger();
-        }
     }
 
     fn get_frequency_low(&self) -> u8 {
@@ -210,13 +203,15 @@ impl SquareChannel {
     }
 
     fn set_frequency_high(&mut self, value: u8) {
+        self.core
+            .length_counter
+            .set_enabled(value & 0x40 != 0, &mut self.core.enabled);
+        self.frequency = (self.frequency & 0x00FF) | ((value & 0x07) as u16) << 8;
+
         let triggered = value & 0x80 != 0;
         if triggered {
             self.trigger();
         }
-
-        self.core.length_counter.enabled = value & 0x40 != 0;
-        self.frequency = (self.frequency & 0x00FF) | ((value & 0x07) as u16) << 8;
     }
 
     pub fn reset(&mut self, channel: ChannelType) {
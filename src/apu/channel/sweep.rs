@@ -9,11 +9,23 @@ const MASK_STEP: u8 = 0x07;
 const MASK_DIR: u8 = 0x08;
 const MASK_PACE: u8 = 0x70;
 
+const FREQUENCY_MAX: u16 = 0x07FF;
+
+/*
+ * https://gbdev.gg8.se/wiki/articles/Gameboy_sound_hardware#Frequency_Sweep
+ * The sweep works on a shadow copy of the frequency taken on trigger. Each
+ * calculation checks for an overflow, which disables the channel even if
+ * the result is never written back.
+ */
 pub struct Sweep {
     step: u8,
     direction: bool,
     pace: u8,
-    pub sequence: u8,
+    timer: u8,
+    enabled: bool,
+    shadow_frequency: u16,
+    // A calculation in negate mode was made since the last trigger
+    negated: bool,
 }
 
 impl Sweep {
@@ -22,36 +34,78 @@ impl Sweep {
             step: 0,
             direction: false,
             pace: 0,
-            sequence: 0,
+            timer: 0,
+            enabled: false,
+            shadow_frequency: 0,
+            negated: false,
         }
     }
 
     pub fn tick(&mut self, frequency: &mut u16, channel_enabled: &mut bool) {
-        if self.pace == 0 {
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer != 0 {
             return;
         }
 
-        self.sequence += 1;
+        self.reload_timer();
+
+        if !self.enabled || self.pace == 0 {
+            return;
+        }
 
-        if self.sequence < self.pace {
+        let new_frequency = self.calculate(channel_enabled);
+        if new_frequency > FREQUENCY_MAX || self.step == 0 {
             return;
         }
 
-        let delta = *frequency >> self.step;
+        self.shadow_frequency = new_frequency;
+        *frequency = new_frequency;
+
+        // The new frequency is checked for an overflow right away
+        self.calculate(channel_enabled);
+    }
+
+    pub fn trigger(&mut self, frequency: u16, channel_enabled: &mut bool) {
+        self.shadow_frequency = frequency;
+        self.reload_timer();
+        self.enabled = self.pace != 0 || self.step != 0;
+        self.negated = false;
+
+        if self.step != 0 {
+            self.calculate(channel_enabled);
+        }
+    }
+
+    // Leaving negate mode after it was used disables the channel
+    pub fn write(&mut self, value: u8, channel_enabled: &mut bool) {
+        let negate_cleared = self.direction && value & MASK_DIR == 0;
+        self.set(value);
+
+        if negate_cleared && self.negated {
+            *channel_enabled = false;
+        }
+    }
+
+    fn calculate(&mut self, channel_enabled: &mut bool) -> u16 {
+        let delta = self.shadow_frequency >> self.step;
 
-        *frequency = if self.direction {
-            frequency.saturating_sub(delta)
+        let new_frequency = if self.direction {
+            self.negated = true;
+            self.shadow_frequency - delta
         } else {
-            frequency.saturating_add(delta)
+            self.shadow_frequency + delta
         };
 
-        // Overflow check
-        if *frequency > 0x07FF {
+        if new_frequency > FREQUENCY_MAX {
             *channel_enabled = false;
-            *frequency = 0x07FF;
         }
 
-        self.sequence = 0;
+        new_frequency
+    }
+
+    // A pace of 0 is treated as 8
+    fn reload_timer(&mut self) {
+        self.timer = if self.pace == 0 { 8 } else { self.pace };
     }
 
     pub fn set(&mut self, value: u8) {
@@ -72,7 +126,10 @@ impl Sweep {
         self.step = 0;
         self.direction = false;
         self.pace = 0;
-        self.sequence = 0;
+        self.timer = 0;
+        self.enabled = false;
+        self.shadow_frequency = 0;
+        self.negated = false;
     }
 
     pub fn default() -> Self {
@@ -140,4 +197,27 @@ mod sweep_tests {
 
         assert_eq!(sweep.get(), 0xF0);
     }
+
+    #[test]
+    fn leaving_negate_mode_disables_channel() {
+        let mut sweep = Sweep::new();
+        let mut channel_enabled = true;
+
+        sweep.set(0x19);
+        sweep.trigger(0x0400, &mut channel_enabled);
+        sweep.write(0x11, &mut channel_enabled);
+
+        assert!(!channel_enabled);
+    }
+
+    #[test]
+    fn overflow_on_trigger() {
+        let mut sweep = Sweep::new();
+        let mut channel_enabled = true;
+
+        sweep.set(0x01);
+        sweep.trigger(0x0600, &mut channel_enabled);
+
+        assert!(!channel_enabled);
+    }
 }
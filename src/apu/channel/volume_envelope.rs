@@ -15,6 +15,8 @@ pub struct VolumeEnvelope {
     pub pace: u8,
     pub direction: bool,
     pub volume: u8,
+    // Loaded into the volume on trigger
+    initial_volume: u8,
 }
 
 impl VolumeEnvelope {
@@ -29,11 +31,12 @@ impl VolumeEnvelope {
             pace,
             direction,
             volume,
+            initial_volume: volume,
         }
     }
 
     pub fn tick(&mut self, channel_enabled: &bool) {
-        if !self.enabled || !channel_enabled {
+        if !self.enabled || !channel_enabled || self.pace == 0 {
             return;
         }
 
@@ -58,15 +61,38 @@ impl VolumeEnvelope {
     pub fn set(&mut self, value: u8) {
         self.pace = value & MASK_PACE;
         self.direction = value & MASK_DIR != 0;
-        self.volume = (value & MASK_VOL) >> 4;
-        self.enabled = self.pace > 0;
+        self.initial_volume = (value & MASK_VOL) >> 4;
+    }
+
+    pub fn trigger(&mut self) {
+        self.volume = self.initial_volume;
+        self.enabled = true;
         self.counter = 0;
     }
 
+    /*
+     * https://gbdev.gg8.se/wiki/articles/Gameboy_sound_hardware#Obscure_Behavior
+     * Writing to NRx2 while the channel is on doesn't reload the volume, but
+     * changes it in a way some games rely on ("zombie mode").
+     */
+    pub fn apply_zombie_mode(&mut self, value: u8) {
+        if self.pace == 0 && self.enabled {
+            self.volume += 1;
+        } else if !self.direction {
+            self.volume += 2;
+        }
+
+        if self.direction != (value & MASK_DIR != 0) {
+            self.volume = 16u8.wrapping_sub(self.volume);
+        }
+
+        self.volume &= 0x0F;
+    }
+
     pub fn get(&self) -> u8 {
         let pace = self.pace & MASK_PACE;
         let direction = if self.direction { MASK_DIR } else { 0x00 };
-        let volume = (self.volume & 0x0F) << 4;
+        let volume = (self.initial_volume & 0x0F) << 4;
 
         pace | direction | volume
     }
@@ -77,6 +103,7 @@ impl VolumeEnvelope {
         self.pace = 0;
         self.direction = false;
         self.volume = 0;
+        self.initial_volume = 0;
     }
 }
 
@@ -146,4 +173,20 @@ mod volume_envelope_tests {
 
         assert_eq!(volume_envelope.get(), MASK_VOL);
     }
+
+    #[test]
+    fn zombie_mode() {
+        let mut volume_envelope = VolumeEnvelope::default();
+
+        // Volume 8, decreasing
+        volume_envelope.set(0x81);
+        volume_envelope.trigger();
+
+        volume_envelope.apply_zombie_mode(0x81);
+        assert_eq!(volume_envelope.volume, 10);
+
+        // Switching to increase mirrors the volume
+        volume_envelope.apply_zombie_mode(0x89);
+        assert_eq!(volume_envelope.volume, 4);
+    }
 }
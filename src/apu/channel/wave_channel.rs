@@ -29,6 +29,8 @@ pub struct WaveChannel {
     pub frequency: u16,
     pub wave_ram: [u8; 32],
     pub wave_ram_position: u8,
+    // Set during the M-cycle in which the channel read a sample
+    sample_read: bool,
 }
 
 impl MemoryAccess for WaveChannel {
@@ -57,6 +59,8 @@ impl MemoryAccess for WaveChannel {
 
 impl ComponentTick for WaveChannel {
     fn tick(&mut self, m_cycles: u8) {
+        self.sample_read = false;
+
         if !self.core.enabled || !self.core.dac_enabled {
             return;
         }
@@ -68,13 +72,19 @@ impl ComponentTick for WaveChannel {
             return;
         }
 
-        let wave_index = self.wave_ram_position;
-        let output = self.wave_ram[wave_index as usize];
+        // High frequencies read more than one sample per tick
+        let period = self.period();
+        while self.core.timer <= 0 {
+            let wave_index = self.wave_ram_position;
+            let output = self.wave_ram[wave_index as usize];
 
-        self.core.output = output >> self.volume_shift();
+            self.core.output = output >> self.volume_shift();
+            self.core.timer += period;
+            self.wave_ram_position = (self.wave_ram_position + 1) & 0x1F;
+        }
 
-        self.core.timer += ((2048 - self.frequency) * 2) as i32;
-        self.wave_ram_position = (self.wave_ram_position + 1) & 0x1F;
+        // Ticks end on the M-cycle of an access, only a read in it counts
+        self.sample_read = self.core.timer > period - 4;
     }
 }
 
@@ -89,19 +99,44 @@ impl WaveChannel {
                 0x6, 0x0, 0x5, 0x9, 0x5, 0x9, 0xB, 0x0, 0x3, 0x4, 0xB, 0x8, 0x2, 0xE, 0xD, 0xA,
             ],
             wave_ram_position: 0,
+            sample_read: false,
         }
     }
 
     pub fn trigger(&mut self) {
+        // The APU ran up to this write, a read due within it collides
+        if self.core.enabled && self.core.timer <= 2 {
+            self.corrupt_wave_ram();
+        }
+
         if self.core.dac_enabled {
             self.core.enabled = true;
         }
 
-        self.core.timer = ((2048 - self.frequency) * 2) as i32;
+        self.core.timer = self.period();
         self.wave_ram_position = 1;
+        self.core.length_counter.trigger(LENGTH_TIMER_MAX);
+    }
+
+    fn period(&self) -> i32 {
+        ((2048 - self.frequency) * 2) as i32
+    }
 
-        if self.core.length_counter.timer == 0 {
-            self.core.length_counter.timer = LENGTH_TIMER_MAX;
+    /*
+     * https://gbdev.gg8.se/wiki/articles/Gameboy_sound_hardware#Obscure_Behavior
+     * On the DMG, retriggering the channel right when it reads a sample
+     * overwrites the start of wave RAM. If the byte being read is one of
+     * the first four, only the first byte is replaced by it, otherwise the
+     * first four bytes are replaced by the aligned block it belongs to.
+     */
+    fn corrupt_wave_ram(&mut self) {
+        let byte = (self.wave_ram_position / 2) as usize;
+
+        if byte < 4 {
+            self.wave_ram.copy_within(byte * 2..byte * 2 + 2, 0);
+        } else {
+            let block = (byte & !0x03) * 2;
+            self.wave_ram.copy_within(block..block + 8, 0);
         }
     }
 
@@ -156,27 +191,51 @@ impl WaveChannel {
     }
 
     fn set_frequency_high(&mut self, value: u8) {
+        self.core
+            .length_counter
+            .set_enabled(value & 0x40 != 0, &mut self.core.enabled);
+        self.frequency = (self.frequency & 0x00FF) | ((value & 0x07) as u16) << 8;
+
         let triggered = value & 0x80 != 0;
         if triggered {
             self.trigger();
         }
+    }
 
-        self.core.length_counter.enabled = value & 0x40 != 0;
-        self.frequency = (self.frequency & 0x00FF) | ((value & 0x07) as u16) << 8;
+    /*
+     * While the channel is on, any wave RAM access goes to the byte the
+     * channel is playing. On the DMG, this only works right after the
+     * channel read it, otherwise reads return 0xFF and writes are ignored.
+     * The memory bus runs the APU up to the M-cycle of the access first.
+     */
+    fn wave_ram_index(&self, address: u16) -> Option<usize> {
+        if !self.core.enabled {
+            return Some((address - WAVE_PATTERN_START) as usize * 2);
+        }
+
+        // The position already points past the sample that was read
+        let position = self.wave_ram_position.wrapping_sub(1) & 0x1F;
+        self.sample_read.then_some((position & !0x01) as usize)
     }
 
     pub fn read_wave_ram(&self, address: u16) -> u8 {
-        let index = (address - WAVE_PATTERN_START) * 2;
-        let upper_nibble = (self.wave_ram[index as usize] & 0xF) << 4;
-        let lower_nibble = self.wave_ram[index as usize + 1] & 0xF;
+        let Some(index) = self.wave_ram_index(address) else {
+            return 0xFF;
+        };
+
+        let upper_nibble = (self.wave_ram[index] & 0xF) << 4;
+        let lower_nibble = self.wave_ram[index + 1] & 0xF;
 
         upper_nibble | lower_nibble
     }
 
     pub fn write_wave_ram(&mut self, address: u16, value: u8) {
-        let index = (address - WAVE_PATTERN_START) * 2;
-        self.wave_ram[index as usize] = (value & 0xF0) >> 4;
-        self.wave_ram[index as usize + 1] = value & 0xF;
+        let Some(index) = self.wave_ram_index(address) else {
+            return;
+        };
+
+        self.wave_ram[index] = (value & 0xF0) >> 4;
+        self.wave_ram[index + 1] = value & 0xF;
     }
 
     pub fn reset(&mut self, channel: ChannelType) {
@@ -184,6 +243,7 @@ impl WaveChannel {
         self.core.length_counter.reset(channel);
         self.volume = 0;
         self.frequency = 0;
+        self.sample_read = false;
     }
 }
 
@@ -192,3 +252,59 @@ impl Default for WaveChannel {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod wave_ram_tests {
+    use super::*;
+
+    #[test]
+    fn retrigger_corrupts_wave_ram() {
+        let mut ch3 = WaveChannel::new();
+        ch3.set_dac_enable(0x80);
+        ch3.trigger();
+
+        for (i, address) in (WAVE_PATTERN_START..=WAVE_PATTERN_END).enumerate() {
+            ch3.core.enabled = false;
+            ch3.write_wave_ram(address, i as u8);
+        }
+
+        // About to read the upper nibble of byte 5
+        ch3.core.enabled = true;
+        ch3.wave_ram_position = 10;
+        ch3.core.timer = 2;
+        ch3.trigger();
+
+        ch3.core.enabled = false;
+        let start: Vec<u8> = (0..4)
+            .map(|i| ch3.read_wave_ram(WAVE_PATTERN_START + i))
+            .collect();
+        assert_eq!(start, [4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn wave_ram_accessible_after_read() {
+        let mut ch3 = WaveChannel::new();
+        ch3.set_dac_enable(0x80);
+        // Period of 8 T-cycles
+        ch3.frequency = 2044;
+        ch3.trigger();
+
+        // Two samples are read within one 4 M-cycle instruction
+        ch3.tick(4);
+        assert_eq!(ch3.wave_ram_position, 3);
+        assert_eq!(ch3.read_wave_ram(WAVE_PATTERN_START), 0x40);
+
+        // One M-cycle later, the next read is still one M-cycle away
+        ch3.tick(1);
+        assert_eq!(ch3.read_wave_ram(WAVE_PATTERN_START), 0xFF);
+    }
+
+    #[test]
+    fn wave_ram_locked_while_playing() {
+        let mut ch3 = WaveChannel::new();
+        ch3.set_dac_enable(0x80);
+        ch3.trigger();
+
+        assert_eq!(ch3.read_wave_ram(WAVE_PATTERN_START), 0xFF);
+    }
+}
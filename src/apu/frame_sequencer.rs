@@ -73,6 +73,11 @@ impl FrameSequencer {
         }
     }
 
+    // Length counters are clocked on even steps
+    pub fn next_step_clocks_length(&self) -> bool {
+        self.step & 0x01 == 0
+    }
+
     fn tick_length_timers(
         &mut self,
         ch1: &mut SquareChannel,
@@ -23,9 +23,37 @@ const MASKS: [(u8, u8); 4] = [
     (CH4_RIGHT_MASK, CH4_LEFT_MASK),
 ];
 
+// Fraction of the previous meter level kept per UI frame
+const METER_DECAY: f32 = 0.85;
+
+// User overrides for a single channel, on top of what the game writes to NR51
+#[derive(Clone, Copy)]
+pub struct ChannelControl {
+    pub muted: bool,
+    pub solo: bool,
+    // 0.0 to 2.0, 1.0 being the original level
+    pub gain: f32,
+    // -1.0 (left) to 1.0 (right), replaces the game's panning if set
+    pub pan: Option<f32>,
+}
+
+impl Default for ChannelControl {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            solo: false,
+            gain: 1.0,
+            pan: None,
+        }
+    }
+}
+
 // 0xFF25 — NR51 (Sound panning)
 pub struct Mixer {
     pub panning: [bool; 8],
+    pub controls: [ChannelControl; 4],
+    peaks: [f32; 4],
+    levels: [f32; 4],
 }
 
 impl Mixer {
@@ -38,23 +66,76 @@ impl Mixer {
             panning[i + 4] = value & left_mask != 0;
         }
 
-        Mixer { panning }
+        Mixer {
+            panning,
+            controls: [ChannelControl::default(); 4],
+            peaks: [0.0; 4],
+            levels: [0.0; 4],
+        }
     }
 
-    pub fn mix(&self, channels: [&ChannelCore; 4]) -> (u8, u8) {
-        let (mut output_left, mut output_right) = (0, 0);
+    // Sums up the analog DAC outputs, scaled to the range of -1.0 to 1.0
+    pub fn mix(&mut self, channels: [&ChannelCore; 4]) -> (f32, f32) {
+        let (mut output_left, mut output_right) = (0.0, 0.0);
+        let any_solo = self.controls.iter().any(|control| control.solo);
 
         for (i, channel) in channels.iter().enumerate() {
-            if self.panning[i + 4] {
-                output_left += channel.get_output();
+            let control = &self.controls[i];
+            let audible = if any_solo {
+                control.solo
+            } else {
+                !control.muted
+            };
+
+            if !audible {
+                continue;
             }
 
-            if self.panning[i] {
-                output_right += channel.get_output();
-            }
+            let output = channel.dac_output() * control.gain;
+            let level = channel.get_output() as f32 / 15.0 * control.gain;
+            self.peaks[i] = self.peaks[i].max(level);
+
+            let (left, right) = match control.pan {
+                Some(pan) => ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0)),
+                None => (
+                    self.panning[i + 4] as u8 as f32,
+                    self.panning[i] as u8 as f32,
+                ),
+            };
+
+            output_left += output * left;
+            output_right += output * right;
+        }
+
+        (output_left / 4.0, output_right / 4.0)
+    }
+
+    // A single channel panned by NR51, on the same scale as the mix
+    pub fn stem(&self, index: usize, channel: &ChannelCore) -> (f32, f32) {
+        let output = channel.dac_output() / 4.0;
+
+        (
+            output * self.panning[index + 4] as u8 as f32,
+            output * self.panning[index] as u8 as f32,
+        )
+    }
+
+    // Meter levels of all channels, meant to be polled once per frame
+    pub fn levels(&mut self) -> [f32; 4] {
+        for (level, peak) in self.levels.iter_mut().zip(self.peaks.iter_mut()) {
+            *level = peak.max(*level * METER_DECAY);
+            *peak = 0.0;
         }
 
-        (output_left / 4, output_right / 4)
+        self.levels
+    }
+
+    // Only NR51 is affected, the user overrides persist
+    pub fn write_panning(&mut self, value: u8) {
+        for (i, &(right_mask, left_mask)) in MASKS.iter().enumerate() {
+            self.panning[i] = value & right_mask != 0;
+            self.panning[i + 4] = value & left_mask != 0;
+        }
     }
 
     pub fn reset(&mut self) {
@@ -79,15 +160,3 @@ impl std::convert::From<&Mixer> for u8 {
             })
     }
 }
-
-impl std::convert::From<u8> for Mixer {
-    fn from(value: u8) -> Self {
-        let mut mixer = Mixer::new();
-        for (i, &(right_mask, left_mask)) in MASKS.iter().enumerate() {
-            mixer.panning[i] = value & right_mask != 0;
-            mixer.panning[i + 4] = value & left_mask != 0;
-        }
-
-        mixer
-    }
-}
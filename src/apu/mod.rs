@@ -6,11 +6,16 @@
  */
 
 pub mod audio;
+mod blip_buffer;
+pub mod capture;
 pub mod channel;
 mod frame_sequencer;
 pub mod master_volume;
-mod mixer;
+pub mod mixer;
 pub mod ogg_player;
+pub mod oscilloscope;
+pub mod output;
+pub mod time_stretch;
 
 use std::{cell::RefCell, rc::Rc, sync::Arc};
 
@@ -19,6 +24,7 @@ use ringbuf::{storage::Heap, traits::Producer, wrap::caching::Caching, SharedRb}
 use crate::{
     apu::{
         audio::SAMPLING_FREQUENCY,
+        capture::{Capture, CaptureTarget},
         channel::{
             noise_channel::NoiseChannel,
             square_channel::{ChannelType, SquareChannel},
@@ -27,8 +33,10 @@ use crate::{
         frame_sequencer::FrameSequencer,
         master_volume::MasterVolume,
         mixer::Mixer,
+        oscilloscope::Oscilloscope,
+        output::OutputStage,
+        time_stretch::{FastForwardAudio, TimeStretch},
     },
-    cpu::clock::CPU_CLOCK_SPEED,
     ComponentTick, MemoryAccess,
 };
 
@@ -66,11 +74,15 @@ pub struct Apu {
     ch4: NoiseChannel,
     frame_sequencer: FrameSequencer,
     pub master_volume: MasterVolume,
-    mixer: Mixer,
+    pub mixer: Mixer,
+    pub oscilloscope: Oscilloscope,
     pub enabled: bool,
-    counter: f64,
+    output: OutputStage,
+    pub time_stretch: TimeStretch,
     fast_forward: Rc<RefCell<u8>>,
-    prod: Caching<Arc<SharedRb<Heap<u8>>>, true, false>,
+    prod: Caching<Arc<SharedRb<Heap<f32>>>, true, false>,
+    // Video and audio recordings can start and stop independently
+    captures: [Option<Capture>; 2],
 }
 
 impl MemoryAccess for Apu {
@@ -113,6 +125,16 @@ impl MemoryAccess for Apu {
             return;
         }
 
+        let extra_clocking = !self.frame_sequencer.next_step_clocks_length();
+        for core in [
+            &mut self.ch1.core,
+            &mut self.ch2.core,
+            &mut self.ch3.core,
+            &mut self.ch4.core,
+        ] {
+            core.length_counter.extra_clocking = extra_clocking;
+        }
+
         match address {
             CH1_START..=CH1_END => {
                 let address = calculate_square_address(CH1_START, address);
@@ -127,7 +149,7 @@ impl MemoryAccess for Apu {
             0xFF1F => {}
             CH4_START..=CH4_END => self.ch4.write_byte(address, value),
             MASTER_VOLUME => self.master_volume.set_master_volume(value),
-            PANNING => self.mixer = value.into(),
+            PANNING => self.mixer.write_panning(value),
             MASTER_CONTROL => self.set_master_control(value),
             0xFF27..=0xFF2F => {}
             _ => unreachable!(),
@@ -147,37 +169,64 @@ impl ComponentTick for Apu {
             &mut self.ch4,
         );
 
-        if !self.enabled {
-            return;
+        if self.enabled {
+            self.tick_channels(m_cycles);
         }
 
-        self.tick_channels(m_cycles);
+        let (output_left, output_right) = self.analog_output();
 
-        self.counter += t_cycles as f64;
+        self.oscilloscope.tick(
+            t_cycles,
+            [
+                self.ch1.core.get_output(),
+                self.ch2.core.get_output(),
+                self.ch3.core.get_output(),
+                self.ch4.core.get_output(),
+            ],
+            (output_left, output_right),
+        );
 
-        let cpu_cycles_per_sample = CPU_CLOCK_SPEED as f64 / (SAMPLING_FREQUENCY as f64)
-            * *self.fast_forward.borrow() as f64;
+        let stems = self
+            .captures
+            .iter()
+            .flatten()
+            .any(Capture::has_stems)
+            .then(|| self.stem_outputs());
 
-        while self.counter >= cpu_cycles_per_sample {
-            let (output_left, output_right) = self.mixer.mix([
-                &self.ch1.core,
-                &self.ch2.core,
-                &self.ch3.core,
-                &self.ch4.core,
-            ]);
+        for capture in self.captures.iter_mut().flatten() {
+            capture.record(t_cycles as u32, (output_left, output_right), stems);
+        }
 
-            if let Ok(()) = self.prod.try_push(output_left) {};
-            if let Ok(()) = self.prod.try_push(output_right) {};
+        if !self.enabled {
+            return;
+        }
+
+        let prod = &mut self.prod;
+        let time_stretch = &mut self.time_stretch;
+        let speed = *self.fast_forward.borrow();
 
-            self.counter -= cpu_cycles_per_sample;
+        // Shortened modes generate at the native rate and drop the excess afterwards
+        if time_stretch.is_active(speed) {
+            self.output.set_speed(1);
+        } else {
+            self.output.set_speed(speed);
         }
+
+        self.output.update(output_left, output_right);
+        self.output.advance(t_cycles as u32);
+        self.output.drain(|left, right| {
+            time_stretch.push(speed, left, right, |left, right| {
+                if let Ok(()) = prod.try_push(left) {};
+                if let Ok(()) = prod.try_push(right) {};
+            });
+        });
     }
 }
 
 impl Apu {
     pub fn new(
         fast_forward: Rc<RefCell<u8>>,
-        prod: Caching<Arc<SharedRb<Heap<u8>>>, true, false>,
+        prod: Caching<Arc<SharedRb<Heap<f32>>>, true, false>,
     ) -> Self {
         Self {
             ch1: SquareChannel::new(ChannelType::CH1),
@@ -187,13 +236,89 @@ impl Apu {
             frame_sequencer: FrameSequencer::new(),
             master_volume: MasterVolume::new(),
             mixer: Mixer::default(),
+            oscilloscope: Oscilloscope::default(),
             enabled: true,
-            counter: 0.0,
+            output: OutputStage::new(SAMPLING_FREQUENCY as u32),
+            time_stretch: TimeStretch::new(FastForwardAudio::TimeStretch),
             fast_forward,
             prod,
+            captures: [None, None],
         }
     }
 
+    // The output device may run at a different rate than requested
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.output.set_sample_rate(sample_rate);
+    }
+
+    // Reopening the output device comes with a new ring buffer
+    pub fn set_producer(&mut self, prod: Caching<Arc<SharedRb<Heap<f32>>>, true, false>) {
+        self.prod = prod;
+    }
+
+    // Set once per frame by the dynamic rate control
+    pub fn set_rate_adjustment(&mut self, adjustment: f64) {
+        self.output.set_rate_adjustment(adjustment);
+    }
+
+    pub fn start_capture(&mut self, target: CaptureTarget, stems: bool) {
+        self.captures[target as usize] = Some(Capture::new(stems));
+    }
+
+    pub fn stop_capture(&mut self, target: CaptureTarget) {
+        self.captures[target as usize] = None;
+    }
+
+    pub fn take_captured_samples(&mut self, target: CaptureTarget) -> Vec<i16> {
+        self.captures[target as usize]
+            .as_mut()
+            .map(Capture::take_samples)
+            .unwrap_or_default()
+    }
+
+    pub fn take_captured_stems(&mut self, target: CaptureTarget) -> [Vec<i16>; 4] {
+        self.captures[target as usize]
+            .as_mut()
+            .map(Capture::take_stems)
+            .unwrap_or_default()
+    }
+
+    // NR50 master volume (0-7) maps to 1/8-8/8
+    fn master_scale(&self) -> (f32, f32) {
+        (
+            (*self.master_volume.get_left_volume() + 1) as f32 / 8.0,
+            (*self.master_volume.get_right_volume() + 1) as f32 / 8.0,
+        )
+    }
+
+    fn analog_output(&mut self) -> (f32, f32) {
+        let (output_left, output_right) = self.mixer.mix([
+            &self.ch1.core,
+            &self.ch2.core,
+            &self.ch3.core,
+            &self.ch4.core,
+        ]);
+        let (scale_left, scale_right) = self.master_scale();
+
+        (output_left * scale_left, output_right * scale_right)
+    }
+
+    // Each channel on its own, without the mixer's user overrides
+    fn stem_outputs(&self) -> [(f32, f32); 4] {
+        let (scale_left, scale_right) = self.master_scale();
+        let channels = [
+            &self.ch1.core,
+            &self.ch2.core,
+            &self.ch3.core,
+            &self.ch4.core,
+        ];
+
+        std::array::from_fn(|i| {
+            let (left, right) = self.mixer.stem(i, channels[i]);
+            (left * scale_left, right * scale_right)
+        })
+    }
+
     fn tick_channels(&mut self, m_cycles: u8) {
         self.ch1.tick(m_cycles);
         self.ch2.tick(m_cycles);
@@ -236,7 +361,6 @@ impl Apu {
         self.frame_sequencer.reset();
         self.mixer.reset();
         self.master_volume.reset();
-        self.counter = 0.0;
     }
 }
 
@@ -10,6 +10,8 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use super::audio::AudioSettings;
+
 pub struct OggPlayer<'a> {
     data: Arc<Mutex<Vec<i16>>>,
     position: Arc<Mutex<usize>>,
@@ -41,6 +43,7 @@ impl AudioCallback for OggPlayer<'_> {
 
 pub fn create_audio_theme<'a>(
     audio_subsystem: &AudioSubsystem,
+    audio_settings: &AudioSettings,
     volume: &'a u8,
 ) -> Result<AudioDevice<OggPlayer<'a>>, Box<dyn Error>> {
     let file_bytes = include_bytes!("../../media/audio/splash.ogg");
@@ -67,10 +70,12 @@ pub fn create_audio_theme<'a>(
     };
 
     let device = audio_subsystem
-        .open_playback(None, &desired_spec, |_spec| OggPlayer {
-            data: Arc::clone(&data),
-            position: Arc::clone(&position),
-            volume,
+        .open_playback(audio_settings.device.as_deref(), &desired_spec, |_spec| {
+            OggPlayer {
+                data: Arc::clone(&data),
+                position: Arc::clone(&position),
+                volume,
+            }
         })
         .map_err(|e| format!("Failed to open audio device: {}", e))?;
 
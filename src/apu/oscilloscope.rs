@@ -0,0 +1,135 @@
+/*
+ * @file    apu/oscilloscope.rs
+ * @brief   Rolling sample history of the channels and the master output.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+// T-cycles between two recorded samples, which gives a rate of 65536 Hz
+const SAMPLE_PERIOD: u32 = 64;
+// Roughly 62ms, long enough to find a trigger for notes down to 32 Hz
+const HISTORY_LENGTH: usize = 4096;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Trace {
+    Channel(usize),
+    Left,
+    Right,
+}
+
+/*
+ * Every trace is kept in a ring buffer. Channels store their digital output
+ * (0-15), the master stores the analog left and right levels after mixing.
+ */
+pub struct Oscilloscope {
+    channels: [[u8; HISTORY_LENGTH]; 4],
+    master: [[f32; HISTORY_LENGTH]; 2],
+    position: usize,
+    counter: u32,
+}
+
+impl Oscilloscope {
+    pub fn new() -> Self {
+        Self {
+            channels: [[0; HISTORY_LENGTH]; 4],
+            master: [[0.0; HISTORY_LENGTH]; 2],
+            position: 0,
+            counter: 0,
+        }
+    }
+
+    pub fn tick(&mut self, t_cycles: u16, channels: [u8; 4], master: (f32, f32)) {
+        self.counter += t_cycles as u32;
+
+        while self.counter >= SAMPLE_PERIOD {
+            self.counter -= SAMPLE_PERIOD;
+
+            for (history, output) in self.channels.iter_mut().zip(channels) {
+                history[self.position] = output;
+            }
+            self.master[0][self.position] = master.0;
+            self.master[1][self.position] = master.1;
+
+            self.position = (self.position + 1) % HISTORY_LENGTH;
+        }
+    }
+
+    // Samples of the trace from oldest to newest
+    fn history(&self, trace: Trace) -> Vec<f32> {
+        let (older, newer) = (self.position..HISTORY_LENGTH, 0..self.position);
+
+        match trace {
+            Trace::Channel(index) => {
+                let history = &self.channels[index];
+                history[older]
+                    .iter()
+                    .chain(&history[newer])
+                    .map(|&sample| sample as f32 / 15.0)
+                    .collect()
+            }
+            Trace::Left | Trace::Right => {
+                let history = &self.master[(trace == Trace::Right) as usize];
+                history[older]
+                    .iter()
+                    .chain(&history[newer])
+                    .copied()
+                    .collect()
+            }
+        }
+    }
+
+    /*
+     * Returns the latest window of `length` samples that starts on a rising
+     * edge through the middle of the signal, so periodic waveforms hold
+     * still from frame to frame. Without an edge, the newest samples are
+     * shown as they come.
+     */
+    pub fn triggered(&self, trace: Trace, length: usize) -> Vec<f32> {
+        let history = self.history(trace);
+        let length = length.min(HISTORY_LENGTH);
+        let latest_start = HISTORY_LENGTH - length;
+
+        let (min, max) = history
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), &sample| {
+                (min.min(sample), max.max(sample))
+            });
+        let middle = (min + max) / 2.0;
+
+        let start = (1..=latest_start)
+            .rev()
+            .find(|&i| history[i - 1] < middle && history[i] >= middle)
+            .unwrap_or(latest_start);
+
+        history[start..start + length].to_vec()
+    }
+}
+
+impl Default for Oscilloscope {
+    fn default() -> Self {
+        Oscilloscope::new()
+    }
+}
+
+#[cfg(test)]
+mod oscilloscope_tests {
+    use super::*;
+
+    #[test]
+    fn window_starts_on_rising_edge() {
+        let mut oscilloscope = Oscilloscope::new();
+
+        // Square wave with a period of 8 samples
+        for i in 0..HISTORY_LENGTH + 3 {
+            let output = if i % 8 < 4 { 0 } else { 15 };
+            oscilloscope.tick(SAMPLE_PERIOD as u16, [output, 0, 0, 0], (0.0, 0.0));
+        }
+
+        let window = oscilloscope.triggered(Trace::Channel(0), 16);
+
+        assert_eq!(window.len(), 16);
+        assert_eq!(window[0], 1.0);
+        assert_eq!(window[3], 1.0);
+        assert_eq!(window[4], 0.0);
+    }
+}
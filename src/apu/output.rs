@@ -0,0 +1,157 @@
+/*
+ * @file    apu/output.rs
+ * @brief   Turns the analog channel mix into filtered stereo samples at the host rate.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use crate::{apu::blip_buffer::BlipBuffer, cpu::clock::CPU_CLOCK_SPEED};
+
+// Capacitor charge factor per T-cycle of the DMG output stage
+// https://gbdev.gg8.se/wiki/articles/Gameboy_sound_hardware#Obscure_Behavior
+const CHARGE_FACTOR: f64 = 0.999958;
+
+/*
+ * The output pins of the Game Boy are coupled through a capacitor, which
+ * acts as a high-pass filter. It removes the DC offset the bipolar DACs
+ * produce, e.g. the pop of a DAC being switched on while its channel is
+ * silent slowly fades back to zero.
+ */
+struct HighPassFilter {
+    capacitor: f32,
+    charge: f32,
+}
+
+impl HighPassFilter {
+    fn new(clocks_per_sample: f64) -> Self {
+        Self {
+            capacitor: 0.0,
+            charge: CHARGE_FACTOR.powf(clocks_per_sample) as f32,
+        }
+    }
+
+    fn set_clocks_per_sample(&mut self, clocks_per_sample: f64) {
+        self.charge = CHARGE_FACTOR.powf(clocks_per_sample) as f32;
+    }
+
+    fn apply(&mut self, input: f32) -> f32 {
+        let output = input - self.capacitor;
+        self.capacitor = input - output * self.charge;
+
+        output
+    }
+}
+
+pub struct OutputStage {
+    left: BlipBuffer,
+    right: BlipBuffer,
+    high_pass: [HighPassFilter; 2],
+    last_output: (f32, f32),
+    sample_rate: u32,
+    // Emulated clock cycles per second, CPU_CLOCK_SPEED times the speed multiplier
+    clock_rate: f64,
+    // Stretches the sample rate slightly to keep the host buffer from running dry or over
+    rate_adjustment: f64,
+    left_samples: Vec<f32>,
+    right_samples: Vec<f32>,
+}
+
+impl OutputStage {
+    pub fn new(sample_rate: u32) -> Self {
+        let clock_rate = CPU_CLOCK_SPEED as f64;
+        let clocks_per_sample = clock_rate / sample_rate as f64;
+
+        Self {
+            left: BlipBuffer::new(clock_rate, sample_rate as f64),
+            right: BlipBuffer::new(clock_rate, sample_rate as f64),
+            high_pass: [
+                HighPassFilter::new(clocks_per_sample),
+                HighPassFilter::new(clocks_per_sample),
+            ],
+            last_output: (0.0, 0.0),
+            sample_rate,
+            clock_rate,
+            rate_adjustment: 1.0,
+            left_samples: Vec::new(),
+            right_samples: Vec::new(),
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.update_rates();
+    }
+
+    // Fast forward squeezes more emulated clock cycles into each output sample
+    pub fn set_speed(&mut self, speed: u8) {
+        let clock_rate = CPU_CLOCK_SPEED as f64 * speed.max(1) as f64;
+        if clock_rate != self.clock_rate {
+            self.clock_rate = clock_rate;
+            self.update_rates();
+        }
+    }
+
+    pub fn set_rate_adjustment(&mut self, adjustment: f64) {
+        if adjustment != self.rate_adjustment {
+            self.rate_adjustment = adjustment;
+            self.update_rates();
+        }
+    }
+
+    fn update_rates(&mut self) {
+        let sample_rate = self.sample_rate as f64;
+        let adjusted_rate = sample_rate * self.rate_adjustment;
+        self.left.set_rates(self.clock_rate, adjusted_rate);
+        self.right.set_rates(self.clock_rate, adjusted_rate);
+
+        // Keeps the cutoff at the same audible frequency while fast forwarding
+        let clocks_per_sample = CPU_CLOCK_SPEED as f64 / sample_rate;
+        for filter in self.high_pass.iter_mut() {
+            filter.set_clocks_per_sample(clocks_per_sample);
+        }
+    }
+
+    // Records the analog output level, which holds until the next update
+    pub fn update(&mut self, left: f32, right: f32) {
+        self.left.add_delta(left - self.last_output.0);
+        self.right.add_delta(right - self.last_output.1);
+        self.last_output = (left, right);
+    }
+
+    pub fn advance(&mut self, t_cycles: u32) {
+        self.left.advance(t_cycles);
+        self.right.advance(t_cycles);
+    }
+
+    // Hands every finished stereo frame to the given sink, in order
+    pub fn drain<F>(&mut self, mut sink: F)
+    where
+        F: FnMut(f32, f32),
+    {
+        let available = self
+            .left
+            .samples_available()
+            .min(self.right.samples_available());
+        if available == 0 {
+            return;
+        }
+
+        self.left.read_samples(available, &mut self.left_samples);
+        self.right.read_samples(available, &mut self.right_samples);
+
+        for (left, right) in self
+            .left_samples
+            .drain(..)
+            .zip(self.right_samples.drain(..))
+        {
+            let left = self.high_pass[0].apply(left);
+            let right = self.high_pass[1].apply(right);
+            sink(left, right);
+        }
+    }
+}
+
+// Converts a sample in the range of -1.0 to 1.0 to 16-bit PCM
+pub fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
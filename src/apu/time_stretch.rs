@@ -0,0 +1,182 @@
+/*
+ * @file    apu/time_stretch.rs
+ * @brief   Fits audio generated at native speed into the time of a fast forward.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::f32::consts::PI;
+
+// Stereo frames handed out at once while fast forwarding
+pub const BLOCK_LENGTH: usize = 512;
+const WINDOW_LENGTH: usize = BLOCK_LENGTH * 2;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FastForwardAudio {
+    // Plays everything faster, which raises the pitch
+    Pitched,
+    // Keeps one block out of every `speed`, pitch stays but the audio jumps
+    DropBlocks,
+    // Overlap-add time compression, pitch stays and the audio flows
+    TimeStretch,
+}
+
+/*
+ * Overlap-add takes windows of two blocks, spaced `speed` blocks apart in
+ * the input, and lays them out one block apart. The Hann windows add up to
+ * one at that overlap, so notes keep their pitch and volume while their
+ * duration shrinks by the speed factor.
+ */
+pub struct TimeStretch {
+    pub mode: FastForwardAudio,
+    speed: u8,
+    window: Vec<f32>,
+    input: Vec<[f32; 2]>,
+    // Second half of the previous window, added to the first half of the next one
+    overlap: Vec<[f32; 2]>,
+    // Input frames to discard before the next window starts
+    skip: usize,
+    block_position: usize,
+    block_index: usize,
+}
+
+impl TimeStretch {
+    pub fn new(mode: FastForwardAudio) -> Self {
+        Self {
+            mode,
+            speed: 1,
+            window: (0..WINDOW_LENGTH)
+                .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / WINDOW_LENGTH as f32).cos())
+                .collect(),
+            input: Vec::with_capacity(WINDOW_LENGTH),
+            overlap: vec![[0.0; 2]; BLOCK_LENGTH],
+            skip: 0,
+            block_position: 0,
+            block_index: 0,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: FastForwardAudio) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.reset();
+        }
+    }
+
+    // Whether samples are generated at the native rate and shortened afterwards
+    pub fn is_active(&self, speed: u8) -> bool {
+        speed > 1 && self.mode != FastForwardAudio::Pitched
+    }
+
+    pub fn push<F>(&mut self, speed: u8, left: f32, right: f32, mut sink: F)
+    where
+        F: FnMut(f32, f32),
+    {
+        if speed != self.speed {
+            self.speed = speed;
+            self.reset();
+        }
+
+        if !self.is_active(speed) {
+            sink(left, right);
+            return;
+        }
+
+        match self.mode {
+            FastForwardAudio::Pitched => unreachable!(),
+            FastForwardAudio::DropBlocks => self.drop_blocks(left, right, sink),
+            FastForwardAudio::TimeStretch => self.overlap_add(left, right, sink),
+        }
+    }
+
+    fn drop_blocks<F>(&mut self, left: f32, right: f32, mut sink: F)
+    where
+        F: FnMut(f32, f32),
+    {
+        if self.block_index == 0 {
+            sink(left, right);
+        }
+
+        self.block_position += 1;
+        if self.block_position == BLOCK_LENGTH {
+            self.block_position = 0;
+            self.block_index = (self.block_index + 1) % self.speed as usize;
+        }
+    }
+
+    fn overlap_add<F>(&mut self, left: f32, right: f32, mut sink: F)
+    where
+        F: FnMut(f32, f32),
+    {
+        if self.skip > 0 {
+            self.skip -= 1;
+            return;
+        }
+
+        self.input.push([left, right]);
+        if self.input.len() < WINDOW_LENGTH {
+            return;
+        }
+
+        for (i, (frame, weight)) in self.input.iter().zip(&self.window).enumerate() {
+            let weighted = [frame[0] * weight, frame[1] * weight];
+
+            if i < BLOCK_LENGTH {
+                let overlap = self.overlap[i];
+                sink(overlap[0] + weighted[0], overlap[1] + weighted[1]);
+            } else {
+                self.overlap[i - BLOCK_LENGTH] = weighted;
+            }
+        }
+
+        let hop = BLOCK_LENGTH * self.speed as usize;
+        let consumed = hop.min(self.input.len());
+        self.input.drain(..consumed);
+        self.skip = hop - consumed;
+    }
+
+    fn reset(&mut self) {
+        self.input.clear();
+        self.overlap.fill([0.0; 2]);
+        self.skip = 0;
+        self.block_position = 0;
+        self.block_index = 0;
+    }
+}
+
+#[cfg(test)]
+mod time_stretch_tests {
+    use super::*;
+
+    fn stretch(mode: FastForwardAudio, speed: u8, input: &[f32]) -> Vec<f32> {
+        let mut time_stretch = TimeStretch::new(mode);
+        let mut output = Vec::new();
+
+        for &sample in input {
+            time_stretch.push(speed, sample, sample, |left, _| output.push(left));
+        }
+
+        output
+    }
+
+    #[test]
+    fn shortens_by_speed() {
+        let input = vec![1.0; BLOCK_LENGTH * 64];
+
+        for mode in [FastForwardAudio::DropBlocks, FastForwardAudio::TimeStretch] {
+            let output = stretch(mode, 4, &input);
+            assert_eq!(output.len(), BLOCK_LENGTH * 16);
+        }
+    }
+
+    #[test]
+    fn overlap_keeps_level() {
+        let input = vec![1.0; BLOCK_LENGTH * 64];
+        let output = stretch(FastForwardAudio::TimeStretch, 3, &input);
+
+        // The first block fades in, every following one overlaps two windows
+        assert!(output[BLOCK_LENGTH..]
+            .iter()
+            .all(|&sample| (sample - 1.0).abs() < 1e-4));
+    }
+}
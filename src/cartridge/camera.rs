@@ -0,0 +1,379 @@
+/*
+ * @file    cartridge/camera.rs
+ * @brief   Game Boy Camera (POCKET CAMERA) cartridge with its image sensor.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use crate::cartridge::{
+    core::CartridgeCore,
+    image_source::{ImageSource, TestPattern, SENSOR_HEIGHT, SENSOR_WIDTH},
+    MemoryBankController, MASK_MSB, RAM_ADDRESS,
+};
+
+// Setting bit 4 of the RAM bank maps the sensor registers instead of RAM
+const REGISTER_BANK: u8 = 0x10;
+const REGISTER_COUNT: usize = 0x36;
+
+const CAPTURE: usize = 0x00;
+const GAIN_AND_EDGE_MODE: usize = 0x01;
+const EXPOSURE_HIGH: usize = 0x02;
+const EXPOSURE_LOW: usize = 0x03;
+const EDGE_RATIO_AND_INVERT: usize = 0x04;
+const DITHER_MATRIX: usize = 0x06;
+
+const CAPTURE_BUSY: u8 = 0x01;
+const N_BIT: u8 = 0x80;
+const INVERT: u8 = 0x08;
+
+// Exposure that passes the sensor brightness through unchanged
+const NEUTRAL_EXPOSURE: i32 = 0x1000;
+const EDGE_RATIOS: [f32; 8] = [0.5, 0.75, 1.0, 1.25, 2.0, 3.0, 4.0, 5.0];
+
+// The sensor writes its picture as tiles to the first RAM bank
+const CAPTURE_ADDRESS: usize = 0x0100;
+const TILE_BYTES: usize = 16;
+
+// Photos in the camera's 128 KiB save, each one in a 4 KiB slot from 0x2000
+pub const PHOTO_COUNT: usize = 30;
+pub const PHOTO_START: usize = 0x2000;
+pub const PHOTO_SLOT_SIZE: usize = 0x1000;
+pub const PHOTO_WIDTH: usize = SENSOR_WIDTH;
+pub const PHOTO_HEIGHT: usize = SENSOR_HEIGHT;
+
+/*
+ * The camera ROM controls the sensor through registers mapped into RAM:
+ * exposure time, edge enhancement and a 4x4 matrix of three thresholds
+ * per pixel that dithers the analog image down to four shades. Writing
+ * bit 0 of the first register starts a capture, which keeps the sensor
+ * busy for a while before the tiles show up in RAM.
+ */
+pub struct PocketCamera {
+    core: CartridgeCore,
+    registers: [u8; REGISTER_COUNT],
+    registers_mapped: bool,
+    // M-cycles until the running capture finishes
+    capture_cycles: u32,
+    source: Box<dyn ImageSource>,
+}
+
+impl PocketCamera {
+    pub fn new(core: CartridgeCore) -> Self {
+        Self {
+            core,
+            registers: [0; REGISTER_COUNT],
+            registers_mapped: false,
+            capture_cycles: 0,
+            source: Box::new(TestPattern::new()),
+        }
+    }
+
+    fn ram_address(&self, ram_size: usize, address: u16) -> usize {
+        let offset = self.core.ram_offset * self.core.ram_bank as usize;
+        ((address as usize - RAM_ADDRESS) + offset) % ram_size
+    }
+
+    fn exposure(&self) -> u16 {
+        u16::from_be_bytes([self.registers[EXPOSURE_HIGH], self.registers[EXPOSURE_LOW]])
+    }
+
+    // Exposure is counted in steps of 16 M-cycles, on top of reading out the sensor
+    fn capture_duration(&self) -> u32 {
+        let readout = if self.registers[GAIN_AND_EDGE_MODE] & N_BIT != 0 {
+            32446
+        } else {
+            32446 + 512
+        };
+
+        readout + 16 * self.exposure() as u32
+    }
+
+    fn finish_capture(&mut self) {
+        self.registers[CAPTURE] &= !CAPTURE_BUSY;
+
+        let shades = process(&self.registers, &self.source.capture());
+        if let Some(ref mut ram_data) = self.core.ram_data {
+            write_tiles(&shades, &mut ram_data[CAPTURE_ADDRESS..]);
+            self.core.ram_changed = true;
+        }
+    }
+}
+
+impl MemoryBankController for PocketCamera {
+    fn read_rom(&self, address: u16) -> u8 {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF (Bank 00)
+            0x0..=0x3 => self.core.rom_data[address as usize],
+            // 0x4000 - 0x7FFF (Bank 00-3F)
+            0x4..=0x7 => {
+                let offset = self.core.rom_offset * self.core.rom_bank as usize;
+                self.core.rom_data[(address as usize - self.core.rom_offset) + offset]
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x1FFF (RAM write enable)
+            0x0 | 0x1 => self.core.ram_enabled = value & 0x0F == 0x0A,
+            // 0x2000 - 0x3FFF (ROM bank number)
+            0x2 | 0x3 => self.core.rom_bank = (value & 0x3F) as u16,
+            // 0x4000 - 0x5FFF (RAM bank number or sensor registers)
+            0x4 | 0x5 => {
+                self.registers_mapped = value & REGISTER_BANK != 0;
+                self.core.ram_bank = value & 0x0F;
+            }
+            0x6 | 0x7 => {}
+            _ => unreachable!(),
+        }
+
+        self.core.set_rom_bank();
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        // Only the capture register can be read back, the others are write-only
+        if self.registers_mapped {
+            return match address & 0x7F {
+                0x00 => self.registers[CAPTURE],
+                _ => 0x00,
+            };
+        }
+
+        // RAM is readable without being enabled, but not while the sensor writes to it
+        if self.capture_cycles > 0 {
+            return 0x00;
+        }
+
+        match self.core.ram_data {
+            Some(ref ram_data) => ram_data[self.ram_address(ram_data.len(), address)],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if self.registers_mapped {
+            let index = (address & 0x7F) as usize;
+
+            match index {
+                CAPTURE => {
+                    if value & CAPTURE_BUSY != 0 && self.capture_cycles == 0 {
+                        self.capture_cycles = self.capture_duration();
+                    }
+
+                    let busy = self.registers[CAPTURE] & CAPTURE_BUSY;
+                    self.registers[CAPTURE] = (value & 0x06) | busy | (value & CAPTURE_BUSY);
+                }
+                1..REGISTER_COUNT => self.registers[index] = value,
+                _ => {}
+            }
+
+            return;
+        }
+
+        if !self.core.ram_enabled {
+            return;
+        }
+
+        let ram_address = match self.core.ram_data {
+            Some(ref ram_data) => self.ram_address(ram_data.len(), address),
+            None => return,
+        };
+
+        self.core.write_ram_data(ram_address, value);
+    }
+
+    fn load_ram(&mut self, ram_data: Vec<u8>) {
+        self.core.ram_data = Some(ram_data);
+    }
+
+    fn save_ram(&self) -> Option<Vec<u8>> {
+        self.core.ram_data.clone()
+    }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
+
+    fn tick(&mut self, m_cycles: u8) {
+        if self.capture_cycles == 0 {
+            return;
+        }
+
+        self.capture_cycles = self.capture_cycles.saturating_sub(m_cycles as u32);
+        if self.capture_cycles == 0 {
+            self.finish_capture();
+        }
+    }
+
+    fn set_image_source(&mut self, source: Box<dyn ImageSource>) {
+        self.source = source;
+    }
+}
+
+/*
+ * Turns the sensor brightness into shades from 0 (white) to 3 (black):
+ * scales it by the exposure, sharpens it against its neighbours when edge
+ * enhancement is on and compares it to the dither matrix thresholds.
+ */
+fn process(registers: &[u8; REGISTER_COUNT], pixels: &[u8]) -> Vec<u8> {
+    let exposure = u16::from_be_bytes([registers[EXPOSURE_HIGH], registers[EXPOSURE_LOW]]);
+    let exposed = pixels
+        .iter()
+        .map(|&pixel| (pixel as i32 * exposure as i32 / NEUTRAL_EXPOSURE) as f32)
+        .collect::<Vec<f32>>();
+
+    let edge_mode = (registers[GAIN_AND_EDGE_MODE] >> 5) & 0b11;
+    let edge_ratio = EDGE_RATIOS[((registers[EDGE_RATIO_AND_INVERT] >> 4) & 0b111) as usize];
+    let invert = registers[EDGE_RATIO_AND_INVERT] & INVERT != 0;
+
+    let pixel = |x: isize, y: isize| {
+        let x = x.clamp(0, SENSOR_WIDTH as isize - 1) as usize;
+        let y = y.clamp(0, SENSOR_HEIGHT as isize - 1) as usize;
+        exposed[y * SENSOR_WIDTH + x]
+    };
+
+    (0..SENSOR_HEIGHT)
+        .flat_map(|y| (0..SENSOR_WIDTH).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let (x, y) = (x as isize, y as isize);
+            let mut value = pixel(x, y);
+
+            // 1: horizontal neighbours, 2: vertical neighbours, 3: both
+            let mut edge = 0.0;
+            if edge_mode & 0b01 != 0 {
+                edge += 2.0 * value - pixel(x - 1, y) - pixel(x + 1, y);
+            }
+            if edge_mode & 0b10 != 0 {
+                edge += 2.0 * value - pixel(x, y - 1) - pixel(x, y + 1);
+            }
+            value += edge * edge_ratio;
+
+            let mut value = value.clamp(0.0, 255.0) as u8;
+            if invert {
+                value = 255 - value;
+            }
+
+            let matrix = DITHER_MATRIX + ((y as usize & 3) * 4 + (x as usize & 3)) * 3;
+            let thresholds = &registers[matrix..matrix + 3];
+
+            match thresholds.iter().position(|&threshold| value < threshold) {
+                Some(index) => 3 - index as u8,
+                None => 0,
+            }
+        })
+        .collect()
+}
+
+// Stores 2-bit shades as 16x14 tiles of 2bpp data
+fn write_tiles(shades: &[u8], tile_data: &mut [u8]) {
+    for (index, &shade) in shades.iter().enumerate() {
+        let (x, y) = (index % SENSOR_WIDTH, index / SENSOR_WIDTH);
+        let tile = (y / 8) * (SENSOR_WIDTH / 8) + x / 8;
+        let address = tile * TILE_BYTES + (y % 8) * 2;
+        let bit = 7 - (x % 8);
+
+        tile_data[address] &= !(1 << bit);
+        tile_data[address] |= (shade & 0b01) << bit;
+        tile_data[address + 1] &= !(1 << bit);
+        tile_data[address + 1] |= ((shade & 0b10) >> 1) << bit;
+    }
+}
+
+// Shades of one photo slot in a camera save, same layout as a capture
+pub fn read_photo(save: &[u8], slot: usize) -> Option<Vec<u8>> {
+    let start = PHOTO_START + slot * PHOTO_SLOT_SIZE;
+    let tile_data = save.get(start..start + PHOTO_WIDTH * PHOTO_HEIGHT / 4)?;
+
+    let shades = (0..PHOTO_WIDTH * PHOTO_HEIGHT)
+        .map(|index| {
+            let (x, y) = (index % PHOTO_WIDTH, index / PHOTO_WIDTH);
+            let tile = (y / 8) * (PHOTO_WIDTH / 8) + x / 8;
+            let address = tile * TILE_BYTES + (y % 8) * 2;
+            let bit = 7 - (x % 8);
+
+            let low = (tile_data[address] >> bit) & 1;
+            let high = (tile_data[address + 1] >> bit) & 1;
+            (high << 1) | low
+        })
+        .collect();
+
+    Some(shades)
+}
+
+#[cfg(test)]
+mod camera_tests {
+    use super::*;
+
+    struct Flat(u8);
+
+    impl ImageSource for Flat {
+        fn capture(&mut self) -> Vec<u8> {
+            vec![self.0; SENSOR_WIDTH * SENSOR_HEIGHT]
+        }
+    }
+
+    fn registers(exposure: u16) -> [u8; REGISTER_COUNT] {
+        let mut registers = [0; REGISTER_COUNT];
+        [registers[EXPOSURE_HIGH], registers[EXPOSURE_LOW]] = exposure.to_be_bytes();
+
+        for matrix in registers[DITHER_MATRIX..].chunks_mut(3) {
+            matrix.copy_from_slice(&[0x40, 0x80, 0xC0]);
+        }
+
+        registers
+    }
+
+    #[test]
+    fn dithers_by_threshold() {
+        let pixels = [0x00, 0x50, 0xA0, 0xF0].repeat(SENSOR_WIDTH * SENSOR_HEIGHT / 4);
+        let shades = process(&registers(NEUTRAL_EXPOSURE as u16), &pixels);
+
+        assert_eq!(shades[..4], [3, 2, 1, 0]);
+
+        // Half the exposure darkens the picture
+        let shades = process(&registers(NEUTRAL_EXPOSURE as u16 / 2), &pixels);
+        assert_eq!(shades[..4], [3, 3, 2, 2]);
+    }
+
+    #[test]
+    fn tiles_round_trip() {
+        let shades = (0..SENSOR_WIDTH * SENSOR_HEIGHT)
+            .map(|index| (index % 7 % 4) as u8)
+            .collect::<Vec<u8>>();
+
+        let mut save = vec![0; PHOTO_START + PHOTO_SLOT_SIZE];
+        write_tiles(&shades, &mut save[PHOTO_START..]);
+
+        assert_eq!(read_photo(&save, 0), Some(shades));
+        assert_eq!(read_photo(&save, 1), None);
+    }
+
+    #[test]
+    fn capture_takes_time() {
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[0x147] = 0xFC;
+        rom_data[0x149] = 0x04;
+
+        let header = crate::cartridge::header::CartridgeHeader::parse(&rom_data).unwrap();
+        let mut camera = PocketCamera::new(CartridgeCore::new(&rom_data, &header));
+        camera.set_image_source(Box::new(Flat(0x00)));
+
+        camera.write_rom(0x4000, REGISTER_BANK);
+        for (index, value) in registers(NEUTRAL_EXPOSURE as u16).iter().enumerate() {
+            camera.write_ram(RAM_ADDRESS as u16 + index as u16, *value);
+        }
+        camera.write_ram(0xA000, CAPTURE_BUSY);
+        assert_eq!(camera.read_ram(0xA000) & CAPTURE_BUSY, CAPTURE_BUSY);
+
+        for _ in 0..camera.capture_duration() {
+            camera.tick(1);
+        }
+        assert_eq!(camera.read_ram(0xA000) & CAPTURE_BUSY, 0);
+
+        // A black picture is all dark tiles
+        camera.write_rom(0x4000, 0x00);
+        assert_eq!(camera.read_ram(0xA100), 0xFF);
+        assert_eq!(camera.read_ram(0xA101), 0xFF);
+    }
+}
@@ -5,7 +5,7 @@
  * @date    September 20, 2023
  */
 
-use crate::cartridge::{CARTRIDGE_TYPE_ADDRESS, RAM_BANK_SIZE, RAM_SIZE_ADDRESS, ROM_BANK_SIZE};
+use crate::cartridge::{header::CartridgeHeader, RAM_BANK_SIZE, ROM_BANK_SIZE};
 
 pub struct CartridgeCore {
     pub rom_data: Vec<u8>,
@@ -15,17 +15,21 @@ pub struct CartridgeCore {
     pub rom_offset: usize,
     pub ram_offset: usize,
     pub ram_enabled: bool,
+    // Battery backed storage changed since the last take_ram_changed
+    pub ram_changed: bool,
 }
 
 impl CartridgeCore {
-    pub fn new(rom_data: &[u8]) -> Self {
-        let ram_data = match rom_data[CARTRIDGE_TYPE_ADDRESS] {
+    pub fn new(rom_data: &[u8], header: &CartridgeHeader) -> Self {
+        let ram_data = match header.cartridge_type {
             // Mbc2 internal ram
             0x05 | 0x06 => {
                 let ram_size = Some(512);
                 ram_size.map(|size| vec![0; size])
             }
-            _ => create_ram(rom_data),
+            // Mbc6 has 32 KiB, whatever the header says
+            0x20 => Some(vec![0; 32 * 1024]),
+            _ => header.ram_size_bytes().map(|size| vec![0; size]),
         };
         let rom_bank = 1;
         let ram_bank = 0;
@@ -41,6 +45,7 @@ impl CartridgeCore {
             rom_offset,
             ram_offset,
             ram_enabled,
+            ram_changed: false,
         }
     }
 
@@ -50,18 +55,18 @@ impl CartridgeCore {
             self.rom_bank = (self.rom_bank as usize % max_banks) as u16;
         }
     }
-}
 
-fn create_ram(rom_data: &[u8]) -> Option<Vec<u8>> {
-    let ram_size = match rom_data[RAM_SIZE_ADDRESS] {
-        0x00 => None,
-        0x01 => None,
-        0x02 => Some(8 * 1024),
-        0x03 => Some(32 * 1024),
-        0x04 => Some(128 * 1024),
-        0x05 => Some(64 * 1024),
-        _ => None,
-    };
+    // Writes the byte and remembers whether the RAM content changed
+    pub fn write_ram_data(&mut self, index: usize, value: u8) {
+        if let Some(ref mut ram_data) = self.ram_data {
+            if ram_data[index] != value {
+                ram_data[index] = value;
+                self.ram_changed = true;
+            }
+        }
+    }
 
-    ram_size.map(|size| vec![0; size])
+    pub fn take_ram_changed(&mut self) -> bool {
+        std::mem::take(&mut self.ram_changed)
+    }
 }
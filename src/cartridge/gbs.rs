@@ -0,0 +1,290 @@
+/*
+ * @file    cartridge/gbs.rs
+ * @brief   Turns GBS (Game Boy Sound System) rips into a playable ROM image.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::error::Error;
+
+use crate::cartridge::{
+    core::CartridgeCore, MemoryBankController, CARTRIDGE_TYPE_ADDRESS, MASK_MSB, RAM_ADDRESS,
+    RAM_SIZE_ADDRESS, ROM_BANK_SIZE,
+};
+
+const MAGIC: &[u8; 3] = b"GBS";
+const HEADER_SIZE: usize = 0x70;
+const STRING_SIZE: usize = 32;
+
+// Not assigned by Nintendo, marks ROM images built from GBS files
+pub const GBS_CARTRIDGE_TYPE: u8 = 0xE0;
+
+const ENTRY_POINT: usize = 0x0100;
+const DRIVER_ADDRESS: u16 = 0x0150;
+const VBLANK_VECTOR: usize = 0x0040;
+const TIMER_VECTOR: usize = 0x0050;
+const UNUSED_VECTORS: [usize; 3] = [0x0048, 0x0058, 0x0060];
+
+const TAC_ENABLE_MASK: u8 = 0x04;
+const VBLANK_INTERRUPT: u8 = 0x01;
+const TIMER_INTERRUPT: u8 = 0x04;
+
+// Opcodes used by the driver
+const NOP: u8 = 0x00;
+const JP_NN: u8 = 0xC3;
+const CALL_NN: u8 = 0xCD;
+const RETI: u8 = 0xD9;
+const DI: u8 = 0xF3;
+const EI: u8 = 0xFB;
+const HALT: u8 = 0x76;
+const JR_E: u8 = 0x18;
+const LD_SP_NN: u8 = 0x31;
+const LD_A_N: u8 = 0x3E;
+const LDH_N_A: u8 = 0xE0;
+const XOR_A: u8 = 0xAF;
+
+// https://ocremix.org/info/GBS_Format_Specification
+#[derive(Clone)]
+pub struct GbsHeader {
+    pub song_count: u8,
+    // 1-based
+    pub first_song: u8,
+    pub load_address: u16,
+    pub init_address: u16,
+    pub play_address: u16,
+    pub stack_pointer: u16,
+    pub timer_modulo: u8,
+    pub timer_control: u8,
+    pub title: String,
+    pub author: String,
+    pub copyright: String,
+}
+
+impl GbsHeader {
+    pub fn parse(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        if !is_gbs(data) || data.len() <= HEADER_SIZE {
+            return Err("Invalid GBS file".into());
+        }
+
+        let word = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let string = |offset: usize| {
+            let bytes = &data[offset..offset + STRING_SIZE];
+            let end = bytes
+                .iter()
+                .position(|&byte| byte == 0)
+                .unwrap_or(STRING_SIZE);
+            String::from_utf8_lossy(&bytes[..end]).into_owned()
+        };
+
+        let header = Self {
+            song_count: data[0x04],
+            first_song: data[0x05].max(1),
+            load_address: word(0x06),
+            init_address: word(0x08),
+            play_address: word(0x0A),
+            stack_pointer: word(0x0C),
+            timer_modulo: data[0x0E],
+            timer_control: data[0x0F],
+            title: string(0x10),
+            author: string(0x30),
+            copyright: string(0x50),
+        };
+
+        if header.song_count == 0 {
+            return Err("GBS file contains no songs".into());
+        }
+
+        if header.load_address < 0x0400 || header.load_address >= 0x8000 {
+            return Err(format!("Invalid GBS load address: {:#06X}", header.load_address).into());
+        }
+
+        Ok(header)
+    }
+
+    // Interrupt the play routine is called from
+    fn interrupt(&self) -> u8 {
+        if self.timer_control & TAC_ENABLE_MASK != 0 {
+            TIMER_INTERRUPT
+        } else {
+            VBLANK_INTERRUPT
+        }
+    }
+}
+
+pub fn is_gbs(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/*
+ * The music data is placed at its load address, the following banks are
+ * appended as they are. Below the load address sits a small driver: RST
+ * vectors jump into the rip, the VBlank and timer vectors call the play
+ * routine, and the entry point sets up the timer, calls init with the song
+ * number in A and halts until the next interrupt.
+ */
+pub fn build_image(data: &[u8], header: &GbsHeader, song: u8) -> Vec<u8> {
+    let music = &data[HEADER_SIZE..];
+    let load_address = header.load_address as usize;
+    let size = (load_address + music.len())
+        .next_multiple_of(ROM_BANK_SIZE)
+        .max(ROM_BANK_SIZE * 2);
+
+    let mut image = vec![0; size];
+    image[load_address..load_address + music.len()].copy_from_slice(music);
+
+    for vector in (0x00..0x40).step_by(8) {
+        let [low, high] = (header.load_address + vector as u16).to_le_bytes();
+        image[vector..vector + 3].copy_from_slice(&[JP_NN, low, high]);
+    }
+
+    let [play_low, play_high] = header.play_address.to_le_bytes();
+    for vector in [VBLANK_VECTOR, TIMER_VECTOR] {
+        image[vector..vector + 4].copy_from_slice(&[CALL_NN, play_low, play_high, RETI]);
+    }
+    for vector in UNUSED_VECTORS {
+        image[vector] = RETI;
+    }
+
+    let [driver_low, driver_high] = DRIVER_ADDRESS.to_le_bytes();
+    image[ENTRY_POINT..ENTRY_POINT + 4].copy_from_slice(&[NOP, JP_NN, driver_low, driver_high]);
+    image[CARTRIDGE_TYPE_ADDRESS] = GBS_CARTRIDGE_TYPE;
+    // 8 KiB
+    image[RAM_SIZE_ADDRESS] = 0x02;
+
+    let [sp_low, sp_high] = header.stack_pointer.to_le_bytes();
+    let [init_low, init_high] = header.init_address.to_le_bytes();
+    // Bit 7 of the timer control selects CGB double speed, which isn't supported
+    let timer_control = header.timer_control & 0x07;
+
+    #[rustfmt::skip]
+    let driver = [
+        DI,
+        LD_SP_NN, sp_low, sp_high,
+        // TIMA starts at the modulo as well, so the first call isn't delayed
+        LD_A_N, header.timer_modulo,
+        LDH_N_A, 0x05,
+        LDH_N_A, 0x06,
+        LD_A_N, timer_control,
+        LDH_N_A, 0x07,
+        LD_A_N, song,
+        CALL_NN, init_low, init_high,
+        XOR_A,
+        LDH_N_A, 0x0F,
+        LD_A_N, header.interrupt(),
+        LDH_N_A, 0xFF,
+        EI,
+        HALT,
+        JR_E, 0xFD,
+    ];
+
+    let driver_address = DRIVER_ADDRESS as usize;
+    image[driver_address..driver_address + driver.len()].copy_from_slice(&driver);
+
+    image
+}
+
+/*
+ * GBS rips switch banks by writing to 0x2000-0x3FFF, much like MBC1 but
+ * without any of its modes. The 8 KiB of external RAM are always accessible
+ * and never saved.
+ */
+pub struct Gbs {
+    core: CartridgeCore,
+}
+
+impl Gbs {
+    pub fn new(core: CartridgeCore) -> Self {
+        Self { core }
+    }
+}
+
+impl MemoryBankController for Gbs {
+    fn read_rom(&self, address: u16) -> u8 {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF (Bank 00)
+            0x0..=0x3 => self.core.rom_data[address as usize],
+            // 0x4000 - 0x7FFF (Switchable bank)
+            0x4..=0x7 => {
+                let offset = self.core.rom_offset * self.core.rom_bank as usize;
+                self.core.rom_data[(address as usize - self.core.rom_offset) + offset]
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        // 0x2000 - 0x3FFF (ROM bank number)
+        if let 0x2 | 0x3 = (address & MASK_MSB) >> 12 {
+            self.core.rom_bank = value.max(1) as u16;
+            self.core.set_rom_bank();
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match self.core.ram_data {
+            Some(ref ram_data) => ram_data[address as usize - RAM_ADDRESS],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        self.core
+            .write_ram_data(address as usize - RAM_ADDRESS, value);
+    }
+
+    fn load_ram(&mut self, _ram_data: Vec<u8>) {}
+
+    fn save_ram(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    // Nothing is saved
+    fn take_ram_changed(&mut self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod gbs_tests {
+    use super::*;
+
+    fn gbs_file() -> Vec<u8> {
+        let mut data = vec![0; HEADER_SIZE + 0x10];
+        data[0..4].copy_from_slice(b"GBS\x01");
+        data[0x04] = 3;
+        data[0x05] = 1;
+        data[0x06..0x08].copy_from_slice(&0x0400u16.to_le_bytes());
+        data[0x08..0x0A].copy_from_slice(&0x0400u16.to_le_bytes());
+        data[0x0A..0x0C].copy_from_slice(&0x0408u16.to_le_bytes());
+        data[0x0C..0x0E].copy_from_slice(&0xDFFFu16.to_le_bytes());
+        data[0x10..0x15].copy_from_slice(b"Title");
+        data[HEADER_SIZE] = 0xC9;
+
+        data
+    }
+
+    #[test]
+    fn parse_header() {
+        let header = GbsHeader::parse(&gbs_file()).unwrap();
+
+        assert_eq!(header.song_count, 3);
+        assert_eq!(header.play_address, 0x0408);
+        assert_eq!(header.title, "Title");
+        assert_eq!(header.interrupt(), VBLANK_INTERRUPT);
+    }
+
+    #[test]
+    fn image_layout() {
+        let data = gbs_file();
+        let header = GbsHeader::parse(&data).unwrap();
+        let image = build_image(&data, &header, 2);
+
+        assert_eq!(image.len(), ROM_BANK_SIZE * 2);
+        assert_eq!(image[0x0400], 0xC9);
+        assert_eq!(
+            &image[VBLANK_VECTOR..VBLANK_VECTOR + 4],
+            &[CALL_NN, 0x08, 0x04, RETI]
+        );
+        assert_eq!(image[CARTRIDGE_TYPE_ADDRESS], GBS_CARTRIDGE_TYPE);
+    }
+}
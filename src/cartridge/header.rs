@@ -0,0 +1,497 @@
+/*
+ * @file    cartridge/header.rs
+ * @brief   Parses the cartridge header at 0x0100-0x014F.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::error::Error;
+
+use crate::cartridge::{gbs::GBS_CARTRIDGE_TYPE, CARTRIDGE_TYPE_ADDRESS, RAM_SIZE_ADDRESS};
+
+const TITLE_START: usize = 0x0134;
+const TITLE_END: usize = 0x0143;
+const MANUFACTURER_CODE_START: usize = 0x013F;
+const CGB_FLAG_ADDRESS: usize = 0x0143;
+const NEW_LICENSEE_CODE_ADDRESS: usize = 0x0144;
+const SGB_FLAG_ADDRESS: usize = 0x0146;
+const ROM_SIZE_ADDRESS: usize = 0x0148;
+const DESTINATION_CODE_ADDRESS: usize = 0x014A;
+const OLD_LICENSEE_CODE_ADDRESS: usize = 0x014B;
+const VERSION_ADDRESS: usize = 0x014C;
+const HEADER_CHECKSUM_ADDRESS: usize = 0x014D;
+const GLOBAL_CHECKSUM_ADDRESS: usize = 0x014E;
+const HEADER_END: usize = 0x014F;
+
+// Tells to look at the new licensee code instead
+const USE_NEW_LICENSEE: u8 = 0x33;
+
+// https://gbdev.io/pandocs/The_Cartridge_Header.html
+pub struct CartridgeHeader {
+    pub title: String,
+    pub manufacturer_code: Option<String>,
+    pub cgb_flag: u8,
+    pub sgb_flag: u8,
+    pub old_licensee_code: u8,
+    pub new_licensee_code: String,
+    pub cartridge_type: u8,
+    pub rom_size: u8,
+    pub ram_size: u8,
+    pub destination_code: u8,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+    computed_header_checksum: u8,
+    computed_global_checksum: u16,
+}
+
+impl CartridgeHeader {
+    pub fn parse(rom_data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        if rom_data.len() <= HEADER_END {
+            return Err("ROM is too small to contain a cartridge header".into());
+        }
+
+        let cgb_flag = rom_data[CGB_FLAG_ADDRESS];
+
+        // Later games shortened the title to make room for the CGB flag and a manufacturer code
+        let manufacturer_code = &rom_data[MANUFACTURER_CODE_START..CGB_FLAG_ADDRESS];
+        let has_manufacturer_code = cgb_flag & 0x80 != 0
+            && manufacturer_code
+                .iter()
+                .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit());
+
+        let title_end = if has_manufacturer_code {
+            MANUFACTURER_CODE_START
+        } else if cgb_flag & 0x80 != 0 {
+            CGB_FLAG_ADDRESS
+        } else {
+            TITLE_END + 1
+        };
+
+        Ok(Self {
+            title: ascii_string(&rom_data[TITLE_START..title_end]),
+            manufacturer_code: has_manufacturer_code.then(|| ascii_string(manufacturer_code)),
+            cgb_flag,
+            sgb_flag: rom_data[SGB_FLAG_ADDRESS],
+            old_licensee_code: rom_data[OLD_LICENSEE_CODE_ADDRESS],
+            new_licensee_code: ascii_string(
+                &rom_data[NEW_LICENSEE_CODE_ADDRESS..NEW_LICENSEE_CODE_ADDRESS + 2],
+            ),
+            cartridge_type: rom_data[CARTRIDGE_TYPE_ADDRESS],
+            rom_size: rom_data[ROM_SIZE_ADDRESS],
+            ram_size: rom_data[RAM_SIZE_ADDRESS],
+            destination_code: rom_data[DESTINATION_CODE_ADDRESS],
+            version: rom_data[VERSION_ADDRESS],
+            header_checksum: rom_data[HEADER_CHECKSUM_ADDRESS],
+            global_checksum: u16::from_be_bytes([
+                rom_data[GLOBAL_CHECKSUM_ADDRESS],
+                rom_data[GLOBAL_CHECKSUM_ADDRESS + 1],
+            ]),
+            computed_header_checksum: header_checksum(rom_data),
+            computed_global_checksum: global_checksum(rom_data),
+        })
+    }
+
+    // Checked by the boot ROM, which locks up on a mismatch
+    pub fn header_checksum_valid(&self) -> bool {
+        self.header_checksum == self.computed_header_checksum
+    }
+
+    // Never checked by the hardware, but a mismatch hints at a bad dump or a patched ROM
+    pub fn global_checksum_valid(&self) -> bool {
+        self.global_checksum == self.computed_global_checksum
+    }
+
+    // GBS rips are wrapped in a generated image without a real header
+    pub fn is_gbs(&self) -> bool {
+        self.cartridge_type == GBS_CARTRIDGE_TYPE
+    }
+
+    // Whether the cartridge keeps its RAM, or flash on MBC6, when switched off
+    pub fn has_battery(&self) -> bool {
+        matches!(
+            self.cartridge_type,
+            0x03 | 0x06 | 0x09 | 0x0D | 0x0F | 0x10 | 0x13 | 0x1B | 0x1E | 0x20 | 0x22 | 0xFC
+                ..=0xFF
+        )
+    }
+
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if self.is_gbs() {
+            return warnings;
+        }
+
+        if !self.header_checksum_valid() {
+            warnings.push(format!(
+                "Header checksum mismatch: expected {:#04X}, found {:#04X}",
+                self.computed_header_checksum, self.header_checksum
+            ));
+        }
+
+        if !self.global_checksum_valid() {
+            warnings.push(format!(
+                "Global checksum mismatch: expected {:#06X}, found {:#06X}",
+                self.computed_global_checksum, self.global_checksum
+            ));
+        }
+
+        warnings
+    }
+
+    pub fn cgb_support(&self) -> &'static str {
+        match self.cgb_flag {
+            0x80 => "Supported",
+            0xC0 => "Required",
+            _ => "None",
+        }
+    }
+
+    pub fn sgb_support(&self) -> bool {
+        self.sgb_flag == 0x03
+    }
+
+    pub fn licensee(&self) -> &'static str {
+        if self.old_licensee_code == USE_NEW_LICENSEE {
+            new_licensee_name(&self.new_licensee_code)
+        } else {
+            old_licensee_name(self.old_licensee_code)
+        }
+    }
+
+    pub fn cartridge_type_name(&self) -> &'static str {
+        match self.cartridge_type {
+            0x00 => "ROM ONLY",
+            0x01 => "MBC1",
+            0x02 => "MBC1+RAM",
+            0x03 => "MBC1+RAM+BATTERY",
+            0x05 => "MBC2",
+            0x06 => "MBC2+BATTERY",
+            0x08 => "ROM+RAM",
+            0x09 => "ROM+RAM+BATTERY",
+            0x0B => "MMM01",
+            0x0C => "MMM01+RAM",
+            0x0D => "MMM01+RAM+BATTERY",
+            0x0F => "MBC3+TIMER+BATTERY",
+            0x10 => "MBC3+TIMER+RAM+BATTERY",
+            0x11 => "MBC3",
+            0x12 => "MBC3+RAM",
+            0x13 => "MBC3+RAM+BATTERY",
+            0x19 => "MBC5",
+            0x1A => "MBC5+RAM",
+            0x1B => "MBC5+RAM+BATTERY",
+            0x1C => "MBC5+RUMBLE",
+            0x1D => "MBC5+RUMBLE+RAM",
+            0x1E => "MBC5+RUMBLE+RAM+BATTERY",
+            0x20 => "MBC6",
+            0x22 => "MBC7+SENSOR+RUMBLE+RAM+BATTERY",
+            GBS_CARTRIDGE_TYPE => "GBS",
+            0xFC => "POCKET CAMERA",
+            0xFD => "BANDAI TAMA5",
+            0xFE => "HuC3",
+            0xFF => "HuC1+RAM+BATTERY",
+            _ => "Unknown",
+        }
+    }
+
+    // In bytes, 32 KiB doubled for every step
+    pub fn rom_size_bytes(&self) -> Option<usize> {
+        (self.rom_size <= 0x08).then(|| (32 * 1024) << self.rom_size)
+    }
+
+    // In bytes, MBC2 has its RAM built in and reports none here
+    pub fn ram_size_bytes(&self) -> Option<usize> {
+        match self.ram_size {
+            0x02 => Some(8 * 1024),
+            0x03 => Some(32 * 1024),
+            0x04 => Some(128 * 1024),
+            0x05 => Some(64 * 1024),
+            _ => None,
+        }
+    }
+
+    pub fn destination(&self) -> &'static str {
+        match self.destination_code {
+            0x00 => "Japan",
+            _ => "Overseas",
+        }
+    }
+}
+
+fn ascii_string(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|&&byte| byte != 0)
+        .map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '?'
+            }
+        })
+        .collect::<String>()
+        .trim_end()
+        .to_owned()
+}
+
+fn header_checksum(rom_data: &[u8]) -> u8 {
+    rom_data[TITLE_START..=VERSION_ADDRESS]
+        .iter()
+        .fold(0u8, |checksum, byte| {
+            checksum.wrapping_sub(*byte).wrapping_sub(1)
+        })
+}
+
+// Sum of every byte in the ROM except the global checksum itself
+pub fn global_checksum(rom_data: &[u8]) -> u16 {
+    rom_data
+        .iter()
+        .enumerate()
+        .filter(|(address, _)| !(GLOBAL_CHECKSUM_ADDRESS..=HEADER_END).contains(address))
+        .fold(0u16, |checksum, (_, byte)| {
+            checksum.wrapping_add(*byte as u16)
+        })
+}
+
+fn old_licensee_name(code: u8) -> &'static str {
+    match code {
+        0x00 => "None",
+        0x01 | 0x31 => "Nintendo",
+        0x08 | 0x38 => "Capcom",
+        0x09 => "HOT-B",
+        0x0A | 0xE0 => "Jaleco",
+        0x0B => "Coconuts Japan",
+        0x0C | 0x6E => "Elite Systems",
+        0x13 | 0x69 => "Electronic Arts",
+        0x18 => "Hudson Soft",
+        0x19 => "ITC Entertainment",
+        0x1A => "Yanoman",
+        0x1D => "Japan Clary",
+        0x1F | 0x4A | 0x61 => "Virgin Games",
+        0x24 => "PCM Complete",
+        0x25 => "San-X",
+        0x28 | 0x7F | 0x97 | 0xC2 => "Kemco",
+        0x29 => "SETA Corporation",
+        0x30 | 0x70 => "Infogrames",
+        0x32 | 0xA2 | 0xB2 => "Bandai",
+        0x34 | 0xA4 => "Konami",
+        0x35 => "HectorSoft",
+        0x39 | 0x9D | 0xD9 => "Banpresto",
+        0x3C => "Entertainment Interactive",
+        0x3E => "Gremlin",
+        0x41 => "Ubi Soft",
+        0x42 | 0xEB => "Atlus",
+        0x44 | 0x4D => "Malibu Interactive",
+        0x46 | 0xCF => "Angel",
+        0x47 => "Spectrum HoloByte",
+        0x49 => "Irem",
+        0x4F => "U.S. Gold",
+        0x50 => "Absolute",
+        0x51 | 0xB0 => "Acclaim Entertainment",
+        0x52 => "Activision",
+        0x53 => "Sammy USA Corporation",
+        0x54 => "GameTek",
+        0x55 => "Park Place",
+        0x56 | 0xDB | 0xFF => "LJN",
+        0x57 => "Matchbox",
+        0x59 => "Milton Bradley Company",
+        0x5A => "Mindscape",
+        0x5B => "Romstar",
+        0x5C | 0xD6 => "Naxat Soft",
+        0x5D => "Tradewest",
+        0x60 => "Titus Interactive",
+        0x67 => "Ocean Software",
+        0x6F => "Electro Brain",
+        0x71 => "Interplay Entertainment",
+        0x72 | 0xAA => "Broderbund",
+        0x73 => "Sculptured Software",
+        0x75 => "The Sales Curve Limited",
+        0x78 => "THQ",
+        0x79 => "Accolade",
+        0x7A => "Triffix Entertainment",
+        0x7C => "MicroProse",
+        0x80 => "Misawa Entertainment",
+        0x83 => "LOZC G.",
+        0x86 | 0xC4 => "Tokuma Shoten",
+        0x8B => "Bullet-Proof Software",
+        0x8C => "Vic Tokai Corp.",
+        0x8E => "Ape Inc.",
+        0x8F => "I'Max",
+        0x91 => "Chunsoft Co.",
+        0x92 => "Video System",
+        0x93 => "Tsubaraya Productions",
+        0x95 | 0xE3 => "Varie",
+        0x96 => "Yonezawa/S'Pal",
+        0x99 => "Arc",
+        0x9A => "Nihon Bussan",
+        0x9B => "Tecmo",
+        0x9C => "Imagineer",
+        0x9F => "Nova",
+        0xA1 => "Hori Electric",
+        0xA6 => "Kawada",
+        0xA7 => "Takara",
+        0xA9 => "Technos Japan",
+        0xAC => "Toei Animation",
+        0xAD => "Toho",
+        0xAF => "Namco",
+        0xB1 => "ASCII Corporation or Nexsoft",
+        0xB4 => "Square Enix",
+        0xB6 => "HAL Laboratory",
+        0xB7 => "SNK",
+        0xB9 | 0xCE => "Pony Canyon",
+        0xBA => "Culture Brain",
+        0xBB => "Sunsoft",
+        0xBD => "Sony Imagesoft",
+        0xBF => "Sammy Corporation",
+        0xC0 | 0xD0 => "Taito",
+        0xC3 => "Square",
+        0xC5 => "Data East",
+        0xC6 => "Tonkin House",
+        0xC8 => "Koei",
+        0xC9 => "UFL",
+        0xCA => "Ultra Games",
+        0xCB => "VAP, Inc.",
+        0xCC => "Use Corporation",
+        0xCD => "Meldac",
+        0xD1 => "SOFEL",
+        0xD2 => "Quest",
+        0xD3 => "Sigma Enterprises",
+        0xD4 => "ASK Kodansha Co.",
+        0xD7 => "Copya System",
+        0xDA => "Tomy",
+        0xDD => "Nippon Computer Systems",
+        0xDE => "Human Ent.",
+        0xDF => "Altron",
+        0xE1 => "Towa Chiki",
+        0xE2 => "Yutaka",
+        0xE5 => "Epoch",
+        0xE7 => "Athena",
+        0xE8 => "Asmik Ace Entertainment",
+        0xE9 => "Natsume",
+        0xEA => "King Records",
+        0xEC => "Epic/Sony Records",
+        0xEE => "IGS",
+        0xF0 => "A Wave",
+        0xF3 => "Extreme Entertainment",
+        _ => "Unknown",
+    }
+}
+
+fn new_licensee_name(code: &str) -> &'static str {
+    match code {
+        "00" => "None",
+        "01" | "31" => "Nintendo",
+        "08" => "Capcom",
+        "13" | "69" => "Electronic Arts",
+        "18" | "38" => "Hudson Soft",
+        "19" => "B-AI",
+        "20" => "KSS",
+        "22" => "Planning Office WADA",
+        "24" => "PCM Complete",
+        "25" => "San-X",
+        "28" => "Kemco",
+        "29" => "SETA Corporation",
+        "30" => "Viacom",
+        "32" => "Bandai",
+        "33" | "93" => "Ocean Software/Acclaim Entertainment",
+        "34" | "54" | "A4" => "Konami",
+        "35" => "HectorSoft",
+        "37" => "Taito",
+        "39" => "Banpresto",
+        "41" => "Ubi Soft",
+        "42" => "Atlus",
+        "44" => "Malibu Interactive",
+        "46" => "Angel",
+        "47" => "Bullet-Proof Software",
+        "49" => "Irem",
+        "50" => "Absolute",
+        "51" => "Acclaim Entertainment",
+        "52" => "Activision",
+        "53" => "Sammy USA Corporation",
+        "55" => "Hi Tech Expressions",
+        "56" => "LJN",
+        "57" => "Matchbox",
+        "58" => "Mattel",
+        "59" => "Milton Bradley Company",
+        "60" => "Titus Interactive",
+        "61" => "Virgin Games",
+        "64" => "Lucasfilm Games",
+        "67" => "Ocean Software",
+        "70" => "Infogrames",
+        "71" => "Interplay Entertainment",
+        "72" => "Broderbund",
+        "73" => "Sculptured Software",
+        "75" => "The Sales Curve Limited",
+        "78" => "THQ",
+        "79" => "Accolade",
+        "80" => "Misawa Entertainment",
+        "83" => "LOZC G.",
+        "86" => "Tokuma Shoten",
+        "87" => "Tsukuda Original",
+        "91" => "Chunsoft Co.",
+        "92" => "Video System",
+        "95" => "Varie",
+        "96" => "Yonezawa/S'Pal",
+        "97" => "Kaneko",
+        "99" => "Pack-In-Video",
+        "9H" => "Bottom Up",
+        "BL" => "MTO",
+        "DK" => "Kodansha",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod header_tests {
+    use super::*;
+
+    fn rom() -> Vec<u8> {
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[TITLE_START..TITLE_START + 6].copy_from_slice(b"TETRIS");
+        rom_data[OLD_LICENSEE_CODE_ADDRESS] = 0x01;
+        rom_data[HEADER_CHECKSUM_ADDRESS] = header_checksum(&rom_data);
+
+        let [high, low] = global_checksum(&rom_data).to_be_bytes();
+        rom_data[GLOBAL_CHECKSUM_ADDRESS] = high;
+        rom_data[GLOBAL_CHECKSUM_ADDRESS + 1] = low;
+
+        rom_data
+    }
+
+    #[test]
+    fn parse_header() {
+        let header = CartridgeHeader::parse(&rom()).unwrap();
+
+        assert_eq!(header.title, "TETRIS");
+        assert_eq!(header.manufacturer_code, None);
+        assert_eq!(header.licensee(), "Nintendo");
+        assert_eq!(header.rom_size_bytes(), Some(32 * 1024));
+        assert!(header.warnings().is_empty());
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let mut rom_data = rom();
+        rom_data[VERSION_ADDRESS] = 0x01;
+
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+
+        assert!(!header.header_checksum_valid());
+        assert!(!header.global_checksum_valid());
+        assert_eq!(header.warnings().len(), 2);
+    }
+
+    #[test]
+    fn cgb_title_with_manufacturer_code() {
+        let mut rom_data = rom();
+        rom_data[TITLE_START..CGB_FLAG_ADDRESS].copy_from_slice(b"POKEMON SILAAXE");
+        rom_data[CGB_FLAG_ADDRESS] = 0x80;
+
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+
+        assert_eq!(header.title, "POKEMON SIL");
+        assert_eq!(header.manufacturer_code.as_deref(), Some("AAXE"));
+        assert_eq!(header.cgb_support(), "Supported");
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * @file    cartridge/huc1.rs
+ * @brief   HuC1 Memory Bank Controller implementation.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use crate::cartridge::{core::CartridgeCore, MemoryBankController, MASK_MSB, RAM_ADDRESS};
+
+const IR_MODE: u8 = 0x0E;
+// Bit 0 set means the receiver sees light, nothing ever shines at it here
+const IR_NO_LIGHT: u8 = 0xC0;
+
+pub struct Huc1 {
+    core: CartridgeCore,
+    // 0xA000 - 0xBFFF maps the infrared port instead of RAM
+    ir_mode: bool,
+}
+
+impl Huc1 {
+    pub fn new(core: CartridgeCore) -> Self {
+        Self {
+            core,
+            ir_mode: false,
+        }
+    }
+
+    fn ram_address(&self, ram_size: usize, address: u16) -> usize {
+        let offset = self.core.ram_offset * self.core.ram_bank as usize;
+        ((address as usize - RAM_ADDRESS) + offset) % ram_size
+    }
+}
+
+impl MemoryBankController for Huc1 {
+    fn read_rom(&self, address: u16) -> u8 {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF (Bank 00)
+            0x0..=0x3 => self.core.rom_data[address as usize],
+            // 0x4000 - 0x7FFF (Bank 01-3F)
+            0x4..=0x7 => {
+                let offset = self.core.rom_offset * self.core.rom_bank as usize;
+                self.core.rom_data[(address as usize - self.core.rom_offset) + offset]
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x1FFF (RAM/IR select), RAM needs no enable
+            0x0 | 0x1 => self.ir_mode = value & 0x0F == IR_MODE,
+            // 0x2000 - 0x3FFF (ROM bank number)
+            0x2 | 0x3 => {
+                let bank_number = if value & 0x3F == 0 { 1 } else { value & 0x3F };
+                self.core.rom_bank = bank_number as u16;
+            }
+            // 0x4000 - 0x5FFF (RAM bank number)
+            0x4 | 0x5 => self.core.ram_bank = value & 0b11,
+            0x6 | 0x7 => {}
+            _ => unreachable!(),
+        }
+
+        self.core.set_rom_bank();
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if self.ir_mode {
+            return IR_NO_LIGHT;
+        }
+
+        if let Some(ref ram_data) = self.core.ram_data {
+            return ram_data[self.ram_address(ram_data.len(), address)];
+        }
+
+        0xFF
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        // Bit 0 switches the IR LED, there is no one to talk to
+        if self.ir_mode {
+            return;
+        }
+
+        let ram_address = match self.core.ram_data {
+            Some(ref ram_data) => self.ram_address(ram_data.len(), address),
+            None => return,
+        };
+
+        self.core.write_ram_data(ram_address, value);
+    }
+
+    fn load_ram(&mut self, ram_data: Vec<u8>) {
+        self.core.ram_data = Some(ram_data);
+    }
+
+    fn save_ram(&self) -> Option<Vec<u8>> {
+        self.core.ram_data.clone()
+    }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
+}
+
+#[cfg(test)]
+mod huc1_tests {
+    use super::*;
+    use crate::cartridge::{header::CartridgeHeader, ROM_BANK_SIZE};
+
+    // 64 banks that start with their own number, 32 KiB RAM
+    fn huc1() -> Huc1 {
+        let mut rom_data = vec![0; 64 * ROM_BANK_SIZE];
+        for bank in 0..64 {
+            rom_data[bank * ROM_BANK_SIZE] = bank as u8;
+        }
+        rom_data[0x147] = 0xFF;
+        rom_data[0x149] = 0x03;
+
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+        Huc1::new(CartridgeCore::new(&rom_data, &header))
+    }
+
+    #[test]
+    fn rom_banking() {
+        let mut mbc = huc1();
+
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x01);
+
+        mbc.write_rom(0x2000, 0x3F);
+        assert_eq!(mbc.read_rom(0x4000), 0x3F);
+        assert_eq!(mbc.read_rom(0x0000), 0x00);
+
+        // Only six bits are wired
+        mbc.write_rom(0x2000, 0x45);
+        assert_eq!(mbc.read_rom(0x4000), 0x05);
+    }
+
+    #[test]
+    fn ram_and_infrared() {
+        let mut mbc = huc1();
+
+        // RAM works without an enable write
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_ram(0xA000, 0x42);
+        assert_eq!(mbc.core.ram_data.as_ref().unwrap()[2 * 0x2000], 0x42);
+
+        mbc.write_rom(0x0000, 0x0E);
+        assert_eq!(mbc.read_ram(0xA000), IR_NO_LIGHT);
+        mbc.write_ram(0xA000, 0x01);
+
+        mbc.write_rom(0x0000, 0x0A);
+        assert_eq!(mbc.read_ram(0xA000), 0x42);
+    }
+}
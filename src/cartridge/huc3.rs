@@ -0,0 +1,288 @@
+/*
+ * @file    cartridge/huc3.rs
+ * @brief   HuC3 Memory Bank Controller implementation with its real-time clock.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::cartridge::{core::CartridgeCore, MemoryBankController, MASK_MSB, RAM_ADDRESS};
+
+// Selected through 0x0000 - 0x1FFF
+const MODE_RAM_READ: u8 = 0x0;
+const MODE_RAM: u8 = 0xA;
+const MODE_COMMAND: u8 = 0xB;
+const MODE_RESPONSE: u8 = 0xC;
+const MODE_SEMAPHORE: u8 = 0xD;
+const MODE_IR: u8 = 0xE;
+
+const COMMAND_READ: u8 = 0x1;
+const COMMAND_WRITE: u8 = 0x3;
+const COMMAND_ADDRESS_LOW: u8 = 0x4;
+const COMMAND_ADDRESS_HIGH: u8 = 0x5;
+const COMMAND_EXTENDED: u8 = 0x6;
+
+const MINUTES_PER_DAY: u64 = 24 * 60;
+// Minutes and days, 12 bits each, stored a nibble at a time
+const TIME_NIBBLES: usize = 6;
+
+// Minutes, days and the time of saving, appended to the RAM in save files
+const RTC_SAVE_SIZE: usize = 12;
+
+/*
+ * The clock is a small microcontroller that counts minutes and days. The
+ * game talks to it through 256 nibbles of memory: it writes commands to
+ * 0xA000 in command mode and reads the answer back in response mode.
+ */
+pub struct Huc3 {
+    core: CartridgeCore,
+    mode: u8,
+    rtc_memory: [u8; 256],
+    rtc_address: u8,
+    response: u8,
+    minutes: u64,
+    days: u64,
+    // Wall-clock time the minutes and days were last brought up to date
+    updated_at: SystemTime,
+}
+
+impl Huc3 {
+    pub fn new(core: CartridgeCore) -> Self {
+        Self {
+            core,
+            mode: MODE_RAM_READ,
+            rtc_memory: [0; 256],
+            rtc_address: 0,
+            response: 0,
+            minutes: 0,
+            days: 0,
+            updated_at: SystemTime::now(),
+        }
+    }
+
+    fn ram_address(&self, ram_size: usize, address: u16) -> usize {
+        let offset = self.core.ram_offset * self.core.ram_bank as usize;
+        ((address as usize - RAM_ADDRESS) + offset) % ram_size
+    }
+
+    // Keeps the part of a minute that has not passed yet for the next update
+    fn update_clock(&mut self) {
+        let elapsed = self.updated_at.elapsed().unwrap_or_default().as_secs() / 60;
+        if elapsed == 0 {
+            return;
+        }
+
+        self.updated_at += Duration::from_secs(elapsed * 60);
+        let minutes = self.minutes + elapsed;
+        self.minutes = minutes % MINUTES_PER_DAY;
+        self.days = (self.days + minutes / MINUTES_PER_DAY) & 0xFFF;
+    }
+
+    fn time_nibbles(&self) -> [u8; TIME_NIBBLES] {
+        let time = self.minutes | (self.days << 12);
+        std::array::from_fn(|i| ((time >> (i * 4)) & 0xF) as u8)
+    }
+
+    fn execute(&mut self, value: u8) {
+        let command = (value >> 4) & 0x7;
+        let argument = value & 0x0F;
+
+        match command {
+            COMMAND_READ => {
+                self.response = self.rtc_memory[self.rtc_address as usize];
+                self.rtc_address = self.rtc_address.wrapping_add(1);
+            }
+            COMMAND_WRITE => {
+                self.rtc_memory[self.rtc_address as usize] = argument;
+                self.rtc_address = self.rtc_address.wrapping_add(1);
+            }
+            COMMAND_ADDRESS_LOW => self.rtc_address = (self.rtc_address & 0xF0) | argument,
+            COMMAND_ADDRESS_HIGH => self.rtc_address = (self.rtc_address & 0x0F) | (argument << 4),
+            COMMAND_EXTENDED => match argument {
+                // Latch the current time into memory
+                0x0 => {
+                    self.update_clock();
+                    let time_nibbles = self.time_nibbles();
+                    self.rtc_memory[..TIME_NIBBLES].copy_from_slice(&time_nibbles);
+                }
+                // Set the time from memory
+                0x1 => {
+                    let time = self.rtc_memory[..TIME_NIBBLES]
+                        .iter()
+                        .enumerate()
+                        .fold(0, |time, (i, &nibble)| time | ((nibble as u64) << (i * 4)));
+
+                    self.minutes = (time & 0xFFF) % MINUTES_PER_DAY;
+                    self.days = time >> 12;
+                    self.updated_at = SystemTime::now();
+                    self.core.ram_changed = true;
+                }
+                // Status, the clock is always ready
+                0x2 => self.response = 0x1,
+                _ => {}
+            },
+            _ => {}
+        }
+
+        self.response = (command << 4) | (self.response & 0x0F);
+    }
+}
+
+impl MemoryBankController for Huc3 {
+    fn read_rom(&self, address: u16) -> u8 {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF (Bank 00)
+            0x0..=0x3 => self.core.rom_data[address as usize],
+            // 0x4000 - 0x7FFF (Bank 01-7F)
+            0x4..=0x7 => {
+                let offset = self.core.rom_offset * self.core.rom_bank as usize;
+                self.core.rom_data[(address as usize - self.core.rom_offset) + offset]
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x1FFF (RAM, clock or IR select)
+            0x0 | 0x1 => self.mode = value & 0x0F,
+            // 0x2000 - 0x3FFF (ROM bank number)
+            0x2 | 0x3 => self.core.rom_bank = (value & 0x7F) as u16,
+            // 0x4000 - 0x5FFF (RAM bank number)
+            0x4 | 0x5 => self.core.ram_bank = value & 0x0F,
+            0x6 | 0x7 => {}
+            _ => unreachable!(),
+        }
+
+        self.core.set_rom_bank();
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match self.mode {
+            MODE_RAM_READ | MODE_RAM => match self.core.ram_data {
+                Some(ref ram_data) => ram_data[self.ram_address(ram_data.len(), address)],
+                None => 0xFF,
+            },
+            MODE_RESPONSE => 0x80 | self.response,
+            // Lets the game know the clock has finished the last command
+            MODE_SEMAPHORE => 0x01,
+            // Nothing is ever received
+            MODE_IR => 0xC0,
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        match self.mode {
+            MODE_RAM => {
+                let ram_address = match self.core.ram_data {
+                    Some(ref ram_data) => self.ram_address(ram_data.len(), address),
+                    None => return,
+                };
+
+                self.core.write_ram_data(ram_address, value);
+            }
+            MODE_COMMAND => self.execute(value),
+            _ => {}
+        }
+    }
+
+    fn load_ram(&mut self, mut ram_data: Vec<u8>) {
+        let ram_size = self
+            .core
+            .ram_data
+            .as_ref()
+            .map_or(0, |ram_data| ram_data.len());
+
+        // Save files without a clock start it from zero
+        if ram_data.len() == ram_size + RTC_SAVE_SIZE {
+            let rtc = ram_data.split_off(ram_size);
+            let saved_at = u64::from_le_bytes(rtc[4..12].try_into().unwrap());
+
+            // Saves padded to size carry no clock either
+            if saved_at != 0 {
+                self.minutes = u16::from_le_bytes([rtc[0], rtc[1]]) as u64 % MINUTES_PER_DAY;
+                self.days = u16::from_le_bytes([rtc[2], rtc[3]]) as u64 & 0xFFF;
+                self.updated_at = UNIX_EPOCH + Duration::from_secs(saved_at);
+                self.update_clock();
+            }
+        }
+
+        self.core.ram_data = Some(ram_data);
+    }
+
+    fn save_ram(&self) -> Option<Vec<u8>> {
+        let mut ram_data = self.core.ram_data.clone().unwrap_or_default();
+        let updated_at = self
+            .updated_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        ram_data.extend_from_slice(&(self.minutes as u16).to_le_bytes());
+        ram_data.extend_from_slice(&(self.days as u16).to_le_bytes());
+        ram_data.extend_from_slice(&updated_at.as_secs().to_le_bytes());
+
+        Some(ram_data)
+    }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
+}
+
+#[cfg(test)]
+mod huc3_tests {
+    use super::*;
+    use crate::cartridge::header::CartridgeHeader;
+
+    fn huc3() -> Huc3 {
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[0x147] = 0xFE;
+        rom_data[0x149] = 0x03;
+
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+        Huc3::new(CartridgeCore::new(&rom_data, &header))
+    }
+
+    #[test]
+    fn latch_time() {
+        let mut huc3 = huc3();
+        huc3.minutes = 0x2A5;
+        huc3.days = 0x123;
+
+        huc3.write_rom(0x0000, MODE_COMMAND);
+        huc3.write_ram(0xA000, 0x60);
+        huc3.write_ram(0xA000, 0x40);
+        huc3.write_ram(0xA000, 0x50);
+
+        let mut nibbles = Vec::new();
+        for _ in 0..TIME_NIBBLES {
+            huc3.write_rom(0x0000, MODE_COMMAND);
+            huc3.write_ram(0xA000, 0x10);
+            huc3.write_rom(0x0000, MODE_RESPONSE);
+            nibbles.push(huc3.read_ram(0xA000) & 0x0F);
+        }
+
+        assert_eq!(nibbles, [0x5, 0xA, 0x2, 0x3, 0x2, 0x1]);
+    }
+
+    #[test]
+    fn clock_survives_saving() {
+        let mut saved = huc3();
+        saved.minutes = MINUTES_PER_DAY - 1;
+        saved.days = 7;
+        // Saved two minutes ago
+        saved.updated_at = SystemTime::now() - Duration::from_secs(120);
+
+        let save = saved.save_ram().unwrap();
+        assert_eq!(save.len(), 32 * 1024 + RTC_SAVE_SIZE);
+
+        let mut loaded = huc3();
+        loaded.load_ram(save);
+
+        assert_eq!(loaded.minutes, 1);
+        assert_eq!(loaded.days, 8);
+        assert_eq!(loaded.core.ram_data.unwrap().len(), 32 * 1024);
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ * @file    cartridge/image_source.rs
+ * @brief   Pictures the Game Boy Camera sensor sees instead of a webcam.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{error::Error, path::Path};
+
+use image::imageops::FilterType;
+
+// Part of the sensor that ends up in the photo
+pub const SENSOR_WIDTH: usize = 128;
+pub const SENSOR_HEIGHT: usize = 112;
+
+pub trait ImageSource {
+    // Brightness of every pixel from 0 (black) to 255 (white), row by row
+    fn capture(&mut self) -> Vec<u8>;
+}
+
+// Gray bars with a stripe that moves a little on every capture
+pub struct TestPattern {
+    frame: usize,
+}
+
+impl TestPattern {
+    pub fn new() -> Self {
+        Self { frame: 0 }
+    }
+}
+
+impl ImageSource for TestPattern {
+    fn capture(&mut self) -> Vec<u8> {
+        self.frame = self.frame.wrapping_add(1);
+
+        (0..SENSOR_HEIGHT)
+            .flat_map(|y| (0..SENSOR_WIDTH).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let stripe = (x + y + self.frame * 2) % SENSOR_WIDTH < 12;
+                if stripe {
+                    0xFF
+                } else {
+                    (x / 16 * 0xFF / 7) as u8
+                }
+            })
+            .collect()
+    }
+}
+
+// A picture file scaled and cropped to fill the sensor
+pub struct StillImage {
+    pixels: Vec<u8>,
+}
+
+impl StillImage {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let image = image::open(path)?.resize_to_fill(
+            SENSOR_WIDTH as u32,
+            SENSOR_HEIGHT as u32,
+            FilterType::Triangle,
+        );
+
+        Ok(Self {
+            pixels: image.to_luma8().into_raw(),
+        })
+    }
+}
+
+impl ImageSource for StillImage {
+    fn capture(&mut self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+}
@@ -37,9 +37,8 @@ impl MemoryBankController for Mbc0 {
     }
 
     fn write_ram(&mut self, address: u16, value: u8) {
-        if let Some(ref mut ram_data) = self.core.ram_data {
-            ram_data[address as usize - RAM_ADDRESS] = value;
-        }
+        self.core
+            .write_ram_data(address as usize - RAM_ADDRESS, value);
     }
 
     fn load_ram(&mut self, ram_data: Vec<u8>) {
@@ -49,4 +48,8 @@ impl MemoryBankController for Mbc0 {
     fn save_ram(&self) -> Option<Vec<u8>> {
         self.core.ram_data.clone()
     }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
 }
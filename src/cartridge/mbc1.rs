@@ -5,7 +5,16 @@
  * @date    June 8, 2024
  */
 
-use crate::cartridge::{core::CartridgeCore, MemoryBankController, MASK_MSB, RAM_ADDRESS};
+use crate::cartridge::{
+    core::CartridgeCore, MemoryBankController, MASK_MSB, RAM_ADDRESS, ROM_BANK_SIZE,
+};
+
+const LOGO_ADDRESS: usize = 0x104;
+const LOGO_LENGTH: usize = 0x30;
+
+// Multicarts hold four 256 KiB games, each starting with its own header
+const MULTICART_ROM_SIZE: usize = 1024 * 1024;
+const MULTICART_GAME_SIZE: usize = 256 * 1024;
 
 enum Mode {
     RomBanking,
@@ -15,46 +24,85 @@ enum Mode {
 pub struct Mbc1 {
     core: CartridgeCore,
     mode: Mode,
+    // 0x2000 - 0x3FFF, lower bits of the ROM bank
+    bank1: u8,
+    // 0x4000 - 0x5FFF, upper bits of the ROM bank or the RAM bank
+    bank2: u8,
+    // MBC1M wires only 4 bits of bank1, bank2 selects the game
+    multicart: bool,
 }
 
 impl Mbc1 {
     pub fn new(core: CartridgeCore) -> Self {
+        let multicart = is_multicart(&core.rom_data);
+
         Self {
             core,
             mode: Mode::RomBanking,
+            bank1: 1,
+            bank2: 0,
+            multicart,
         }
     }
+
+    fn upper_bank(&self) -> usize {
+        let shift = if self.multicart { 4 } else { 5 };
+        (self.bank2 as usize) << shift
+    }
+
+    fn lower_bank(&self) -> usize {
+        let mask = if self.multicart { 0x0F } else { 0x1F };
+        (self.bank1 & mask) as usize
+    }
+
+    fn rom_address(&self, bank: usize, address: u16) -> usize {
+        let rom_banks = (self.core.rom_data.len() / ROM_BANK_SIZE).max(1);
+        let offset = (bank % rom_banks) * ROM_BANK_SIZE;
+
+        offset + (address as usize % ROM_BANK_SIZE)
+    }
+
+    fn ram_address(&self, ram_size: usize, address: u16) -> usize {
+        // Only 32 KiB RAM is banked, and only in mode 1
+        let bank = match self.mode {
+            Mode::RamBanking => self.bank2 as usize,
+            Mode::RomBanking => 0,
+        };
+        let ram_banks = (ram_size / self.core.ram_offset).max(1);
+        let offset = (bank % ram_banks) * self.core.ram_offset;
+
+        offset + ((address as usize - RAM_ADDRESS) % self.core.ram_offset)
+    }
 }
 
 impl MemoryBankController for Mbc1 {
     fn read_rom(&self, address: u16) -> u8 {
-        match (address & MASK_MSB) >> 12 {
-            // 0x0000 - 0x3FFF (Bank 00)
-            0x0..=0x3 => self.core.rom_data[address as usize],
+        let bank = match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF (Bank 00, or 20/40/60 in mode 1)
+            0x0..=0x3 => match self.mode {
+                Mode::RamBanking => self.upper_bank(),
+                Mode::RomBanking => 0,
+            },
             // 0x4000 - 0x7FFF (Bank 01-7F)
-            0x4..=0x7 => {
-                let offset = self.core.rom_offset * self.core.rom_bank as usize;
-                self.core.rom_data[(address as usize - self.core.rom_offset) + offset]
-            }
+            0x4..=0x7 => self.upper_bank() | self.lower_bank(),
             _ => unreachable!(),
-        }
+        };
+
+        self.core.rom_data[self.rom_address(bank, address)]
     }
 
     fn write_rom(&mut self, address: u16, value: u8) {
         match (address & MASK_MSB) >> 12 {
             // 0x0000 - 0x1FFF (RAM enable)
-            0x0 | 0x1 => self.core.ram_enabled = value == 0x0A,
+            0x0 | 0x1 => self.core.ram_enabled = value & 0x0F == 0x0A,
             // 0x2000 - 0x3FFF (ROM bank number)
             0x2 | 0x3 => {
-                // Specify the lower 5 bits
-                let bank_number = if value == 0 { 1 } else { value };
-                self.core.rom_bank = (self.core.rom_bank & 0b0110_0000) | (bank_number & 0b0001_1111) as u16;
+                // The zero check sees all 5 bits, even on multicarts
+                let bank_number = value & 0b0001_1111;
+                self.bank1 = if bank_number == 0 { 1 } else { bank_number };
             }
             // 0x4000 - 0x5FFF (RAM bank number — or — upper bits of ROM bank number)
-            0x4 | 0x5 => match self.mode {
-                Mode::RamBanking => self.core.ram_bank = value & 0b11,
-                Mode::RomBanking => self.core.rom_bank |= ((value & 0b11) << 5) as u16,
-            },
+            0x4 | 0x5 => self.bank2 = value & 0b11,
             // 0x6000 - 0x7FFF (Banking mode select)
             0x6 | 0x7 => match value & 0b1 {
                 0 => self.mode = Mode::RomBanking,
@@ -63,8 +111,6 @@ impl MemoryBankController for Mbc1 {
             },
             _ => unreachable!(),
         }
-
-        self.core.set_rom_bank();
     }
 
     fn read_ram(&self, address: u16) -> u8 {
@@ -73,8 +119,7 @@ impl MemoryBankController for Mbc1 {
         }
 
         if let Some(ref ram_data) = self.core.ram_data {
-            let offset = self.core.ram_offset * self.core.ram_bank as usize;
-            return ram_data[(address as usize - RAM_ADDRESS) + offset];
+            return ram_data[self.ram_address(ram_data.len(), address)];
         }
 
         0xFF
@@ -85,10 +130,13 @@ impl MemoryBankController for Mbc1 {
             return;
         }
 
-        if let Some(ref mut ram_data) = self.core.ram_data {
-            let offset = self.core.ram_offset * self.core.ram_bank as usize;
-            ram_data[(address as usize - RAM_ADDRESS) + offset] = value;
-        }
+        let ram_size = match self.core.ram_data {
+            Some(ref ram_data) => ram_data.len(),
+            None => return,
+        };
+        let ram_address = self.ram_address(ram_size, address);
+
+        self.core.write_ram_data(ram_address, value);
     }
 
     fn load_ram(&mut self, ram_data: Vec<u8>) {
@@ -98,4 +146,162 @@ impl MemoryBankController for Mbc1 {
     fn save_ram(&self) -> Option<Vec<u8>> {
         self.core.ram_data.clone()
     }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
+}
+
+// The menu and every game carry the Nintendo logo, a regular 8 Mbit ROM only has it in bank 00
+fn is_multicart(rom_data: &[u8]) -> bool {
+    if rom_data.len() != MULTICART_ROM_SIZE {
+        return false;
+    }
+
+    let logo = &rom_data[LOGO_ADDRESS..LOGO_ADDRESS + LOGO_LENGTH];
+    let game_address = MULTICART_GAME_SIZE + LOGO_ADDRESS;
+
+    rom_data[game_address..game_address + LOGO_LENGTH] == *logo
+}
+
+#[cfg(test)]
+mod mbc1_tests {
+    use super::*;
+    use crate::cartridge::header::CartridgeHeader;
+
+    // Every bank starts with its own number, ram_size is the header code
+    fn mbc1(rom_banks: usize, ram_size: u8) -> Mbc1 {
+        let mut rom_data = vec![0; rom_banks * ROM_BANK_SIZE];
+        for bank in 0..rom_banks {
+            rom_data[bank * ROM_BANK_SIZE] = bank as u8;
+        }
+        rom_data[0x147] = 0x03;
+        rom_data[0x149] = ram_size;
+
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+        Mbc1::new(CartridgeCore::new(&rom_data, &header))
+    }
+
+    #[test]
+    fn bank_registers() {
+        let mut mbc = mbc1(128, 0x00);
+
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x01);
+
+        // Writing 0x20 reads as zero on 5 bits and maps bank 01
+        mbc.write_rom(0x2000, 0x20);
+        assert_eq!(mbc.read_rom(0x4000), 0x01);
+
+        mbc.write_rom(0x2000, 0x1F);
+        mbc.write_rom(0x4000, 0x02);
+        assert_eq!(mbc.read_rom(0x4000), 0x5F);
+        assert_eq!(mbc.read_rom(0x0000), 0x00);
+
+        // Mode 1 also remaps the first ROM region on large ROMs
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_rom(0x0000), 0x40);
+        assert_eq!(mbc.read_rom(0x4000), 0x5F);
+
+        // Clearing the lower bits leaves the upper bits alone
+        mbc.write_rom(0x2000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x41);
+    }
+
+    #[test]
+    fn small_rom_wraps() {
+        let mut mbc = mbc1(32, 0x00);
+
+        mbc.write_rom(0x2000, 0x05);
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_rom(0x6000, 0x01);
+
+        assert_eq!(mbc.read_rom(0x0000), 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x05);
+    }
+
+    #[test]
+    fn ram_banking() {
+        let mut mbc = mbc1(4, 0x03);
+        mbc.write_rom(0x0000, 0x0A);
+
+        for bank in 0..4 {
+            mbc.write_rom(0x4000, bank);
+            mbc.write_rom(0x6000, 0x01);
+            mbc.write_ram(0xA000, bank + 1);
+        }
+
+        // Mode 0 always maps RAM bank 0
+        mbc.write_rom(0x6000, 0x00);
+        assert_eq!(mbc.read_ram(0xA000), 0x01);
+
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_ram(0xA000), 0x04);
+
+        // 8 KiB RAM ignores the bank bits
+        let mut mbc = mbc1(4, 0x02);
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_rom(0x6000, 0x01);
+        mbc.write_ram(0xA000, 0x42);
+        assert_eq!(mbc.read_ram(0xA000), 0x42);
+    }
+
+    #[test]
+    fn ram_enable() {
+        let mut mbc = mbc1(4, 0x02);
+
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_ram(0xA000, 0x42);
+        assert_eq!(mbc.read_ram(0xA000), 0x42);
+
+        // Only the lower nibble counts, anywhere in 0x0000 - 0x1FFF
+        mbc.write_rom(0x1FFF, 0x1B);
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+        mbc.write_ram(0xA000, 0x24);
+
+        mbc.write_rom(0x1234, 0xFA);
+        assert_eq!(mbc.read_ram(0xA000), 0x42);
+    }
+
+    #[test]
+    fn reports_ram_changes() {
+        let mut mbc = mbc1(4, 0x02);
+
+        mbc.write_ram(0xA000, 0x42);
+        mbc.write_rom(0x0000, 0x0A);
+        assert!(!mbc.take_ram_changed());
+
+        mbc.write_ram(0xA000, 0x42);
+        assert!(mbc.take_ram_changed());
+        assert!(!mbc.take_ram_changed());
+
+        // Writing the stored value again changes nothing
+        mbc.write_ram(0xA000, 0x42);
+        assert!(!mbc.take_ram_changed());
+    }
+
+    #[test]
+    fn multicart() {
+        let mut mbc = mbc1(64, 0x00);
+        let logo = [0xCE; LOGO_LENGTH];
+        for game in 0..4 {
+            let address = game * MULTICART_GAME_SIZE + LOGO_ADDRESS;
+            mbc.core.rom_data[address..address + LOGO_LENGTH].copy_from_slice(&logo);
+        }
+        mbc.multicart = is_multicart(&mbc.core.rom_data);
+        assert!(mbc.multicart);
+
+        // Bank 10 passes the zero check but maps the first bank of the game
+        mbc.write_rom(0x2000, 0x10);
+        mbc.write_rom(0x4000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x10);
+
+        mbc.write_rom(0x2000, 0x0F);
+        assert_eq!(mbc.read_rom(0x4000), 0x1F);
+
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_rom(0x0000), 0x30);
+    }
 }
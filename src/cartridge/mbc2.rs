@@ -81,9 +81,8 @@ impl MemoryBankController for Mbc2 {
             return;
         }
 
-        if let Some(ref mut ram_data) = self.core.ram_data {
-            ram_data[address as usize - RAM_ADDRESS] = value & 0xF;
-        }
+        self.core
+            .write_ram_data(address as usize - RAM_ADDRESS, value & 0xF);
     }
 
     fn load_ram(&mut self, ram_data: Vec<u8>) {
@@ -93,4 +92,8 @@ impl MemoryBankController for Mbc2 {
     fn save_ram(&self) -> Option<Vec<u8>> {
         self.core.ram_data.clone()
     }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
 }
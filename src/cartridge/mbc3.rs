@@ -67,13 +67,9 @@ impl MemoryBankController for Mbc3 {
             return;
         }
 
-        let ram_offset = self.core.ram_offset;
-        let ram_bank = self.core.ram_bank;
-
-        if let Some(ref mut ram_data) = self.core.ram_data {
-            let offset = ram_offset * ram_bank as usize;
-            ram_data[(address as usize - RAM_ADDRESS) + offset] = value;
-        }
+        let offset = self.core.ram_offset * self.core.ram_bank as usize;
+        self.core
+            .write_ram_data((address as usize - RAM_ADDRESS) + offset, value);
     }
 
     fn load_ram(&mut self, ram_data: Vec<u8>) {
@@ -83,4 +79,8 @@ impl MemoryBankController for Mbc3 {
     fn save_ram(&self) -> Option<Vec<u8>> {
         self.core.ram_data.clone()
     }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
 }
@@ -9,11 +9,23 @@ use crate::cartridge::{core::CartridgeCore, MemoryBankController, MASK_MSB, RAM_
 
 pub struct Mbc5 {
     core: CartridgeCore,
+    // Rumble cartridges wire bit 3 of the RAM bank to the motor
+    has_rumble: bool,
+    motor_on: bool,
+    // M-cycles the motor was on, out of those elapsed since the last take_rumble
+    motor_cycles: u32,
+    elapsed_cycles: u32,
 }
 
 impl Mbc5 {
-    pub fn new(core: CartridgeCore) -> Self {
-        Self { core }
+    pub fn new(core: CartridgeCore, has_rumble: bool) -> Self {
+        Self {
+            core,
+            has_rumble,
+            motor_on: false,
+            motor_cycles: 0,
+            elapsed_cycles: 0,
+        }
     }
 }
 
@@ -34,7 +46,14 @@ impl MemoryBankController for Mbc5 {
             0x0 | 0x1 => self.core.ram_enabled = (value & 0x0F) == 0x0A,
             0x2 => self.core.rom_bank = (self.core.rom_bank & 0x100) | (value as u16),
             0x3 => self.core.rom_bank = (self.core.rom_bank & 0xFF) | ((value as u16 & 0x01) << 8),
-            0x4 | 0x5 => self.core.ram_bank = value & 0x0F,
+            0x4 | 0x5 => {
+                if self.has_rumble {
+                    self.motor_on = value & 0x08 != 0;
+                    self.core.ram_bank = value & 0x07;
+                } else {
+                    self.core.ram_bank = value & 0x0F;
+                }
+            }
             0x6 | 0x7 => {}
             _ => unreachable!(),
         }
@@ -60,13 +79,9 @@ impl MemoryBankController for Mbc5 {
             return;
         }
 
-        let ram_offset = self.core.ram_offset;
-        let ram_bank = self.core.ram_bank;
-
-        if let Some(ref mut ram_data) = self.core.ram_data {
-            let offset = ram_offset * ram_bank as usize;
-            ram_data[(address as usize - RAM_ADDRESS) + offset] = value;
-        }
+        let offset = self.core.ram_offset * self.core.ram_bank as usize;
+        self.core
+            .write_ram_data((address as usize - RAM_ADDRESS) + offset, value);
     }
 
     fn load_ram(&mut self, ram_data: Vec<u8>) {
@@ -76,4 +91,66 @@ impl MemoryBankController for Mbc5 {
     fn save_ram(&self) -> Option<Vec<u8>> {
         self.core.ram_data.clone()
     }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
+
+    fn tick(&mut self, m_cycles: u8) {
+        if !self.has_rumble {
+            return;
+        }
+
+        self.elapsed_cycles += m_cycles as u32;
+        if self.motor_on {
+            self.motor_cycles += m_cycles as u32;
+        }
+    }
+
+    // Games pulse the motor to vary its strength, so this is the share of time it was on
+    fn take_rumble(&mut self) -> f32 {
+        let motor_cycles = std::mem::take(&mut self.motor_cycles);
+        let elapsed_cycles = std::mem::take(&mut self.elapsed_cycles);
+
+        if elapsed_cycles == 0 {
+            return 0.0;
+        }
+
+        motor_cycles as f32 / elapsed_cycles as f32
+    }
+}
+
+#[cfg(test)]
+mod mbc5_tests {
+    use super::*;
+    use crate::cartridge::header::CartridgeHeader;
+
+    fn mbc5(cartridge_type: u8) -> Mbc5 {
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[0x147] = cartridge_type;
+        rom_data[0x149] = 0x03;
+
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+        Mbc5::new(
+            CartridgeCore::new(&rom_data, &header),
+            cartridge_type >= 0x1C,
+        )
+    }
+
+    #[test]
+    fn rumble_bit_drives_motor() {
+        let mut mbc = mbc5(0x1E);
+        mbc.write_rom(0x4000, 0x09);
+        assert_eq!(mbc.core.ram_bank, 0x01);
+
+        mbc.tick(1);
+        mbc.write_rom(0x4000, 0x01);
+        mbc.tick(3);
+        assert_eq!(mbc.take_rumble(), 0.25);
+        assert_eq!(mbc.take_rumble(), 0.0);
+
+        let mut mbc = mbc5(0x1B);
+        mbc.write_rom(0x4000, 0x09);
+        assert_eq!(mbc.core.ram_bank, 0x09);
+    }
 }
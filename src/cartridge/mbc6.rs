@@ -0,0 +1,313 @@
+/*
+ * @file    cartridge/mbc6.rs
+ * @brief   MBC6 Memory Bank Controller with its flash memory.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use crate::cartridge::{core::CartridgeCore, MemoryBankController};
+
+// ROM and flash are switched in halves of the usual bank size, RAM in 4 KiB banks
+const HALF_BANK_SIZE: usize = 8 * 1024;
+const RAM_HALF_BANK_SIZE: usize = 4 * 1024;
+
+// MX29F008, erased a sector at a time
+const FLASH_SIZE: usize = 1024 * 1024;
+const FLASH_SECTOR_SIZE: usize = 64 * 1024;
+
+// Command cycles are told apart by the lower 15 address bits
+const FLASH_UNLOCK_1: usize = 0x5555;
+const FLASH_UNLOCK_2: usize = 0x2AAA;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FlashState {
+    Read,
+    // Number of unlock cycles seen so far, and whether an erase was announced
+    Unlock { cycle: u8, erase: bool },
+    Command { erase: bool },
+    Program,
+    Id,
+}
+
+/*
+ * Each half of 0x4000 - 0x7FFF maps an 8 KiB bank of either ROM or flash.
+ * Flash takes the usual JEDEC commands: two unlock cycles and a command,
+ * then the byte to program or another unlock sequence to erase.
+ */
+pub struct Mbc6 {
+    core: CartridgeCore,
+    rom_banks: [u8; 2],
+    flash_selected: [bool; 2],
+    ram_banks: [u8; 2],
+    flash: Vec<u8>,
+    flash_enabled: bool,
+    flash_write_enabled: bool,
+    flash_state: FlashState,
+}
+
+impl Mbc6 {
+    pub fn new(core: CartridgeCore) -> Self {
+        Self {
+            core,
+            rom_banks: [0; 2],
+            flash_selected: [false; 2],
+            ram_banks: [0; 2],
+            flash: vec![0xFF; FLASH_SIZE],
+            flash_enabled: false,
+            flash_write_enabled: false,
+            flash_state: FlashState::Read,
+        }
+    }
+
+    // Index of the 0x4000 - 0x5FFF and 0x6000 - 0x7FFF halves
+    fn half(address: u16) -> usize {
+        (address as usize >> 13) & 0b1
+    }
+
+    fn flash_address(&self, half: usize, address: u16) -> usize {
+        let offset = self.rom_banks[half] as usize * HALF_BANK_SIZE;
+        (offset + (address as usize % HALF_BANK_SIZE)) % FLASH_SIZE
+    }
+
+    fn ram_address(&self, ram_size: usize, address: u16) -> usize {
+        let half = (address as usize >> 12) & 0b1;
+        let offset = self.ram_banks[half] as usize * RAM_HALF_BANK_SIZE;
+
+        (offset + (address as usize % RAM_HALF_BANK_SIZE)) % ram_size
+    }
+
+    fn write_flash(&mut self, address: usize, value: u8) {
+        let command_address = address & 0x7FFF;
+
+        self.flash_state = match (self.flash_state, value) {
+            // The byte after a program command is data, even if it reads as a reset
+            (FlashState::Program, _) => {
+                // Programming can only clear bits
+                self.flash[address] &= value;
+                self.core.ram_changed = true;
+                FlashState::Read
+            }
+            (_, 0xF0) => FlashState::Read,
+            (FlashState::Read | FlashState::Id, 0xAA) if command_address == FLASH_UNLOCK_1 => {
+                FlashState::Unlock {
+                    cycle: 1,
+                    erase: false,
+                }
+            }
+            (FlashState::Unlock { cycle: 1, erase }, 0x55) if command_address == FLASH_UNLOCK_2 => {
+                FlashState::Command { erase }
+            }
+            (FlashState::Command { erase: false }, _) if command_address == FLASH_UNLOCK_1 => {
+                match value {
+                    0x80 => FlashState::Unlock {
+                        cycle: 0,
+                        erase: true,
+                    },
+                    0x90 => FlashState::Id,
+                    0xA0 => FlashState::Program,
+                    _ => FlashState::Read,
+                }
+            }
+            (FlashState::Unlock { cycle: 0, erase }, 0xAA) if command_address == FLASH_UNLOCK_1 => {
+                FlashState::Unlock { cycle: 1, erase }
+            }
+            (FlashState::Command { erase: true }, 0x10) if command_address == FLASH_UNLOCK_1 => {
+                self.flash.fill(0xFF);
+                self.core.ram_changed = true;
+                FlashState::Read
+            }
+            (FlashState::Command { erase: true }, 0x30) => {
+                let sector = address - address % FLASH_SECTOR_SIZE;
+                self.flash[sector..sector + FLASH_SECTOR_SIZE].fill(0xFF);
+                self.core.ram_changed = true;
+                FlashState::Read
+            }
+            _ => FlashState::Read,
+        };
+    }
+}
+
+impl MemoryBankController for Mbc6 {
+    fn read_rom(&self, address: u16) -> u8 {
+        if address < 0x4000 {
+            return self.core.rom_data[address as usize];
+        }
+
+        let half = Self::half(address);
+        if self.flash_selected[half] {
+            if !self.flash_enabled {
+                return 0xFF;
+            }
+
+            // Manufacturer and device code of the MX29F008
+            if self.flash_state == FlashState::Id {
+                return match address & 0x01 {
+                    0 => 0xC2,
+                    _ => 0x81,
+                };
+            }
+
+            return self.flash[self.flash_address(half, address)];
+        }
+
+        let rom_banks = (self.core.rom_data.len() / HALF_BANK_SIZE).max(1);
+        let offset = (self.rom_banks[half] as usize % rom_banks) * HALF_BANK_SIZE;
+        self.core.rom_data[offset + (address as usize % HALF_BANK_SIZE)]
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x03FF => self.core.ram_enabled = value & 0x0F == 0x0A,
+            0x0400..=0x07FF => self.ram_banks[0] = value & 0x07,
+            0x0800..=0x0BFF => self.ram_banks[1] = value & 0x07,
+            0x0C00..=0x0FFF => self.flash_enabled = value & 0x01 != 0,
+            0x1000 => self.flash_write_enabled = value & 0x01 != 0,
+            // 0x2000 - 0x3FFF (Bank and ROM/flash select for both halves)
+            0x2000..=0x3FFF => {
+                let half = (address as usize >> 12) & 0b1;
+
+                if address & 0x0800 == 0 {
+                    self.rom_banks[half] = value;
+                } else {
+                    self.flash_selected[half] = value == 0x08;
+                }
+            }
+            0x4000..=0x7FFF => {
+                let half = Self::half(address);
+
+                if self.flash_selected[half] && self.flash_enabled && self.flash_write_enabled {
+                    let flash_address = self.flash_address(half, address);
+                    self.write_flash(flash_address, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if !self.core.ram_enabled {
+            return 0xFF;
+        }
+
+        if let Some(ref ram_data) = self.core.ram_data {
+            return ram_data[self.ram_address(ram_data.len(), address)];
+        }
+
+        0xFF
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if !self.core.ram_enabled {
+            return;
+        }
+
+        let ram_address = match self.core.ram_data {
+            Some(ref ram_data) => self.ram_address(ram_data.len(), address),
+            None => return,
+        };
+
+        self.core.write_ram_data(ram_address, value);
+    }
+
+    // Save files hold the RAM followed by the flash
+    fn load_ram(&mut self, mut ram_data: Vec<u8>) {
+        if ram_data.len() > FLASH_SIZE {
+            self.flash = ram_data.split_off(ram_data.len() - FLASH_SIZE);
+        }
+
+        self.core.ram_data = Some(ram_data);
+    }
+
+    fn save_ram(&self) -> Option<Vec<u8>> {
+        let mut ram_data = self.core.ram_data.clone().unwrap_or_default();
+        ram_data.extend_from_slice(&self.flash);
+
+        Some(ram_data)
+    }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
+}
+
+#[cfg(test)]
+mod mbc6_tests {
+    use super::*;
+    use crate::cartridge::header::CartridgeHeader;
+
+    fn mbc6() -> Mbc6 {
+        let mut rom_data = vec![0; 0x100000];
+        rom_data[0x147] = 0x20;
+
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+        let mut mbc6 = Mbc6::new(CartridgeCore::new(&rom_data, &header));
+
+        mbc6.write_rom(0x0C00, 0x01);
+        mbc6.write_rom(0x1000, 0x01);
+        // Flash in both halves
+        mbc6.write_rom(0x2800, 0x08);
+        mbc6.write_rom(0x3800, 0x08);
+        mbc6
+    }
+
+    // Unlock cycles go to flash 0x5555 and 0x2AAA, banks 2 and 1
+    fn command(mbc6: &mut Mbc6, value: u8) {
+        mbc6.write_rom(0x2000, 0x02);
+        mbc6.write_rom(0x3000, 0x01);
+        mbc6.write_rom(0x5555, 0xAA);
+        mbc6.write_rom(0x6AAA, 0x55);
+        mbc6.write_rom(0x5555, value);
+    }
+
+    #[test]
+    fn program_and_erase() {
+        let mut mbc6 = mbc6();
+
+        command(&mut mbc6, 0xA0);
+        mbc6.write_rom(0x2000, 0x04);
+        mbc6.write_rom(0x4010, 0x42);
+        assert_eq!(mbc6.read_rom(0x4010), 0x42);
+        assert_eq!(mbc6.flash[4 * HALF_BANK_SIZE + 0x10], 0x42);
+
+        command(&mut mbc6, 0x80);
+        command(&mut mbc6, 0x30);
+        assert_eq!(mbc6.flash[4 * HALF_BANK_SIZE + 0x10], 0xFF);
+    }
+
+    #[test]
+    fn programs_reset_byte() {
+        let mut mbc6 = mbc6();
+
+        command(&mut mbc6, 0xA0);
+        mbc6.write_rom(0x2000, 0x04);
+        mbc6.write_rom(0x4020, 0xF0);
+        assert_eq!(mbc6.flash[4 * HALF_BANK_SIZE + 0x20], 0xF0);
+
+        // Outside of a program command 0xF0 still resets
+        command(&mut mbc6, 0x90);
+        mbc6.write_rom(0x4020, 0xF0);
+        assert!(matches!(mbc6.flash_state, FlashState::Read));
+    }
+
+    #[test]
+    fn reports_flash_changes() {
+        let mut mbc6 = mbc6();
+
+        command(&mut mbc6, 0xA0);
+        mbc6.write_rom(0x2000, 0x04);
+        assert!(!mbc6.take_ram_changed());
+
+        mbc6.write_rom(0x4020, 0x00);
+        assert!(mbc6.take_ram_changed());
+    }
+
+    #[test]
+    fn writes_need_enable() {
+        let mut mbc6 = mbc6();
+        mbc6.write_rom(0x1000, 0x00);
+
+        command(&mut mbc6, 0xA0);
+        mbc6.write_rom(0x5555, 0x00);
+        assert_eq!(mbc6.flash[2 * HALF_BANK_SIZE + 0x1555], 0xFF);
+    }
+}
@@ -0,0 +1,382 @@
+/*
+ * @file    cartridge/mbc7.rs
+ * @brief   MBC7 Memory Bank Controller with accelerometer and EEPROM.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use crate::cartridge::{core::CartridgeCore, MemoryBankController, MASK_MSB};
+
+// Accelerometer reading while lying flat, one g of tilt moves it by about 0x70
+const ACCELEROMETER_CENTER: f32 = 0x81D0 as f32;
+const ACCELEROMETER_RANGE: f32 = 0x70 as f32;
+const ACCELEROMETER_ERASED: u16 = 0x8000;
+
+// 93LC56, 128 words of 16 bits
+const EEPROM_SIZE: usize = 256;
+const EEPROM_WORDS: u8 = 128;
+
+// Pins of the EEPROM at 0xA080
+const EEPROM_CS: u8 = 0x80;
+const EEPROM_CLK: u8 = 0x40;
+const EEPROM_DI: u8 = 0x02;
+const EEPROM_DO: u8 = 0x01;
+
+// Start bit excluded: 2 bits of opcode and 8 bits of address
+const COMMAND_BITS: u8 = 10;
+
+enum EepromState {
+    // Waiting for the start bit
+    Idle,
+    Command,
+    Read,
+    Write { address: Option<u8> },
+    // Waiting for chip select to go low
+    Done,
+}
+
+/*
+ * The EEPROM speaks Microwire: while chip select is high, every rising
+ * clock edge shifts one bit in from DI or out to DO. A command is a start
+ * bit, a 2-bit opcode and an 8-bit address, writes follow with 16 bits of
+ * data and reads shift out a dummy zero before the word.
+ */
+struct Eeprom {
+    data: Vec<u8>,
+    state: EepromState,
+    shift: u16,
+    bits: u8,
+    address: u8,
+    write_enabled: bool,
+    clk: bool,
+    data_out: bool,
+    // Data changed since the last take_ram_changed
+    changed: bool,
+}
+
+impl Eeprom {
+    fn new() -> Self {
+        Self {
+            data: vec![0xFF; EEPROM_SIZE],
+            state: EepromState::Idle,
+            shift: 0,
+            bits: 0,
+            address: 0,
+            write_enabled: false,
+            clk: false,
+            data_out: true,
+            changed: false,
+        }
+    }
+
+    fn word(&self, address: u8) -> u16 {
+        let index = (address % EEPROM_WORDS) as usize * 2;
+        u16::from_le_bytes([self.data[index], self.data[index + 1]])
+    }
+
+    fn set_word(&mut self, address: u8, word: u16) {
+        if !self.write_enabled {
+            return;
+        }
+
+        let index = (address % EEPROM_WORDS) as usize * 2;
+        self.changed |= self.data[index..index + 2] != word.to_le_bytes();
+        self.data[index..index + 2].copy_from_slice(&word.to_le_bytes());
+    }
+
+    fn read(&self) -> u8 {
+        let pins = if self.clk { EEPROM_CLK } else { 0 };
+        if self.data_out {
+            pins | EEPROM_DO
+        } else {
+            pins
+        }
+    }
+
+    fn write(&mut self, value: u8) {
+        if value & EEPROM_CS == 0 {
+            self.state = EepromState::Idle;
+            self.clk = false;
+            self.data_out = true;
+            return;
+        }
+
+        let clk = value & EEPROM_CLK != 0;
+        let rising_edge = clk && !self.clk;
+        self.clk = clk;
+
+        if rising_edge {
+            self.clock(value & EEPROM_DI != 0);
+        }
+    }
+
+    fn clock(&mut self, data_in: bool) {
+        match self.state {
+            EepromState::Idle => {
+                if data_in {
+                    self.state = EepromState::Command;
+                    self.shift = 0;
+                    self.bits = 0;
+                }
+            }
+            EepromState::Command => {
+                self.shift = (self.shift << 1) | data_in as u16;
+                self.bits += 1;
+
+                if self.bits == COMMAND_BITS {
+                    self.execute();
+                }
+            }
+            EepromState::Read => {
+                self.data_out = self.shift & 0x8000 != 0;
+                self.shift <<= 1;
+                self.bits += 1;
+
+                // Keeps going with the next word for as long as the clock runs
+                if self.bits == 16 {
+                    self.address = self.address.wrapping_add(1) % EEPROM_WORDS;
+                    self.shift = self.word(self.address);
+                    self.bits = 0;
+                }
+            }
+            EepromState::Write { address } => {
+                self.shift = (self.shift << 1) | data_in as u16;
+                self.bits += 1;
+
+                if self.bits == 16 {
+                    match address {
+                        Some(address) => self.set_word(address, self.shift),
+                        None => (0..EEPROM_WORDS).for_each(|address| {
+                            self.set_word(address, self.shift);
+                        }),
+                    }
+
+                    self.state = EepromState::Done;
+                    self.data_out = true;
+                }
+            }
+            EepromState::Done => {}
+        }
+    }
+
+    fn execute(&mut self) {
+        let opcode = (self.shift >> 8) & 0b11;
+        let address = self.shift as u8;
+
+        self.state = EepromState::Done;
+        self.data_out = true;
+        self.shift = 0;
+        self.bits = 0;
+
+        match opcode {
+            // READ
+            0b10 => {
+                self.address = address % EEPROM_WORDS;
+                self.shift = self.word(self.address);
+                self.state = EepromState::Read;
+                self.data_out = false;
+            }
+            // WRITE
+            0b01 => {
+                self.state = EepromState::Write {
+                    address: Some(address),
+                }
+            }
+            // ERASE
+            0b11 => self.set_word(address, 0xFFFF),
+            _ => match address >> 6 {
+                // EWDS
+                0b00 => self.write_enabled = false,
+                // WRAL
+                0b01 => self.state = EepromState::Write { address: None },
+                // ERAL
+                0b10 => (0..EEPROM_WORDS).for_each(|address| self.set_word(address, 0xFFFF)),
+                // EWEN
+                _ => self.write_enabled = true,
+            },
+        }
+    }
+}
+
+pub struct Mbc7 {
+    core: CartridgeCore,
+    // 0xA000 - 0xAFFF needs both enables
+    ram_enabled_2: bool,
+    tilt: (f32, f32),
+    latched: (u16, u16),
+    eeprom: Eeprom,
+}
+
+impl Mbc7 {
+    pub fn new(core: CartridgeCore) -> Self {
+        Self {
+            core,
+            ram_enabled_2: false,
+            tilt: (0.0, 0.0),
+            latched: (ACCELEROMETER_ERASED, ACCELEROMETER_ERASED),
+            eeprom: Eeprom::new(),
+        }
+    }
+
+    fn accelerometer(tilt: f32) -> u16 {
+        (ACCELEROMETER_CENTER + tilt.clamp(-1.0, 1.0) * ACCELEROMETER_RANGE) as u16
+    }
+}
+
+impl MemoryBankController for Mbc7 {
+    fn read_rom(&self, address: u16) -> u8 {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF (Bank 00)
+            0x0..=0x3 => self.core.rom_data[address as usize],
+            // 0x4000 - 0x7FFF (Bank 01-7F)
+            0x4..=0x7 => {
+                let offset = self.core.rom_offset * self.core.rom_bank as usize;
+                self.core.rom_data[(address as usize - self.core.rom_offset) + offset]
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x1FFF (RAM enable 1)
+            0x0 | 0x1 => self.core.ram_enabled = value == 0x0A,
+            // 0x2000 - 0x3FFF (ROM bank number)
+            0x2 | 0x3 => self.core.rom_bank = (value & 0x7F) as u16,
+            // 0x4000 - 0x5FFF (RAM enable 2)
+            0x4 | 0x5 => self.ram_enabled_2 = value == 0x40,
+            0x6 | 0x7 => {}
+            _ => unreachable!(),
+        }
+
+        self.core.set_rom_bank();
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if !self.core.ram_enabled || !self.ram_enabled_2 || address >= 0xB000 {
+            return 0xFF;
+        }
+
+        let (x, y) = self.latched;
+
+        match (address >> 4) & 0x0F {
+            0x2 => x as u8,
+            0x3 => (x >> 8) as u8,
+            0x4 => y as u8,
+            0x5 => (y >> 8) as u8,
+            // No Z axis
+            0x6 => 0x00,
+            0x8 => self.eeprom.read(),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if !self.core.ram_enabled || !self.ram_enabled_2 || address >= 0xB000 {
+            return;
+        }
+
+        match (address >> 4) & 0x0F {
+            // Erase the latched values
+            0x0 if value == 0x55 => {
+                self.latched = (ACCELEROMETER_ERASED, ACCELEROMETER_ERASED);
+            }
+            // Latch the current tilt, only works after erasing
+            0x1 if value == 0xAA && self.latched.0 == ACCELEROMETER_ERASED => {
+                self.latched = (
+                    Self::accelerometer(self.tilt.0),
+                    Self::accelerometer(self.tilt.1),
+                );
+            }
+            0x8 => self.eeprom.write(value),
+            _ => {}
+        }
+    }
+
+    fn load_ram(&mut self, ram_data: Vec<u8>) {
+        if ram_data.len() == EEPROM_SIZE {
+            self.eeprom.data = ram_data;
+        }
+    }
+
+    fn save_ram(&self) -> Option<Vec<u8>> {
+        Some(self.eeprom.data.clone())
+    }
+
+    fn take_ram_changed(&mut self) -> bool {
+        std::mem::take(&mut self.eeprom.changed)
+    }
+
+    fn set_tilt(&mut self, x: f32, y: f32) {
+        self.tilt = (x, y);
+    }
+}
+
+#[cfg(test)]
+mod mbc7_tests {
+    use super::*;
+    use crate::cartridge::{header::CartridgeHeader, ROM_BANK_SIZE};
+
+    // Clocks one bit into the EEPROM and returns DO afterwards
+    fn clock(eeprom: &mut Eeprom, bit: bool) -> bool {
+        let data_in = if bit { EEPROM_DI } else { 0 };
+        eeprom.write(EEPROM_CS | data_in);
+        eeprom.write(EEPROM_CS | EEPROM_CLK | data_in);
+        eeprom.read() & EEPROM_DO != 0
+    }
+
+    fn send(eeprom: &mut Eeprom, value: u32, bits: u8) {
+        for bit in (0..bits).rev() {
+            clock(eeprom, (value >> bit) & 1 != 0);
+        }
+        eeprom.write(0x00);
+    }
+
+    #[test]
+    fn eeprom_write_and_read() {
+        let mut eeprom = Eeprom::new();
+
+        // EWEN, then WRITE 0xBEEF to word 5
+        send(&mut eeprom, 0b100_1100_0000, 11);
+        send(&mut eeprom, (0b101_0000_0101 << 16) | 0xBEEF, 27);
+        assert_eq!(eeprom.word(5), 0xBEEF);
+
+        // READ word 5, DO starts with a dummy zero
+        for bit in (0..11).rev() {
+            let dummy = clock(&mut eeprom, (0b110_0000_0101 >> bit) & 1 != 0);
+            if bit == 0 {
+                assert!(!dummy);
+            }
+        }
+        let word = (0..16).fold(0, |word, _| (word << 1) | clock(&mut eeprom, false) as u16);
+        assert_eq!(word, 0xBEEF);
+    }
+
+    #[test]
+    fn eeprom_write_protected() {
+        let mut eeprom = Eeprom::new();
+
+        send(&mut eeprom, (0b101_0000_0000 << 16) | 0x1234, 27);
+        assert_eq!(eeprom.word(0), 0xFFFF);
+    }
+
+    #[test]
+    fn reports_eeprom_changes() {
+        let mut rom_data = vec![0; 2 * ROM_BANK_SIZE];
+        rom_data[0x147] = 0x22;
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+        let mut mbc7 = Mbc7::new(CartridgeCore::new(&rom_data, &header));
+        mbc7.write_rom(0x0000, 0x0A);
+        mbc7.write_rom(0x4000, 0x40);
+
+        // Latching the accelerometer leaves the save alone
+        mbc7.write_ram(0xA000, 0x55);
+        mbc7.write_ram(0xA010, 0xAA);
+        assert!(!mbc7.take_ram_changed());
+
+        send(&mut mbc7.eeprom, 0b100_1100_0000, 11);
+        assert!(!mbc7.take_ram_changed());
+        send(&mut mbc7.eeprom, (0b101_0000_0101 << 16) | 0xBEEF, 27);
+        assert!(mbc7.take_ram_changed());
+    }
+}
@@ -0,0 +1,290 @@
+/*
+ * @file    cartridge/mmm01.rs
+ * @brief   MMM01 multicart Memory Bank Controller implementation.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use crate::cartridge::{
+    core::CartridgeCore, MemoryBankController, CARTRIDGE_TYPE_ADDRESS, MASK_MSB, RAM_ADDRESS,
+    ROM_BANK_SIZE,
+};
+
+// The menu and its header sit in the last 32 KiB of the ROM
+const MENU_SIZE: usize = 2 * ROM_BANK_SIZE;
+
+/*
+ * Returns the menu, whose header describes the cartridge, when the ROM
+ * is an MMM01 multicart. The header at the start belongs to the first game.
+ */
+pub fn menu(rom_data: &[u8]) -> Option<&[u8]> {
+    let menu = rom_data.get(rom_data.len().checked_sub(MENU_SIZE)?..)?;
+
+    matches!(menu[CARTRIDGE_TYPE_ADDRESS], 0x0B..=0x0D).then_some(menu)
+}
+
+/*
+ * Starts out unmapped, showing the menu. The menu picks the game's ROM and
+ * RAM banks along with masks of the bank bits that stay fixed, then sets
+ * the map bit. From there on it acts like an MBC1 restricted to
+ * that game, and only a reset brings back the menu.
+ */
+pub struct Mmm01 {
+    core: CartridgeCore,
+    mapped: bool,
+    // Masked bank bits are fixed by the menu, the others belong to the game
+    rom_bank: u16,
+    rom_bank_mask: u16,
+    ram_bank: u8,
+    ram_bank_mask: u8,
+    ram_banking_mode: bool,
+    mode_locked: bool,
+}
+
+impl Mmm01 {
+    pub fn new(core: CartridgeCore) -> Self {
+        Self {
+            core,
+            mapped: false,
+            rom_bank: 0,
+            rom_bank_mask: 0,
+            ram_bank: 0,
+            ram_bank_mask: 0,
+            ram_banking_mode: false,
+            mode_locked: false,
+        }
+    }
+
+    fn rom_address(&self, bank: usize, address: u16) -> usize {
+        let rom_banks = (self.core.rom_data.len() / ROM_BANK_SIZE).max(1);
+        (bank % rom_banks) * ROM_BANK_SIZE + (address as usize % ROM_BANK_SIZE)
+    }
+
+    // ROM bank bits 0-4 the game can switch
+    fn game_rom_bits(&self) -> u16 {
+        0x1F & !self.rom_bank_mask
+    }
+
+    // RAM bank bits 0-1 the game can switch
+    fn game_ram_bits(&self) -> u8 {
+        0b11 & !self.ram_bank_mask
+    }
+
+    fn ram_address(&self, ram_size: usize, address: u16) -> usize {
+        let ram_bank = if self.ram_banking_mode {
+            self.ram_bank
+        } else {
+            self.ram_bank & !self.game_ram_bits()
+        };
+        let offset = self.core.ram_offset * ram_bank as usize;
+
+        ((address as usize - RAM_ADDRESS) + offset) % ram_size
+    }
+}
+
+impl MemoryBankController for Mmm01 {
+    fn read_rom(&self, address: u16) -> u8 {
+        if !self.mapped {
+            let menu_start = self.core.rom_data.len().saturating_sub(MENU_SIZE);
+            return self.core.rom_data[menu_start + address as usize];
+        }
+
+        let bank = match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF (First bank of the game)
+            0x0..=0x3 => self.rom_bank & !self.game_rom_bits(),
+            // 0x4000 - 0x7FFF (Switchable bank of the game)
+            0x4..=0x7 => {
+                if self.rom_bank & self.game_rom_bits() == 0 {
+                    self.rom_bank | 0x01
+                } else {
+                    self.rom_bank
+                }
+            }
+            _ => unreachable!(),
+        };
+
+        self.core.rom_data[self.rom_address(bank as usize, address)]
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x1FFF (RAM enable, RAM bank mask and map enable)
+            0x0 | 0x1 => {
+                self.core.ram_enabled = value & 0x0F == 0x0A;
+
+                if !self.mapped {
+                    self.ram_bank_mask = (value >> 4) & 0b11;
+                    self.mapped = value & 0x40 != 0;
+                }
+            }
+            // 0x2000 - 0x3FFF (ROM bank, bits 5-6 only while unmapped)
+            0x2 | 0x3 => {
+                let writable = if self.mapped {
+                    self.game_rom_bits()
+                } else {
+                    0x7F
+                };
+
+                self.rom_bank = (self.rom_bank & !writable) | (value as u16 & writable);
+            }
+            // 0x4000 - 0x5FFF (RAM bank, upper ROM bank bits while unmapped)
+            0x4 | 0x5 => {
+                let writable = if self.mapped {
+                    self.game_ram_bits()
+                } else {
+                    0x0F
+                };
+                self.ram_bank = (self.ram_bank & !writable) | (value & writable);
+
+                if !self.mapped {
+                    self.rom_bank = (self.rom_bank & 0x7F) | ((value as u16 & 0x30) << 3);
+                    self.mode_locked = value & 0x40 != 0;
+                }
+            }
+            // 0x6000 - 0x7FFF (Banking mode and ROM bank mask)
+            0x6 | 0x7 => {
+                if !self.mode_locked {
+                    self.ram_banking_mode = value & 0x01 != 0;
+                }
+
+                if !self.mapped {
+                    // Bits 2-5 fix ROM bank bits 1-4, bit 0 always belongs to the game
+                    self.rom_bank_mask = (value as u16 & 0x3C) >> 1;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if !self.core.ram_enabled {
+            return 0xFF;
+        }
+
+        if let Some(ref ram_data) = self.core.ram_data {
+            return ram_data[self.ram_address(ram_data.len(), address)];
+        }
+
+        0xFF
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if !self.core.ram_enabled {
+            return;
+        }
+
+        let ram_address = match self.core.ram_data {
+            Some(ref ram_data) => self.ram_address(ram_data.len(), address),
+            None => return,
+        };
+
+        self.core.write_ram_data(ram_address, value);
+    }
+
+    fn load_ram(&mut self, ram_data: Vec<u8>) {
+        self.core.ram_data = Some(ram_data);
+    }
+
+    fn save_ram(&self) -> Option<Vec<u8>> {
+        self.core.ram_data.clone()
+    }
+
+    fn take_ram_changed(&mut self) -> bool {
+        self.core.take_ram_changed()
+    }
+}
+
+#[cfg(test)]
+mod mmm01_tests {
+    use super::*;
+    use crate::cartridge::header::CartridgeHeader;
+
+    // 32 banks that start with their own number, the menu in banks 30 and 31
+    fn mmm01() -> Mmm01 {
+        let mut rom_data = vec![0; 32 * ROM_BANK_SIZE];
+        for bank in 0..32 {
+            rom_data[bank * ROM_BANK_SIZE] = bank as u8;
+        }
+        let menu_start = rom_data.len() - MENU_SIZE;
+        rom_data[menu_start + CARTRIDGE_TYPE_ADDRESS] = 0x0B;
+        rom_data[menu_start + 0x149] = 0x03;
+
+        let header = CartridgeHeader::parse(menu(&rom_data).unwrap()).unwrap();
+        Mmm01::new(CartridgeCore::new(&rom_data, &header))
+    }
+
+    #[test]
+    fn finds_menu() {
+        let mbc = mmm01();
+        assert!(menu(&mbc.core.rom_data).is_some());
+
+        // A regular ROM has no MMM01 header at the end
+        assert!(menu(&vec![0; 32 * ROM_BANK_SIZE]).is_none());
+        assert!(menu(&[0; 0x100]).is_none());
+    }
+
+    #[test]
+    fn shows_menu_until_mapped() {
+        let mut mbc = mmm01();
+        assert_eq!(mbc.read_rom(0x0000), 30);
+        assert_eq!(mbc.read_rom(0x4000), 31);
+
+        // Bank writes don't switch anything while the menu is shown
+        mbc.write_rom(0x2000, 0x08);
+        assert_eq!(mbc.read_rom(0x4000), 31);
+
+        // Without a mask the game spans the whole ROM
+        mbc.write_rom(0x0000, 0x40);
+        assert_eq!(mbc.read_rom(0x0000), 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x08);
+    }
+
+    #[test]
+    fn masks_game_banks() {
+        let mut mbc = mmm01();
+
+        // An eight bank game at bank 8, bits 3 and 4 are fixed by the menu
+        mbc.write_rom(0x2000, 0x08);
+        mbc.write_rom(0x6000, 0x30);
+        mbc.write_rom(0x0000, 0x40);
+
+        mbc.write_rom(0x2000, 0x1F);
+        assert_eq!(mbc.read_rom(0x4000), 0x0F);
+        assert_eq!(mbc.read_rom(0x0000), 0x08);
+
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x09);
+    }
+
+    #[test]
+    fn latches_mapping() {
+        let mut mbc = mmm01();
+        mbc.write_rom(0x2000, 0x08);
+        mbc.write_rom(0x6000, 0x30);
+        mbc.write_rom(0x0000, 0x40);
+
+        // Neither the map bit nor the masks can be changed by the game
+        mbc.write_rom(0x0000, 0x00);
+        mbc.write_rom(0x6000, 0x00);
+        mbc.write_rom(0x2000, 0x1F);
+        assert_eq!(mbc.read_rom(0x0000), 0x08);
+        assert_eq!(mbc.read_rom(0x4000), 0x0F);
+    }
+
+    #[test]
+    fn masks_ram_banks() {
+        let mut mbc = mmm01();
+
+        // RAM bank 2 with both bank bits fixed, enabled in the same write as the mapping
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_rom(0x0000, 0x7A);
+
+        mbc.write_rom(0x4000, 0x01);
+        mbc.write_ram(0xA000, 0x42);
+        assert_eq!(mbc.core.ram_data.as_ref().unwrap()[2 * 0x2000], 0x42);
+        assert_eq!(mbc.read_ram(0xA000), 0x42);
+
+        mbc.write_rom(0x0000, 0x00);
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+    }
+}
@@ -5,17 +5,45 @@
  * @date    June 8, 2024
  */
 
+pub mod camera;
 mod core;
+pub mod gbs;
+pub mod header;
+mod huc1;
+mod huc3;
+pub mod image_source;
 mod mbc0;
 mod mbc1;
 mod mbc2;
 mod mbc3;
 mod mbc5;
+mod mbc6;
+mod mbc7;
+mod mmm01;
+mod save_file;
+pub mod save_format;
 
-use std::{error::Error, fs::File, io::Write};
+use std::{error::Error, path::Path};
 
 use crate::{
-    cartridge::{core::CartridgeCore, mbc0::Mbc0, mbc1::Mbc1, mbc2::Mbc2, mbc3::Mbc3, mbc5::Mbc5},
+    cartridge::{
+        camera::PocketCamera,
+        core::CartridgeCore,
+        gbs::{Gbs, GBS_CARTRIDGE_TYPE},
+        header::CartridgeHeader,
+        huc1::Huc1,
+        huc3::Huc3,
+        image_source::ImageSource,
+        mbc0::Mbc0,
+        mbc1::Mbc1,
+        mbc2::Mbc2,
+        mbc3::Mbc3,
+        mbc5::Mbc5,
+        mbc6::Mbc6,
+        mbc7::Mbc7,
+        mmm01::Mmm01,
+        save_format::SaveFormat,
+    },
     MemoryAccess,
 };
 
@@ -35,10 +63,31 @@ pub trait MemoryBankController {
     fn write_ram(&mut self, address: u16, value: u8);
     fn load_ram(&mut self, ram_data: Vec<u8>);
     fn save_ram(&self) -> Option<Vec<u8>>;
+    // Whether what save_ram returns changed since the last call
+    fn take_ram_changed(&mut self) -> bool;
+
+    // Tilt of the cartridge from -1.0 to 1.0, only MBC7 has an accelerometer
+    fn set_tilt(&mut self, _x: f32, _y: f32) {}
+
+    // Picture seen by the Game Boy Camera sensor
+    fn set_image_source(&mut self, _source: Box<dyn ImageSource>) {}
+
+    // For hardware on the cartridge that takes time, like the camera sensor
+    fn tick(&mut self, _m_cycles: u8) {}
+
+    // Rumble strength from 0.0 to 1.0 since the last call
+    fn take_rumble(&mut self) -> f32 {
+        0.0
+    }
 }
 
 pub struct Cartridge {
     pub mbc: Box<dyn MemoryBankController>,
+    pub header: CartridgeHeader,
+    // Cartridge storage changed since the last save
+    pub ram_dirty: bool,
+    // The save from before this session is backed up when it is first replaced
+    backed_up: bool,
 }
 
 impl MemoryAccess for Cartridge {
@@ -56,8 +105,15 @@ impl MemoryAccess for Cartridge {
 
     fn write_byte(&mut self, address: u16, value: u8) {
         match (address & MASK_MSB) >> 12 {
-            0x0..=0x7 => self.mbc.write_rom(address, value),
-            0xA | 0xB => self.mbc.write_ram(address, value),
+            // MBC6 flash is written through the ROM area
+            0x0..=0x7 => {
+                self.mbc.write_rom(address, value);
+                self.ram_dirty |= self.mbc.take_ram_changed();
+            }
+            0xA | 0xB => {
+                self.mbc.write_ram(address, value);
+                self.ram_dirty |= self.mbc.take_ram_changed();
+            }
             _ => eprintln!(
                 "Unknown address: {:#X} Can't write byte: {:#X}",
                 address, value
@@ -67,32 +123,92 @@ impl MemoryAccess for Cartridge {
 }
 
 impl Cartridge {
-    pub fn build(rom_data: Vec<u8>) -> Result<Self, &'static str> {
-        let core = CartridgeCore::new(&rom_data);
+    pub fn build(rom_data: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        let header = CartridgeHeader::parse(mmm01::menu(&rom_data).unwrap_or(&rom_data))?;
+        for warning in header.warnings() {
+            eprintln!("Warning: {warning}.");
+        }
+
+        let core = CartridgeCore::new(&rom_data, &header);
 
-        let mbc: Box<dyn MemoryBankController> = match rom_data[CARTRIDGE_TYPE_ADDRESS] {
+        let mbc: Box<dyn MemoryBankController> = match header.cartridge_type {
             0x0 => Box::new(Mbc0::new(core)),
             0x01..=0x03 => Box::new(Mbc1::new(core)),
             0x05 | 0x06 => Box::new(Mbc2::new(core)),
+            0x0B..=0x0D => Box::new(Mmm01::new(core)),
             0x0F..=0x13 => Box::new(Mbc3::new(core)),
-            0x19..=0x1E => Box::new(Mbc5::new(core)),
-            _ => return Err("Error: Cartridge type not supported"),
+            0x19..=0x1E => Box::new(Mbc5::new(core, header.cartridge_type >= 0x1C)),
+            0x20 => Box::new(Mbc6::new(core)),
+            0x22 => Box::new(Mbc7::new(core)),
+            0xFE => Box::new(Huc3::new(core)),
+            0xFF => Box::new(Huc1::new(core)),
+            GBS_CARTRIDGE_TYPE => Box::new(Gbs::new(core)),
+            0xFC => Box::new(PocketCamera::new(core)),
+            _ => return Err("Error: Cartridge type not supported".into()),
         };
 
-        Ok(Self { mbc })
+        Ok(Self {
+            mbc,
+            header,
+            ram_dirty: false,
+            backed_up: false,
+        })
     }
 
     pub fn load_game(&mut self, ram_data: Vec<u8>) {
+        // Cartridges without RAM have nothing to restore
+        let Some(expected) = self.mbc.save_ram() else {
+            return;
+        };
+
+        let (ram_data, warnings) = save_format::decode(ram_data, expected.len());
+        for warning in warnings {
+            eprintln!("Warning: {warning}.");
+        }
+
         self.mbc.load_ram(ram_data);
         println!("Game loaded.")
     }
 
-    pub fn save_game(&self, save_path: &str) -> Result<(), Box<dyn Error>> {
-        if let Some(ram_data) = self.mbc.save_ram() {
-            let mut file = File::create(save_path)?;
-            file.write_all(&ram_data)?;
+    // Replaces the RAM with a save from another emulator, which autosave then keeps
+    pub fn import_save(&mut self, save_path: &Path) -> Result<(), Box<dyn Error>> {
+        if self.mbc.save_ram().is_none() {
+            return Err("Cartridge has no RAM to import into".into());
         }
 
+        self.load_game(std::fs::read(save_path)?);
+        self.ram_dirty = true;
+
         Ok(())
     }
+
+    pub fn export_save(&self, save_path: &Path, format: SaveFormat) -> Result<(), Box<dyn Error>> {
+        let ram_data = self
+            .mbc
+            .save_ram()
+            .ok_or("Cartridge has no RAM to export")?;
+
+        save_file::write(save_path, &format.encode(&ram_data))
+    }
+
+    // Whether anything was saved, cartridges without a battery lose their RAM anyway
+    pub fn save_game(&mut self, save_path: &Path) -> Result<bool, Box<dyn Error>> {
+        if !self.header.has_battery() {
+            return Ok(false);
+        }
+
+        let Some(ram_data) = self.mbc.save_ram() else {
+            return Ok(false);
+        };
+
+        if !self.backed_up {
+            save_file::backup(save_path)?;
+            self.backed_up = true;
+        }
+
+        save_file::write(save_path, &ram_data)?;
+        self.ram_dirty = false;
+
+        Ok(true)
+    }
 }
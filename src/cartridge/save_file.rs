@@ -0,0 +1,128 @@
+/*
+ * @file    cartridge/save_file.rs
+ * @brief   Writes battery saves atomically and keeps backups of older ones.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::export::{is_timestamped, timestamp};
+
+const BACKUP_DIRECTORY: &str = "backups";
+// Older backups of the same save are deleted
+const MAX_BACKUPS: usize = 5;
+
+/*
+ * The data goes to a temporary file next to the save first, which then
+ * replaces it by renaming. A crash halfway through leaves the old save
+ * intact instead of a truncated one.
+ */
+pub fn write(save_path: &Path, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut temp_path = save_path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    if let Some(directory) = save_path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp_path, save_path)?;
+
+    Ok(())
+}
+
+// Copies the current save to a timestamped file, there is nothing to do without one
+pub fn backup(save_path: &Path) -> Result<(), Box<dyn Error>> {
+    if !save_path.exists() {
+        return Ok(());
+    }
+
+    let directory = save_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(BACKUP_DIRECTORY);
+    fs::create_dir_all(&directory)?;
+
+    let stem = save_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "save".to_string());
+
+    fs::copy(
+        save_path,
+        directory.join(format!("{}_{}.sav", stem, timestamp())),
+    )?;
+
+    remove_old_backups(&directory, &stem)
+}
+
+// Timestamps sort by age, so everything but the last few names goes
+fn remove_old_backups(directory: &Path, stem: &str) -> Result<(), Box<dyn Error>> {
+    let mut backups = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy())
+                .is_some_and(|name| is_timestamped(&name, stem, "sav"))
+        })
+        .collect::<Vec<PathBuf>>();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(MAX_BACKUPS);
+    for path in &backups[..excess] {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod save_file_tests {
+    use super::*;
+
+    #[test]
+    fn keeps_latest_backups() {
+        let directory = std::env::temp_dir().join(format!("gaemboi_saves_{}", std::process::id()));
+        let backups = directory.join(BACKUP_DIRECTORY);
+        fs::create_dir_all(&backups).unwrap();
+
+        for second in 0..MAX_BACKUPS + 2 {
+            let name = format!("game_2026-01-01_00-00-0{}.sav", second);
+            fs::write(backups.join(name), [0]).unwrap();
+        }
+        fs::write(backups.join("other_2026-01-01_00-00-00.sav"), [0]).unwrap();
+        // Backups of a game whose name starts with the same stem sort first
+        fs::write(backups.join("game_dx_2026-01-01_00-00-00.sav"), [0]).unwrap();
+
+        let save_path = directory.join("game.sav");
+        write(&save_path, &[1, 2, 3]).unwrap();
+        backup(&save_path).unwrap();
+
+        let mut names = fs::read_dir(&backups)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<String>>();
+        names.sort();
+
+        assert_eq!(fs::read(&save_path).unwrap(), [1, 2, 3]);
+        assert_eq!(names.len(), MAX_BACKUPS + 2);
+        assert!(!names.contains(&"game_2026-01-01_00-00-00.sav".to_string()));
+        assert!(names.contains(&"game_2026-01-01_00-00-06.sav".to_string()));
+        assert!(names.contains(&"other_2026-01-01_00-00-00.sav".to_string()));
+        assert!(names.contains(&"game_dx_2026-01-01_00-00-00.sav".to_string()));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
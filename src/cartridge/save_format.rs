@@ -0,0 +1,166 @@
+/*
+ * @file    cartridge/save_format.rs
+ * @brief   Converts battery saves from and to the layouts of other emulators.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::rom_file;
+
+/*
+ * VBA-M, BGB and SameBoy append the MBC3 clock to the RAM: seconds, minutes,
+ * hours, days low and days high as 32-bit values, the same again for the
+ * latched copy, then the time of saving as a 64-bit UNIX timestamp.
+ * Older VBA versions store the timestamp in 32 bits.
+ */
+const RTC_REGISTERS_SIZE: usize = 2 * 5 * 4;
+const RTC_FOOTER_SIZE: usize = RTC_REGISTERS_SIZE + 8;
+const RTC_FOOTER_SIZE_LEGACY: usize = RTC_REGISTERS_SIZE + 4;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SaveFormat {
+    // Plain RAM, as written by most emulators and flash carts
+    Raw,
+    // Plain RAM, named the way RetroArch cores expect it
+    Srm,
+    RtcFooter,
+    RtcFooterLegacy,
+}
+
+pub enum SaveTransfer {
+    Import,
+    Export(SaveFormat),
+}
+
+impl SaveFormat {
+    pub const ALL: [SaveFormat; 4] = [
+        SaveFormat::Raw,
+        SaveFormat::Srm,
+        SaveFormat::RtcFooter,
+        SaveFormat::RtcFooterLegacy,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SaveFormat::Raw => "Raw (.sav)",
+            SaveFormat::Srm => "RetroArch (.srm)",
+            SaveFormat::RtcFooter => "RTC Footer, 48 bytes (VBA-M/BGB/SameBoy)",
+            SaveFormat::RtcFooterLegacy => "RTC Footer, 44 bytes (VBA)",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            SaveFormat::Srm => "srm",
+            _ => "sav",
+        }
+    }
+
+    // The clock isn't emulated, so footers hold a stopped clock at zero from now on
+    pub fn encode(self, ram_data: &[u8]) -> Vec<u8> {
+        let mut data = ram_data.to_vec();
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        match self {
+            SaveFormat::Raw | SaveFormat::Srm => {}
+            SaveFormat::RtcFooter => {
+                data.extend_from_slice(&[0; RTC_REGISTERS_SIZE]);
+                data.extend_from_slice(&saved_at.to_le_bytes());
+            }
+            SaveFormat::RtcFooterLegacy => {
+                data.extend_from_slice(&[0; RTC_REGISTERS_SIZE]);
+                data.extend_from_slice(&(saved_at as u32).to_le_bytes());
+            }
+        }
+
+        data
+    }
+}
+
+/*
+ * Fits a save in any of the formats above to the size the cartridge expects,
+ * which is the header RAM size plus whatever the mapper keeps next to it.
+ * Clock footers are dropped, other sizes are padded with zeros or truncated.
+ */
+pub fn decode(mut data: Vec<u8>, expected_size: usize) -> (Vec<u8>, Vec<String>) {
+    let mut warnings = Vec::new();
+
+    let footer_size = data.len().saturating_sub(expected_size);
+    if footer_size == RTC_FOOTER_SIZE || footer_size == RTC_FOOTER_SIZE_LEGACY {
+        data.truncate(expected_size);
+        warnings.push(format!(
+            "Dropped the {}-byte RTC footer of the save, the clock isn't emulated",
+            footer_size
+        ));
+    } else if data.len() != expected_size {
+        warnings.push(format!(
+            "Save has {} bytes but the cartridge expects {}, {}",
+            data.len(),
+            expected_size,
+            if data.len() < expected_size {
+                "padding with zeros"
+            } else {
+                "truncating"
+            }
+        ));
+        data.resize(expected_size, 0);
+    }
+
+    (data, warnings)
+}
+
+// Default file name for exporting the save of a ROM
+pub fn export_file_name(rom_path: Option<&str>, format: SaveFormat) -> String {
+    let stem = rom_path
+        .and_then(|rom_path| {
+            rom_file::base_path(rom_path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "save".to_string());
+
+    format!("{}.{}", stem, format.extension())
+}
+
+#[cfg(test)]
+mod save_format_tests {
+    use super::*;
+
+    #[test]
+    fn footers_round_trip() {
+        let ram_data = vec![0x42; 8 * 1024];
+
+        for format in SaveFormat::ALL {
+            let (decoded, warnings) = decode(format.encode(&ram_data), ram_data.len());
+            assert_eq!(decoded, ram_data);
+            assert_eq!(
+                warnings.len(),
+                matches!(format, SaveFormat::RtcFooter | SaveFormat::RtcFooterLegacy) as usize
+            );
+        }
+
+        assert_eq!(SaveFormat::RtcFooter.encode(&ram_data).len(), 8 * 1024 + 48);
+        assert_eq!(
+            SaveFormat::RtcFooterLegacy.encode(&ram_data).len(),
+            8 * 1024 + 44
+        );
+    }
+
+    #[test]
+    fn fits_wrong_sizes() {
+        let (padded, warnings) = decode(vec![0x42; 2 * 1024], 8 * 1024);
+        assert_eq!(padded.len(), 8 * 1024);
+        assert_eq!(padded[2 * 1024], 0);
+        assert_eq!(warnings.len(), 1);
+
+        // Flash carts often write a full 32 KiB
+        let (truncated, warnings) = decode(vec![0x42; 32 * 1024], 8 * 1024);
+        assert_eq!(truncated, vec![0x42; 8 * 1024]);
+        assert_eq!(warnings.len(), 1);
+    }
+}
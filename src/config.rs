@@ -5,20 +5,47 @@
  * @date    May 23, 2024
  */
 
+use std::path::{Path, PathBuf};
+
+// ROMs passed by name alone are also looked for in here
+const ROM_DIRECTORY: &str = "roms";
+
 pub struct Config {
     pub file_path: Option<String>,
+    pub saves_directory: Option<PathBuf>,
 }
 
 impl Config {
     pub fn build(args: &[String]) -> Self {
         let mut file_path = None;
+        let mut saves_directory = None;
 
-        for arg in args {
-            if !arg.contains("gaemboi") {
-                file_path = Some("roms/".to_owned() + arg)
+        // The first argument is the program itself
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--saves-dir" => match args.next() {
+                    Some(directory) => saves_directory = Some(PathBuf::from(directory)),
+                    None => eprintln!("Warning: --saves-dir needs a directory."),
+                },
+                _ if arg.starts_with("--") => eprintln!("Warning: Unknown option {arg}."),
+                _ => file_path = Some(rom_path(arg)),
             }
         }
 
-        Self { file_path }
+        Self {
+            file_path,
+            saves_directory,
+        }
     }
 }
+
+// Absolute or relative to the working directory, falling back to the ROM directory
+fn rom_path(arg: &str) -> String {
+    let in_rom_directory = Path::new(ROM_DIRECTORY).join(arg);
+    if !Path::new(arg).exists() && in_rom_directory.exists() {
+        return in_rom_directory.to_string_lossy().into_owned();
+    }
+
+    arg.to_string()
+}
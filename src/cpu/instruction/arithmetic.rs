@@ -7,7 +7,7 @@
 
 use crate::cpu::{
     instruction::{CycleDuration, Target},
-    Cpu, MemoryAccess,
+    Cpu,
 };
 
 // Adds to the 8-bit A register, the 8-bit register r,
@@ -32,7 +32,7 @@ pub fn add_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // Adds to the 8-bit A register, the immediate data n,
 // and stores the result back into the A register
 pub fn add_n(cpu: &mut Cpu) -> CycleDuration {
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next());
     let a = cpu.registers.get_a();
 
     let half_carry = ((a & 0x0F) + (n & 0x0F)) > 0x0F;
@@ -54,7 +54,7 @@ pub fn add_n(cpu: &mut Cpu) -> CycleDuration {
 pub fn add_a_hl(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
     let hl = cpu.registers.get_hl();
-    let data = cpu.memory_bus.read_byte(hl);
+    let data = cpu.memory_bus.cpu_read(hl);
 
     let result = a.wrapping_add(data);
     cpu.registers.set_a(result);
@@ -106,7 +106,7 @@ pub fn add_hl_sp(cpu: &mut Cpu) -> CycleDuration {
 
 // Add the signed immediate value to SP
 pub fn add_sp_n(cpu: &mut Cpu) -> CycleDuration {
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next()) as i8;
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next()) as i8;
     let sp = cpu.stack_pointer as i32;
     let result = sp.wrapping_add(n as i32) as u16;
 
@@ -149,7 +149,7 @@ pub fn adc_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // the immediate data n, and stores the result back
 // into the A register
 pub fn adc_n(cpu: &mut Cpu) -> CycleDuration {
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next());
     let a = cpu.registers.get_a();
 
     let carry: u8 = cpu.registers.flags.get_carry().into();
@@ -174,7 +174,7 @@ pub fn adc_hl(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
 
     let hl = cpu.registers.get_hl();
-    let value = cpu.memory_bus.read_byte(hl);
+    let value = cpu.memory_bus.cpu_read(hl);
 
     let carry: u8 = cpu.registers.flags.get_carry().into();
     let half_carry = (a & 0x0F) + (value & 0x0F) + (carry & 0x0F) > 0x0F;
@@ -213,7 +213,7 @@ pub fn sub_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // immediate data n, and stores the result
 // back into the A register
 pub fn sub_n(cpu: &mut Cpu) -> CycleDuration {
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next());
     let a = cpu.registers.get_a();
 
     let result = a.wrapping_sub(n);
@@ -234,7 +234,7 @@ pub fn sub_n(cpu: &mut Cpu) -> CycleDuration {
 pub fn sub_hl(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
     let hl = cpu.registers.get_hl();
-    let value = cpu.memory_bus.read_byte(hl);
+    let value = cpu.memory_bus.cpu_read(hl);
     let half_carry = (a & 0x0F) < (value & 0x0F);
 
     let result = a.wrapping_sub(value);
@@ -276,7 +276,7 @@ pub fn sbc_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // stores the result back into the A register
 pub fn sbc_n(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next());
 
     let carry: u8 = cpu.registers.flags.get_carry().into();
 
@@ -302,7 +302,7 @@ pub fn sbc_hl(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
     let carry: u8 = cpu.registers.flags.get_carry().into();
     let hl = cpu.registers.get_hl();
-    let data = cpu.memory_bus.read_byte(hl);
+    let data = cpu.memory_bus.cpu_read(hl);
 
     let result = a.wrapping_sub(carry).wrapping_sub(data);
     let half_carry = (a ^ data ^ result) & 0x10 != 0;
@@ -339,7 +339,7 @@ pub fn and_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // 8-bit A register and immediate data n, and
 // stores the result back into the A register
 pub fn and_n(cpu: &mut Cpu) -> CycleDuration {
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next());
     let a = cpu.registers.get_a();
     let result = a & n;
     cpu.registers.set_a(result);
@@ -359,7 +359,7 @@ pub fn and_n(cpu: &mut Cpu) -> CycleDuration {
 pub fn and_hl(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
     let hl = cpu.registers.get_hl();
-    let data = cpu.memory_bus.read_byte(hl);
+    let data = cpu.memory_bus.cpu_read(hl);
 
     let result = a & data;
     cpu.registers.set_a(result);
@@ -399,11 +399,11 @@ pub fn inc_rr(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // by the 16-bit register HL
 pub fn inc_hl(cpu: &mut Cpu) -> CycleDuration {
     let hl = cpu.registers.get_hl();
-    let data = cpu.memory_bus.read_byte(hl);
+    let data = cpu.memory_bus.cpu_read(hl);
     let half_carry = (data & 0x0F).wrapping_add(1) > 0x0F;
 
     let result = data.wrapping_add(1);
-    cpu.memory_bus.write_byte(hl, result);
+    cpu.memory_bus.cpu_write(hl, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
@@ -455,10 +455,10 @@ pub fn dec_sp(cpu: &mut Cpu) -> CycleDuration {
 // specified by the 16-bit register HL
 pub fn dec_hl(cpu: &mut Cpu) -> CycleDuration {
     let hl = cpu.registers.get_hl();
-    let value = cpu.memory_bus.read_byte(hl);
+    let value = cpu.memory_bus.cpu_read(hl);
     let result = value.wrapping_sub(1);
 
-    cpu.memory_bus.write_byte(hl, result);
+    cpu.memory_bus.cpu_write(hl, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(true);
@@ -491,7 +491,7 @@ pub fn or_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // result back into the A register
 pub fn or_n(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next());
 
     let result = a | n;
 
@@ -512,7 +512,7 @@ pub fn or_n(cpu: &mut Cpu) -> CycleDuration {
 pub fn or_hl(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
     let hl = cpu.registers.get_hl();
-    let value = cpu.memory_bus.read_byte(hl);
+    let value = cpu.memory_bus.cpu_read(hl);
 
     let result = a | value;
     cpu.registers.set_a(result);
@@ -549,7 +549,7 @@ pub fn xor_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // result back into the A register
 pub fn xor_n(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next());
 
     let result = a ^ n;
     cpu.registers.set_a(result);
@@ -569,7 +569,7 @@ pub fn xor_n(cpu: &mut Cpu) -> CycleDuration {
 pub fn xor_hl(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
     let hl = cpu.registers.get_hl();
-    let data = cpu.memory_bus.read_byte(hl);
+    let data = cpu.memory_bus.cpu_read(hl);
 
     let result = a ^ data;
     cpu.registers.set_a(result);
@@ -587,7 +587,7 @@ pub fn xor_hl(cpu: &mut Cpu) -> CycleDuration {
 // This instructions basically identical to SUB n,
 // but does not update the A register
 pub fn cp_n(cpu: &mut Cpu) -> CycleDuration {
-    let byte = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let byte = cpu.memory_bus.cpu_read(cpu.program_counter.next());
     let a = cpu.registers.get_a();
 
     let zero = a.wrapping_sub(byte) == 0;
@@ -631,7 +631,7 @@ pub fn cp_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 pub fn cp_hl(cpu: &mut Cpu) -> CycleDuration {
     let a = cpu.registers.get_a();
     let hl = cpu.registers.get_hl();
-    let data = cpu.memory_bus.read_byte(hl);
+    let data = cpu.memory_bus.cpu_read(hl);
 
     let result = a.wrapping_sub(data);
 
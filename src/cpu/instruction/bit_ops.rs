@@ -7,7 +7,7 @@
 
 use crate::cpu::{
     instruction::{CycleDuration, Target},
-    Cpu, MemoryAccess,
+    Cpu,
 };
 
 // Test bit at position in target register,
@@ -30,7 +30,7 @@ pub fn bit_r(cpu: &mut Cpu, position: u8, target: Target) -> CycleDuration {
 // set the zero flag if bit not set
 pub fn bit_hl(cpu: &mut Cpu, position: u8) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let bitmask: u8 = 1 << position;
     let result = byte & bitmask;
@@ -58,12 +58,12 @@ pub fn res_r(cpu: &mut Cpu, position: u8, target: Target) -> CycleDuration {
 // Set bit at position in the byte pointed by HL to 0
 pub fn res_hl(cpu: &mut Cpu, position: u8) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let bitmask: u8 = !(1 << position);
     let result = byte & bitmask;
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     CycleDuration::Default
 }
@@ -83,12 +83,12 @@ pub fn set_r(cpu: &mut Cpu, position: u8, target: Target) -> CycleDuration {
 // Set bit at position in the byte pointed by HL to 1
 pub fn set_hl(cpu: &mut Cpu, position: u8) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let bitmask: u8 = 1 << position;
     let result = byte | bitmask;
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     CycleDuration::Default
 }
@@ -7,7 +7,7 @@
 
 use crate::cpu::{
     instruction::{CycleDuration, Flag},
-    Cpu, MemoryAccess,
+    Cpu,
 };
 
 // Unconditional jump to the absolute address
@@ -63,7 +63,7 @@ pub fn jp_hl(cpu: &mut Cpu) -> CycleDuration {
 // Unconditional jump to the relative address
 // specified by the signed 8-bit immediate value
 pub fn jr_e(cpu: &mut Cpu) -> CycleDuration {
-    let address = cpu.memory_bus.read_byte(cpu.program_counter.next()) as i8;
+    let address = cpu.memory_bus.cpu_read(cpu.program_counter.next()) as i8;
     cpu.program_counter.relative_jump(address);
 
     CycleDuration::Default
@@ -73,7 +73,7 @@ pub fn jr_e(cpu: &mut Cpu) -> CycleDuration {
 // by the signed 8-bit immediate value, depending on the
 // flag condition
 pub fn jr_c_e(cpu: &mut Cpu, flag: Flag) -> CycleDuration {
-    let address = cpu.memory_bus.read_byte(cpu.program_counter.next()) as i8;
+    let address = cpu.memory_bus.cpu_read(cpu.program_counter.next()) as i8;
     let flag = cpu.registers.flags.get_flag(flag);
 
     if flag {
@@ -88,7 +88,7 @@ pub fn jr_c_e(cpu: &mut Cpu, flag: Flag) -> CycleDuration {
 // by the signed 8-bit immediate value, depending on the
 // flag condition
 pub fn jr_nc_e(cpu: &mut Cpu, flag: Flag) -> CycleDuration {
-    let address = cpu.memory_bus.read_byte(cpu.program_counter.next()) as i8;
+    let address = cpu.memory_bus.cpu_read(cpu.program_counter.next()) as i8;
     let flag = cpu.registers.flags.get_flag(flag);
 
     if !flag {
@@ -7,7 +7,7 @@
 
 use crate::cpu::{
     instruction::{CycleDuration, Target},
-    Cpu, MemoryAccess,
+    Cpu,
 };
 
 // 8-bit load instructions transfer one byte of data
@@ -26,7 +26,7 @@ pub fn ld_rr_r(cpu: &mut Cpu, pair_target: Target, reg_target: Target) -> CycleD
     let address = cpu.registers.get_pair(&pair_target);
     let value = cpu.registers.get_register(&reg_target);
 
-    cpu.memory_bus.write_byte(address, value);
+    cpu.memory_bus.cpu_write(address, value);
 
     CycleDuration::Default
 }
@@ -44,7 +44,7 @@ pub fn ld_rr_nn(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // by the 16-bit register to the 8-bit register
 pub fn ld_r_rr(cpu: &mut Cpu, reg_target: Target, pair_target: Target) -> CycleDuration {
     let address = cpu.registers.get_pair(&pair_target);
-    let value = cpu.memory_bus.read_byte(address);
+    let value = cpu.memory_bus.cpu_read(address);
 
     cpu.registers.set_register(reg_target, value);
 
@@ -53,7 +53,7 @@ pub fn ld_r_rr(cpu: &mut Cpu, reg_target: Target, pair_target: Target) -> CycleD
 
 // Load the immediate 8-bit value to the 8-bit target register
 pub fn ld_r_n(cpu: &mut Cpu, target: Target) -> CycleDuration {
-    let value = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let value = cpu.memory_bus.cpu_read(cpu.program_counter.next());
     cpu.registers.set_register(target, value);
 
     CycleDuration::Default
@@ -63,9 +63,9 @@ pub fn ld_r_n(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // HL, the immediate data n
 pub fn ld_hl_n(cpu: &mut Cpu) -> CycleDuration {
     let hl = cpu.registers.get_hl();
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next());
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next());
 
-    cpu.memory_bus.write_byte(hl, n);
+    cpu.memory_bus.cpu_write(hl, n);
 
     CycleDuration::Default
 }
@@ -77,7 +77,7 @@ pub fn ld_hl_plus_a(cpu: &mut Cpu) -> CycleDuration {
     let hl = cpu.registers.get_hl();
     let a = cpu.registers.get_a();
 
-    cpu.memory_bus.write_byte(hl, a);
+    cpu.memory_bus.cpu_write(hl, a);
     cpu.registers.set_hl(hl.wrapping_add(1));
 
     CycleDuration::Default
@@ -89,7 +89,7 @@ pub fn ld_hl_plus_a(cpu: &mut Cpu) -> CycleDuration {
 pub fn ld_hl_minus_a(cpu: &mut Cpu) -> CycleDuration {
     let hl = cpu.registers.get_hl();
     let a = cpu.registers.get_a();
-    cpu.memory_bus.write_byte(hl, a);
+    cpu.memory_bus.cpu_write(hl, a);
 
     cpu.registers.set_hl(hl.wrapping_sub(1));
 
@@ -98,7 +98,7 @@ pub fn ld_hl_minus_a(cpu: &mut Cpu) -> CycleDuration {
 
 // Add the signed immediate value to SP and store the result in HL
 pub fn ld_hl_sp_plus_n(cpu: &mut Cpu) -> CycleDuration {
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next()) as i8;
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next()) as i8;
     let sp = cpu.stack_pointer as i32;
 
     let result = sp.wrapping_add(n as i32) as u16;
@@ -121,7 +121,7 @@ pub fn ld_hl_sp_plus_n(cpu: &mut Cpu) -> CycleDuration {
 // of HL is incremented after the memory read
 pub fn ld_a_hl_plus(cpu: &mut Cpu) -> CycleDuration {
     let hl = cpu.registers.get_hl();
-    let value = cpu.memory_bus.read_byte(hl);
+    let value = cpu.memory_bus.cpu_read(hl);
 
     cpu.registers.set_a(value);
     cpu.registers.set_hl(hl.wrapping_add(1));
@@ -134,7 +134,7 @@ pub fn ld_a_hl_plus(cpu: &mut Cpu) -> CycleDuration {
 // of HL is decremented after the memory read
 pub fn ld_a_hl_minus(cpu: &mut Cpu) -> CycleDuration {
     let hl = cpu.registers.get_hl();
-    let value = cpu.memory_bus.read_byte(hl);
+    let value = cpu.memory_bus.cpu_read(hl);
 
     cpu.registers.set_a(value);
     cpu.registers.set_hl(hl.wrapping_sub(1));
@@ -146,7 +146,7 @@ pub fn ld_a_hl_minus(cpu: &mut Cpu) -> CycleDuration {
 // address specified by the 16-bit operand nn
 pub fn ld_a_nn(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.get_nn_little_endian();
-    let value = cpu.memory_bus.read_byte(address);
+    let value = cpu.memory_bus.cpu_read(address);
 
     cpu.registers.set_a(value);
 
@@ -159,7 +159,7 @@ pub fn ld_nn_a(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.get_nn_little_endian();
     let a = cpu.registers.get_a();
 
-    cpu.memory_bus.write_byte(address, a);
+    cpu.memory_bus.cpu_write(address, a);
 
     CycleDuration::Default
 }
@@ -170,11 +170,11 @@ pub fn ld_nn_a(cpu: &mut Cpu) -> CycleDuration {
 // byte to 0xFF and the least significant byte to the value of
 // n, so the possible range is 0xFF00-0xFFFF
 pub fn ldh_n_a(cpu: &mut Cpu) -> CycleDuration {
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next()) as u16;
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next()) as u16;
     let address = 0xFF00 | n;
 
     let value = cpu.registers.get_a();
-    cpu.memory_bus.write_byte(address, value);
+    cpu.memory_bus.cpu_write(address, value);
 
     CycleDuration::Default
 }
@@ -185,10 +185,10 @@ pub fn ldh_n_a(cpu: &mut Cpu) -> CycleDuration {
 // the least significant byte to the value of n, so the possible
 // range is 0xFF00-0xFFFF
 pub fn ldh_a_n(cpu: &mut Cpu) -> CycleDuration {
-    let n = cpu.memory_bus.read_byte(cpu.program_counter.next()) as u16;
+    let n = cpu.memory_bus.cpu_read(cpu.program_counter.next()) as u16;
     let address = 0xFF00 | n;
 
-    let value = cpu.memory_bus.read_byte(address);
+    let value = cpu.memory_bus.cpu_read(address);
     cpu.registers.set_a(value);
 
     CycleDuration::Default
@@ -203,7 +203,7 @@ pub fn ldh_a_c(cpu: &mut Cpu) -> CycleDuration {
     let c: u8 = cpu.registers.get_c();
     let address: u16 = 0xFF00 | c as u16;
 
-    let value = cpu.memory_bus.read_byte(address);
+    let value = cpu.memory_bus.cpu_read(address);
     cpu.registers.set_a(value);
 
     CycleDuration::Default
@@ -234,8 +234,8 @@ pub fn ld_nn_sp(cpu: &mut Cpu) -> CycleDuration {
     let lsb = sp as u8;
     let msb = (sp >> 8) as u8;
 
-    cpu.memory_bus.write_byte(nn, lsb);
-    cpu.memory_bus.write_byte(nn.wrapping_add(1), msb);
+    cpu.memory_bus.cpu_write(nn, lsb);
+    cpu.memory_bus.cpu_write(nn.wrapping_add(1), msb);
 
     CycleDuration::Default
 }
@@ -251,7 +251,7 @@ pub fn ld_c_a(cpu: &mut Cpu) -> CycleDuration {
 
     let address: u16 = 0xFF00 | (c as u16);
 
-    cpu.memory_bus.write_byte(address, value);
+    cpu.memory_bus.cpu_write(address, value);
 
     CycleDuration::Default
 }
@@ -7,7 +7,7 @@
 
 use crate::cpu::{
     instruction::{CycleDuration, Target},
-    Cpu, MemoryAccess,
+    Cpu,
 };
 
 // Rotate the contents of the 8-bit A register to the right by one bit.
@@ -104,12 +104,12 @@ pub fn rlc_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // Rotate the byte pointed to by HL to the left
 pub fn rlc_hl(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let shifted_out = (byte & 0b1000_0000) != 0;
     let result = (byte << 1) | (byte >> 7);
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
@@ -139,12 +139,12 @@ pub fn rrc_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // Rotate the byte pointed to by HL to the right
 pub fn rrc_hl(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let shifted_out = (byte & 0x01) != 0;
     let result = (byte >> 1) | (byte << 7);
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
@@ -175,13 +175,13 @@ pub fn rl_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // Rotate bits in the byte pointed to by HL to the left through carry
 pub fn rl_hl(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
     let carry: u8 = cpu.registers.flags.get_carry().into();
 
     let shifted_out = (byte & 0b1000_0000) != 0;
     let result = (byte << 1) | carry;
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
@@ -212,13 +212,13 @@ pub fn rr_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // Rotate the byte pointed to by HL to the right through carry
 pub fn rr_hl(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
     let carry: u8 = cpu.registers.flags.get_carry().into();
 
     let shifted_out = (byte & 0x01) != 0;
     let result = (byte >> 1) | (carry << 7);
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
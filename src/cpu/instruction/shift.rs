@@ -7,7 +7,7 @@
 
 use crate::cpu::{
     instruction::{CycleDuration, Target},
-    Cpu, MemoryAccess,
+    Cpu,
 };
 
 // Shifts all the bits of the register to the
@@ -32,12 +32,12 @@ pub fn srl_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // to the right by one position
 pub fn srl_hl(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let shifted_out = (byte & 0b0000_0001) != 0;
     let result = byte >> 1;
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
@@ -67,12 +67,12 @@ pub fn sla_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // Shift the byte pointed to by HL to the left arithmetically
 pub fn sla_hl(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let shifted_out = (byte & 0b1000_0000) != 0;
     let result = byte << 1;
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
@@ -102,12 +102,12 @@ pub fn sra_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // Shift the byte pointed to by HL to the right arithmetically
 pub fn sra_hl(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let shifted_out = (byte & 0x01) != 0;
     let result = (byte >> 1) | (byte & 0b1000_0000);
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
@@ -136,11 +136,11 @@ pub fn swap_r(cpu: &mut Cpu, target: Target) -> CycleDuration {
 // Swap the upper 4 bits in the byte pointed by HL and the lower 4 ones
 pub fn swap_hl(cpu: &mut Cpu) -> CycleDuration {
     let address = cpu.registers.get_hl();
-    let byte = cpu.memory_bus.read_byte(address);
+    let byte = cpu.memory_bus.cpu_read(address);
 
     let result = (byte >> 4) | (byte << 4);
 
-    cpu.memory_bus.write_byte(address, result);
+    cpu.memory_bus.cpu_write(address, result);
 
     cpu.registers.flags.set_zero(result == 0);
     cpu.registers.flags.set_subtract(false);
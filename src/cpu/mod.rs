@@ -21,10 +21,8 @@ use crate::{
     interrupt::Interrupt,
     memory_bus::MemoryBus,
     ppu::colors::Colors,
-    MemoryAccess,
 };
 
-const HEADER_CHECKSUM_ADDRESS: usize = 0x014D;
 const STACK_POINTER_START: u16 = 0xFFFE;
 
 pub struct Cpu {
@@ -43,15 +41,17 @@ impl Cpu {
         rom_data: Vec<u8>,
         colors: Rc<RefCell<Colors>>,
         fast_forward: Rc<RefCell<u8>>,
-        prod: Caching<Arc<SharedRb<Heap<u8>>>, true, false>
+        prod: Caching<Arc<SharedRb<Heap<f32>>>, true, false>
     ) -> Result<Self, Box<dyn Error>> {
+        // Fails on files too small for a header before anything reads it
+        let memory_bus = MemoryBus::new(rom_data, colors, fast_forward, prod)?;
+
         // If the header checksum is 0x00, then the carry and
         // half-carry flags are clear; otherwise, they are both set
-
-        let flags_enabled = rom_data[HEADER_CHECKSUM_ADDRESS] != 0x00;
+        let flags_enabled = memory_bus.cartridge.header.header_checksum != 0x00;
 
         Ok(Self {
-            memory_bus: MemoryBus::new(rom_data, colors, fast_forward, prod)?,
+            memory_bus,
             registers: Registers::new(flags_enabled),
             program_counter: ProgramCounter::new(),
             stack_pointer: STACK_POINTER_START,
@@ -86,7 +86,7 @@ impl Cpu {
             }
         }
 
-        let byte = self.memory_bus.read_byte(self.program_counter.next());
+        let byte = self.memory_bus.cpu_read(self.program_counter.next());
         let instruction = Instruction::from_byte(byte);
 
         // Check if mnemonic refers to the prefix table
@@ -111,7 +111,7 @@ impl Cpu {
 
     // Handle next instruction from prefix table
     fn prefix_step(&mut self) -> u8 {
-        let byte = self.memory_bus.read_byte(self.program_counter.next());
+        let byte = self.memory_bus.cpu_read(self.program_counter.next());
         let instruction = Instruction::from_prefix_byte(byte);
         let cycle_duration = self.execute_prefix(instruction);
 
@@ -123,18 +123,18 @@ impl Cpu {
 
     // Multi-byte data is handled in little-endian format
     fn get_nn_little_endian(&mut self) -> u16 {
-        let low_byte = self.memory_bus.read_byte(self.program_counter.next()) as u16;
-        let high_byte = self.memory_bus.read_byte(self.program_counter.next()) as u16;
+        let low_byte = self.memory_bus.cpu_read(self.program_counter.next()) as u16;
+        let high_byte = self.memory_bus.cpu_read(self.program_counter.next()) as u16;
 
         (high_byte << 8) | low_byte
     }
 
     // Stack grows downwards
     fn pop_stack(&mut self) -> u16 {
-        let low_byte = self.memory_bus.read_byte(self.stack_pointer) as u16;
+        let low_byte = self.memory_bus.cpu_read(self.stack_pointer) as u16;
         self.stack_pointer = self.stack_pointer.wrapping_add(1);
 
-        let high_byte = self.memory_bus.read_byte(self.stack_pointer) as u16;
+        let high_byte = self.memory_bus.cpu_read(self.stack_pointer) as u16;
         self.stack_pointer = self.stack_pointer.wrapping_add(1);
 
         (high_byte << 8) | low_byte
@@ -145,10 +145,10 @@ impl Cpu {
         let low_byte = address as u8;
 
         self.stack_pointer = self.stack_pointer.wrapping_sub(1);
-        self.memory_bus.write_byte(self.stack_pointer, high_byte);
+        self.memory_bus.cpu_write(self.stack_pointer, high_byte);
 
         self.stack_pointer = self.stack_pointer.wrapping_sub(1);
-        self.memory_bus.write_byte(self.stack_pointer, low_byte);
+        self.memory_bus.cpu_write(self.stack_pointer, low_byte);
     }
 
     // https://gbdev.io/pandocs/Interrupts.html#interrupt-handling
@@ -301,3 +301,22 @@ impl Cpu {
         }
     }
 }
+
+#[cfg(test)]
+mod cpu_tests {
+    use super::*;
+    use ringbuf::{traits::Split, HeapRb};
+
+    #[test]
+    fn rejects_rom_without_header() {
+        let (prod, _cons) = HeapRb::<f32>::new(16).split();
+        let cpu = Cpu::new(
+            vec![0; 0x100],
+            Rc::new(RefCell::new(Colors::new())),
+            Rc::new(RefCell::new(1)),
+            prod,
+        );
+
+        assert!(cpu.is_err());
+    }
+}
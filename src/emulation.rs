@@ -1,30 +1,46 @@
-use std::{cell::RefCell, error::Error, rc::Rc, sync::Arc, time::Instant};
+use std::{
+    cell::RefCell,
+    error::Error,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use egui_sdl2_gl::{
     egui::Context,
     painter::Painter,
-    sdl2::{video::Window, AudioSubsystem, EventPump},
+    sdl2::{controller::GameController, video::Window, AudioSubsystem, EventPump},
     EguiStateHandler,
 };
 
 use ringbuf::{
     storage::Heap,
+    traits::Split,
     wrap::{caching::Caching, Wrap},
-    SharedRb,
+    HeapRb, SharedRb,
 };
 
 use crate::{
-    apu::audio::create_audio_device,
+    apu::{
+        audio::{open_audio_output, playback_devices},
+        capture::CaptureTarget,
+    },
+    cartridge::image_source::{ImageSource, StillImage, TestPattern},
     cpu::{
-        clock::{Clock, CYCLES_PER_FRAME},
+        clock::{Clock, CPU_CLOCK_SPEED, CYCLES_PER_FRAME},
         Cpu,
     },
     event_handler::EventHandler,
-    ppu::colors::Colors,
+    export::timestamp,
+    ppu::{colors::Colors, VIEWPORT_HEIGHT, VIEWPORT_WIDTH},
+    recorder::{output_path, AudioRecorder, Recorder},
     sync_bridge::SyncBridge,
     ui::UIManager,
 };
 
+const RUMBLE_DURATION_MS: u32 = 100;
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(10);
+
 pub trait MemoryAccess {
     fn read_byte(&self, address: u16) -> u8;
     fn write_byte(&mut self, address: u16, value: u8);
@@ -41,6 +57,9 @@ pub struct Emulation {
     frame_count: u16,
     last_second: Instant,
     fps: f32,
+    recorder: Option<Recorder>,
+    audio_recorder: Option<AudioRecorder>,
+    last_autosave: Instant,
 }
 
 impl Emulation {
@@ -48,7 +67,7 @@ impl Emulation {
         rom_data: Vec<u8>,
         colors: Rc<RefCell<Colors>>,
         fast_forward: Rc<RefCell<u8>>,
-        prod: Caching<Arc<SharedRb<Heap<u8>>>, true, false>,
+        prod: Caching<Arc<SharedRb<Heap<f32>>>, true, false>,
     ) -> Result<Self, Box<dyn Error>> {
         let frame_times = Vec::new();
         let frame_count = 0;
@@ -62,6 +81,9 @@ impl Emulation {
             frame_count,
             last_second,
             fps,
+            recorder: None,
+            audio_recorder: None,
+            last_autosave: Instant::now(),
         })
     }
 
@@ -77,20 +99,30 @@ impl Emulation {
         painter: &mut Painter,
         ui_manager: &mut UIManager,
         colors: Rc<RefCell<Colors>>,
-        audio_subsystem: &AudioSubsystem,
-        cons: Caching<Arc<SharedRb<Heap<u8>>>, false, true>,
+        audio_subsystem: Option<&AudioSubsystem>,
+        mut gamepad: Option<&mut GameController>,
+        cons: Caching<Arc<SharedRb<Heap<f32>>>, false, true>,
     ) {
         let mut sync_bridge = SyncBridge::new();
-        let ring_buffer_ref = cons.rb_ref().clone();
+        let mut ring_buffer_ref = cons.rb_ref().clone();
 
-        let audio_device = create_audio_device(
+        let mut audio_settings = event_handler.audio_settings.clone();
+        let mut audio_output = open_audio_output(
             audio_subsystem,
-            self.cpu.memory_bus.apu.master_volume.get_left_volume(),
-            self.cpu.memory_bus.apu.master_volume.get_right_volume(),
+            &audio_settings,
             &event_handler.volume,
             cons,
         );
-        audio_device.resume();
+        self.cpu
+            .memory_bus
+            .apu
+            .set_sample_rate(audio_output.sample_rate());
+        audio_output.resume();
+        event_handler.audio_devices = playback_devices(audio_subsystem);
+        event_handler.camera_image_changed = event_handler.camera_image.is_some();
+        event_handler.haptics_available = gamepad
+            .as_deref()
+            .is_some_and(|gamepad| gamepad.has_rumble());
 
         // ---------------- EMULATION LOOP ------------------------
         while !event_handler.quit {
@@ -101,12 +133,51 @@ impl Emulation {
 
             event_handler.poll(event_pump, egui_state, window, painter);
             self.cpu.memory_bus.joypad.handle_input(event_handler);
+            self.cpu
+                .memory_bus
+                .cartridge
+                .mbc
+                .set_tilt(event_handler.tilt_x, event_handler.tilt_y);
+            if event_handler.camera_image_changed {
+                self.update_camera_source(event_handler);
+            }
+
+            if event_handler.audio_settings != audio_settings {
+                audio_settings = event_handler.audio_settings.clone();
+
+                let (prod, cons) =
+                    HeapRb::<f32>::new(audio_settings.ring_buffer_capacity()).split();
+                ring_buffer_ref = cons.rb_ref().clone();
+                self.cpu.memory_bus.apu.set_producer(prod);
+
+                audio_output = open_audio_output(
+                    audio_subsystem,
+                    &audio_settings,
+                    &event_handler.volume,
+                    cons,
+                );
+                self.cpu
+                    .memory_bus
+                    .apu
+                    .set_sample_rate(audio_output.sample_rate());
+                audio_output.resume();
+            }
+            self.cpu
+                .memory_bus
+                .apu
+                .time_stretch
+                .set_mode(event_handler.fast_forward_audio);
+            self.update_recorder(event_handler);
+            self.update_audio_recorder(event_handler);
+            self.update_vgm_log(event_handler);
 
             while self.clock.cycles_passed <= CYCLES_PER_FRAME {
                 let m_cycles = self.cpu.step();
                 self.cpu.memory_bus.tick(m_cycles);
 
                 if self.cpu.memory_bus.ppu.should_draw {
+                    self.record_frame(event_handler);
+
                     ui_manager.draw(
                         egui_ctx,
                         egui_state,
@@ -126,8 +197,15 @@ impl Emulation {
             }
 
             self.clock.reset();
+            self.update_rumble(event_handler, gamepad.as_deref_mut());
+            self.autosave(event_handler);
+
+            if event_handler.gbs_header.is_some() {
+                event_handler.gbs_elapsed += CYCLES_PER_FRAME as f32 / CPU_CLOCK_SPEED as f32;
+            }
 
             let fast_forward = *event_handler.fast_forward.borrow();
+            let audio_stretched = self.cpu.memory_bus.apu.time_stretch.is_active(fast_forward);
 
             if self.last_second.elapsed().as_secs() >= 1 {
                 self.fps = self.frame_count as f32 / self.frame_times.iter().sum::<f32>();
@@ -141,9 +219,17 @@ impl Emulation {
                 &fast_forward,
                 event_handler.performance_mode,
                 self.cpu.memory_bus.apu.enabled,
+                audio_stretched,
                 ring_buffer_ref.clone(),
             );
 
+            self.cpu
+                .memory_bus
+                .apu
+                .set_rate_adjustment(sync_bridge.rate_adjustment);
+            event_handler.audio_buffer_fill = sync_bridge.buffer_fill;
+            event_handler.rate_adjustment = sync_bridge.rate_adjustment;
+
             let frame_time = frame_start_time.elapsed().as_secs_f32();
             self.frame_times.push(frame_time);
             self.frame_count += 1;
@@ -152,5 +238,238 @@ impl Emulation {
                 break;
             }
         }
+
+        event_handler.rumble = 0.0;
+        if let Some(gamepad) = gamepad {
+            // Stopping a motor that is already off is harmless
+            let _ = gamepad.set_rumble(0, 0, 0);
+        }
+
+        event_handler.recording = None;
+        event_handler.audio_recording = false;
+        event_handler.vgm_logging = false;
+        self.update_recorder(event_handler);
+        self.update_audio_recorder(event_handler);
+        self.update_vgm_log(event_handler);
+    }
+
+    // Hands the picked picture to the camera sensor, or the test pattern if there is none
+    fn update_camera_source(&mut self, event_handler: &mut EventHandler) {
+        event_handler.camera_image_changed = false;
+
+        let source: Box<dyn ImageSource> = match &event_handler.camera_image {
+            Some(path) => match StillImage::load(path) {
+                Ok(image) => Box::new(image),
+                Err(e) => {
+                    eprintln!("Error loading camera image: {e}.");
+                    event_handler.camera_image = None;
+                    Box::new(TestPattern::new())
+                }
+            },
+            None => Box::new(TestPattern::new()),
+        };
+
+        self.cpu.memory_bus.cartridge.mbc.set_image_source(source);
+    }
+
+    // Flushes cartridge RAM every few seconds while the game keeps writing to it
+    fn autosave(&mut self, event_handler: &EventHandler) {
+        let cartridge = &mut self.cpu.memory_bus.cartridge;
+        if !cartridge.ram_dirty || self.last_autosave.elapsed() < AUTOSAVE_INTERVAL {
+            return;
+        }
+
+        self.last_autosave = Instant::now();
+
+        let Some(rom_path) = &event_handler.rom_path else {
+            return;
+        };
+
+        let save_path = event_handler
+            .paths
+            .save_path(rom_path, event_handler.rom_checksum);
+        if let Err(e) = cartridge.save_game(&save_path) {
+            eprintln!("Error autosaving game: {e}.");
+        }
+    }
+
+    // Follows the cartridge motor on the gamepad, the UI shows it when that is not possible
+    fn update_rumble(
+        &mut self,
+        event_handler: &mut EventHandler,
+        gamepad: Option<&mut GameController>,
+    ) {
+        let rumble = self.cpu.memory_bus.cartridge.mbc.take_rumble();
+        if rumble == 0.0 && event_handler.rumble == 0.0 {
+            return;
+        }
+
+        event_handler.rumble = rumble;
+
+        let Some(gamepad) = gamepad.filter(|_| event_handler.haptics_available) else {
+            return;
+        };
+
+        // Outlasts a frame, the next update either renews or stops it
+        let strength = (rumble * u16::MAX as f32) as u16;
+        if let Err(e) = gamepad.set_rumble(strength, strength, RUMBLE_DURATION_MS) {
+            eprintln!("Error setting rumble: {e}.");
+            event_handler.haptics_available = false;
+        }
+    }
+
+    // Starts or stops recording whenever the requested format changes
+    fn update_recorder(&mut self, event_handler: &mut EventHandler) {
+        if event_handler.recording.is_some() == self.recorder.is_some() {
+            return;
+        }
+
+        if let Some(mut recorder) = self.recorder.take() {
+            self.cpu.memory_bus.apu.stop_capture(CaptureTarget::Video);
+            event_handler.recording_seconds = None;
+
+            match recorder.finish() {
+                Ok(_) => println!("Recording saved to {}.", recorder.path.display()),
+                Err(e) => eprintln!("Error saving recording: {e}."),
+            }
+
+            return;
+        }
+
+        let (Some(format), Some(rom_path)) = (event_handler.recording, &event_handler.rom_path)
+        else {
+            return;
+        };
+
+        match Recorder::build(format, rom_path, VIEWPORT_WIDTH, VIEWPORT_HEIGHT) {
+            Ok(recorder) => {
+                self.cpu
+                    .memory_bus
+                    .apu
+                    .start_capture(CaptureTarget::Video, false);
+                event_handler.recording_seconds = Some(0.0);
+                self.recorder = Some(recorder);
+            }
+            Err(e) => {
+                eprintln!("Error starting recording: {e}.");
+                event_handler.recording = None;
+            }
+        }
+    }
+
+    // Starts or stops the WAV recording, stems are chosen when it starts
+    fn update_audio_recorder(&mut self, event_handler: &mut EventHandler) {
+        if event_handler.audio_recording == self.audio_recorder.is_some() {
+            return;
+        }
+
+        if let Some(mut audio_recorder) = self.audio_recorder.take() {
+            // Samples since the last frame would be lost otherwise
+            self.record_audio(&mut audio_recorder, event_handler);
+            self.cpu.memory_bus.apu.stop_capture(CaptureTarget::Audio);
+            event_handler.audio_recording_seconds = None;
+
+            match audio_recorder.finish() {
+                Ok(_) => println!("Audio saved to {}.", audio_recorder.path.display()),
+                Err(e) => eprintln!("Error saving audio: {e}."),
+            }
+
+            return;
+        }
+
+        let Some(rom_path) = &event_handler.rom_path else {
+            event_handler.audio_recording = false;
+            return;
+        };
+
+        match AudioRecorder::build(rom_path, event_handler.record_stems) {
+            Ok(audio_recorder) => {
+                self.cpu
+                    .memory_bus
+                    .apu
+                    .start_capture(CaptureTarget::Audio, audio_recorder.has_stems());
+                event_handler.audio_recording_seconds = Some(0.0);
+                self.audio_recorder = Some(audio_recorder);
+            }
+            Err(e) => {
+                eprintln!("Error starting audio recording: {e}.");
+                event_handler.audio_recording = false;
+            }
+        }
+    }
+
+    // Starts or stops logging APU register writes, the file is written when stopped
+    fn update_vgm_log(&mut self, event_handler: &mut EventHandler) {
+        let memory_bus = &mut self.cpu.memory_bus;
+
+        if let Some(vgm_log) = &memory_bus.vgm_log {
+            event_handler.vgm_seconds = Some(vgm_log.elapsed_seconds());
+        }
+
+        if event_handler.vgm_logging == memory_bus.vgm_log.is_some() {
+            return;
+        }
+
+        if let Some(mut vgm_log) = memory_bus.vgm_log.take() {
+            event_handler.vgm_seconds = None;
+
+            let Some(rom_path) = &event_handler.rom_path else {
+                return;
+            };
+
+            let path = output_path(rom_path, &timestamp(), "vgm");
+            match vgm_log.save(&path) {
+                Ok(_) => println!("VGM log saved to {}.", path.display()),
+                Err(e) => eprintln!("Error saving VGM log: {e}."),
+            }
+
+            return;
+        }
+
+        memory_bus.start_vgm_log();
+        event_handler.vgm_seconds = Some(0.0);
+    }
+
+    fn record_frame(&mut self, event_handler: &mut EventHandler) {
+        if let Some(mut audio_recorder) = self.audio_recorder.take() {
+            self.record_audio(&mut audio_recorder, event_handler);
+            self.audio_recorder = Some(audio_recorder);
+        }
+
+        let Some(recorder) = &mut self.recorder else {
+            return;
+        };
+
+        let samples = self
+            .cpu
+            .memory_bus
+            .apu
+            .take_captured_samples(CaptureTarget::Video);
+
+        if let Err(e) = recorder.capture(&self.cpu.memory_bus.ppu.viewport_buffer, &samples) {
+            eprintln!("Error recording frame: {e}.");
+            event_handler.recording = None;
+            return;
+        }
+
+        event_handler.recording_seconds = Some(recorder.elapsed_seconds());
+    }
+
+    fn record_audio(
+        &mut self,
+        audio_recorder: &mut AudioRecorder,
+        event_handler: &mut EventHandler,
+    ) {
+        let apu = &mut self.cpu.memory_bus.apu;
+        let samples = apu.take_captured_samples(CaptureTarget::Audio);
+        let stems = apu.take_captured_stems(CaptureTarget::Audio);
+
+        if let Err(e) = audio_recorder.capture(&samples, &stems) {
+            eprintln!("Error recording audio: {e}.");
+            event_handler.audio_recording = false;
+            return;
+        }
+
+        event_handler.audio_recording_seconds = Some(audio_recorder.elapsed_seconds());
     }
 }
@@ -5,18 +5,39 @@
  * @date    November 11, 2023
  */
 
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
 
 use egui_sdl2_gl::{
     painter::Painter,
-    sdl2::{controller::Button, event::Event, keyboard::Keycode, video::Window, EventPump},
+    sdl2::{
+        controller::{Axis, Button},
+        event::Event,
+        keyboard::Keycode,
+        video::Window,
+        EventPump,
+    },
     EguiStateHandler,
 };
 
-use crate::State;
+use crate::{
+    apu::{audio::AudioSettings, time_stretch::FastForwardAudio},
+    cartridge::{gbs::GbsHeader, save_format::SaveTransfer},
+    export::VramExport,
+    library::Library,
+    paths::Paths,
+    recorder::RecordingFormat,
+    ui::filters::{Ghosting, Scaler, ScalingMode},
+    State,
+};
 
 pub struct EventHandler {
     pub file_path: Option<String>,
+    pub rom_path: Option<String>,
+    // Of the unpatched ROM, which the save is named after
+    pub rom_checksum: u16,
+    pub paths: Paths,
+    pub library: Library,
+    pub library_opened: bool,
     pub state: State,
     pub a: Option<Keycode>,
     pub pressed_a: bool,
@@ -34,29 +55,78 @@ pub struct EventHandler {
     pub pressed_down: bool,
     pub right: Option<Keycode>,
     pub pressed_right: bool,
+    // Cartridge tilt from the mouse position or the left stick, -1.0 to 1.0
+    pub tilt_x: f32,
+    pub tilt_y: f32,
+    // Picture for the Game Boy Camera sensor, the test pattern when unset
+    pub camera_image: Option<PathBuf>,
+    pub camera_image_changed: bool,
+    // Strength of the cartridge motor during the last frame
+    pub rumble: f32,
+    // Whether the gamepad can rumble, the viewport shows an indicator otherwise
+    pub haptics_available: bool,
     pub window_scale: u32,
     pub previous_scale: u32,
     pub window_resized: bool,
     pub volume: u8,
     pub last_volume: u8,
     pub volume_slider: bool,
+    pub audio_settings: AudioSettings,
+    pub audio_devices: Vec<String>,
+    pub audio_settings_opened: bool,
     pub fast_forward: Rc<RefCell<u8>>,
+    pub fast_forward_audio: FastForwardAudio,
     pub performance_mode: bool,
+    // Reported by the dynamic rate control every frame
+    pub audio_buffer_fill: f32,
+    pub rate_adjustment: f64,
     pub show_waveform: bool,
     pub show_square_waves: bool,
+    pub show_oscilloscope: bool,
     pub cpu_status_opened: bool,
+    pub rom_info_opened: bool,
+    pub mixer_opened: bool,
     pub keybindings_opened: bool,
     pub color_scheme_opened: bool,
     pub about_opened: bool,
     pub bug_report_opened: bool,
     pub quit: bool,
     pub rebinding_key: Option<&'static str>,
+    pub vram_export: Option<(VramExport, PathBuf)>,
+    pub save_transfer: Option<(SaveTransfer, PathBuf)>,
+    pub screenshot_requested: bool,
+    pub screenshot_to_clipboard: bool,
+    pub screenshot_scaled: bool,
+    pub screenshot_grayscale: bool,
+    pub recording: Option<RecordingFormat>,
+    pub recording_seconds: Option<f32>,
+    pub audio_recording: bool,
+    pub record_stems: bool,
+    pub audio_recording_seconds: Option<f32>,
+    pub vgm_logging: bool,
+    pub vgm_seconds: Option<f32>,
+    pub gbs_header: Option<GbsHeader>,
+    // 0-based track that is currently playing
+    pub gbs_song: u8,
+    // Track to switch to, applied when the file is reloaded
+    pub gbs_track: Option<u8>,
+    pub gbs_elapsed: f32,
+    pub gbs_player_opened: bool,
+    pub scaler: Scaler,
+    pub ghosting: Ghosting,
+    pub lcd_grid: bool,
+    pub scaling_mode: ScalingMode,
 }
 
 impl EventHandler {
     pub fn new() -> Self {
         Self {
             file_path: None,
+            rom_path: None,
+            rom_checksum: 0,
+            paths: Paths::new(None),
+            library: Library::new(PathBuf::new()),
+            library_opened: false,
             state: State::Splash,
             a: Some(Keycode::N),
             pressed_a: false,
@@ -74,23 +144,60 @@ impl EventHandler {
             pressed_down: false,
             right: Some(Keycode::D),
             pressed_right: false,
+            tilt_x: 0.0,
+            tilt_y: 0.0,
+            camera_image: None,
+            camera_image_changed: false,
+            rumble: 0.0,
+            haptics_available: false,
             window_scale: 4,
             previous_scale: 4,
             window_resized: false,
             volume: 50,
             last_volume: 50,
             volume_slider: true,
+            audio_settings: AudioSettings::default(),
+            audio_devices: Vec::new(),
+            audio_settings_opened: false,
             fast_forward: Rc::new(RefCell::new(1)),
+            fast_forward_audio: FastForwardAudio::TimeStretch,
             performance_mode: true,
+            audio_buffer_fill: 0.0,
+            rate_adjustment: 1.0,
             show_waveform: false,
             show_square_waves: false,
+            show_oscilloscope: false,
             cpu_status_opened: false,
+            rom_info_opened: false,
+            mixer_opened: false,
             keybindings_opened: false,
             color_scheme_opened: false,
             about_opened: false,
             bug_report_opened: false,
             quit: false,
             rebinding_key: None,
+            vram_export: None,
+            save_transfer: None,
+            screenshot_requested: false,
+            screenshot_to_clipboard: false,
+            screenshot_scaled: false,
+            screenshot_grayscale: false,
+            recording: None,
+            recording_seconds: None,
+            audio_recording: false,
+            record_stems: false,
+            audio_recording_seconds: None,
+            vgm_logging: false,
+            vgm_seconds: None,
+            gbs_header: None,
+            gbs_song: 0,
+            gbs_track: None,
+            gbs_elapsed: 0.0,
+            gbs_player_opened: false,
+            scaler: Scaler::None,
+            ghosting: Ghosting::Off,
+            lcd_grid: false,
+            scaling_mode: ScalingMode::Integer,
         }
     }
 
@@ -133,6 +240,7 @@ impl EventHandler {
                             Some(Keycode::Down) => self.decrease_scale(),
                             Some(Keycode::Left) => self.decrease_volume(),
                             Some(Keycode::Right) => self.increase_volume(),
+                            Some(Keycode::F12) => self.screenshot_requested = true,
                             _ => {}
                         }
                     }
@@ -170,6 +278,22 @@ impl EventHandler {
                     Button::DPadRight => self.pressed_right = false,
                     _ => {}
                 },
+                Event::ControllerAxisMotion { axis, value, .. } => {
+                    let tilt = (value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+
+                    match axis {
+                        Axis::LeftX => self.tilt_x = tilt,
+                        Axis::LeftY => self.tilt_y = tilt,
+                        _ => {}
+                    }
+                }
+                Event::MouseMotion { x, y, .. } => {
+                    let (width, height) = window.size();
+                    self.tilt_x = (x as f32 / width as f32 * 2.0 - 1.0).clamp(-1.0, 1.0);
+                    self.tilt_y = (y as f32 / height as f32 * 2.0 - 1.0).clamp(-1.0, 1.0);
+
+                    egui_state.process_input(window, event, painter);
+                }
                 Event::DropFile { filename, .. } => {
                     self.file_path = Some(filename);
                 }
@@ -0,0 +1,327 @@
+/*
+ * @file    export.rs
+ * @brief   Dumps video memory contents and screenshots to PNG images and raw binary files.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{
+    error::Error,
+    fs::File,
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use egui_sdl2_gl::egui::Color32;
+use image::{
+    imageops::{self, FilterType},
+    ImageBuffer, Rgba,
+};
+
+use crate::{
+    cartridge::camera::{read_photo, PHOTO_COUNT, PHOTO_HEIGHT, PHOTO_WIDTH},
+    ppu::{
+        Ppu, OAM_SIZE, TILEMAP_END_0, TILEMAP_END_1, TILEMAP_HEIGHT, TILEMAP_START_0,
+        TILEMAP_START_1, TILEMAP_WIDTH, TILETABLE_HEIGHT, TILETABLE_WIDTH, TILE_WIDTH,
+        VIEWPORT_HEIGHT, VIEWPORT_WIDTH,
+    },
+};
+
+// Battery RAM of the Game Boy Camera
+const CAMERA_SAVE_SIZE: usize = 128 * 1024;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum VramExport {
+    // Tiletable, both tilemaps, every sprite and the viewport as PNG images
+    Images,
+    // Tile data as .2bpp and tilemap indices as .tilemap, compatible with RGBDS rgbgfx
+    Raw,
+}
+
+pub fn export_vram(ppu: &Ppu, export: VramExport, directory: &Path) -> Result<(), Box<dyn Error>> {
+    match export {
+        VramExport::Images => export_images(ppu, directory),
+        VramExport::Raw => export_raw(ppu, directory),
+    }
+}
+
+fn export_images(ppu: &Ppu, directory: &Path) -> Result<(), Box<dyn Error>> {
+    save_png(
+        &directory.join("tiletable.png"),
+        TILETABLE_WIDTH,
+        TILETABLE_HEIGHT,
+        &ppu.tiletable(),
+    )?;
+
+    save_png(
+        &directory.join("tilemap0.png"),
+        TILEMAP_WIDTH,
+        TILEMAP_HEIGHT,
+        &ppu.tilemap(TILEMAP_START_0, TILEMAP_END_0),
+    )?;
+
+    save_png(
+        &directory.join("tilemap1.png"),
+        TILEMAP_WIDTH,
+        TILEMAP_HEIGHT,
+        &ppu.tilemap(TILEMAP_START_1, TILEMAP_END_1),
+    )?;
+
+    save_png(
+        &directory.join("viewport.png"),
+        VIEWPORT_WIDTH,
+        VIEWPORT_HEIGHT,
+        &ppu.viewport_buffer,
+    )?;
+
+    let sprite_height = ppu.sprite_height();
+    for index in 0..OAM_SIZE {
+        save_png(
+            &directory.join(format!("sprite{:02}.png", index)),
+            TILE_WIDTH as usize,
+            sprite_height,
+            &ppu.sprite(index),
+        )?;
+    }
+
+    Ok(())
+}
+
+/*
+ * rgbgfx stores tiles in the same 2bpp layout the Game Boy uses, and a
+ * tilemap as one tile index byte per entry. Besides the full tile data, the
+ * 256 tiles currently addressable by the tilemaps are written in index order,
+ * so that the .tilemap files can be used with bg_tiles.2bpp directly.
+ */
+fn export_raw(ppu: &Ppu, directory: &Path) -> Result<(), Box<dyn Error>> {
+    write_file(&directory.join("tiles.2bpp"), ppu.tile_data())?;
+    write_file(&directory.join("bg_tiles.2bpp"), &ppu.indexed_tile_data())?;
+    write_file(
+        &directory.join("tilemap0.tilemap"),
+        &ppu.tilemap_indices(TILEMAP_START_0, TILEMAP_END_0),
+    )?;
+    write_file(
+        &directory.join("tilemap1.tilemap"),
+        &ppu.tilemap_indices(TILEMAP_START_1, TILEMAP_END_1),
+    )?;
+
+    Ok(())
+}
+
+pub fn save_png(
+    path: &Path,
+    width: usize,
+    height: usize,
+    pixels: &[Color32],
+) -> Result<(), Box<dyn Error>> {
+    let image = to_image(width, height, pixels)?;
+    image.save(path)?;
+
+    Ok(())
+}
+
+pub fn to_image(
+    width: usize,
+    height: usize,
+    pixels: &[Color32],
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, Box<dyn Error>> {
+    let data = pixels
+        .iter()
+        .flat_map(|color| color.to_srgba_unmultiplied())
+        .collect::<Vec<u8>>();
+
+    ImageBuffer::from_raw(width as u32, height as u32, data)
+        .ok_or_else(|| "Pixel buffer does not match the image dimensions".into())
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut file = File::create(path)?;
+    file.write_all(data)?;
+
+    Ok(())
+}
+
+const GRAYSCALE_SHADES: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
+
+/*
+ * Renders the current viewport as RGBA pixels, either with the active
+ * color scheme, or with raw gray shades as they are selected by the
+ * palette registers.
+ */
+pub fn screenshot_image(
+    ppu: &Ppu,
+    scale: u32,
+    grayscale: bool,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, Box<dyn Error>> {
+    let pixels = if grayscale {
+        ppu.shade_buffer
+            .iter()
+            .map(|shade| {
+                let value = GRAYSCALE_SHADES[*shade as usize];
+                Color32::from_rgb(value, value, value)
+            })
+            .collect::<Vec<Color32>>()
+    } else {
+        ppu.viewport_buffer.to_vec()
+    };
+
+    let image = to_image(VIEWPORT_WIDTH, VIEWPORT_HEIGHT, &pixels)?;
+    let image = if scale > 1 {
+        imageops::resize(
+            &image,
+            VIEWPORT_WIDTH as u32 * scale,
+            VIEWPORT_HEIGHT as u32 * scale,
+            FilterType::Nearest,
+        )
+    } else {
+        image
+    };
+
+    Ok(image)
+}
+
+// Saves the current viewport as a PNG at the given path
+pub fn save_screenshot(
+    ppu: &Ppu,
+    path: &Path,
+    scale: u32,
+    grayscale: bool,
+) -> Result<(), Box<dyn Error>> {
+    let image = screenshot_image(ppu, scale, grayscale)?;
+
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+
+    image.save(path)?;
+
+    Ok(())
+}
+
+/*
+ * Writes all photo slots of a Game Boy Camera save as PNG images next to
+ * it. Deleted photos stay in their slot until overwritten, so they are
+ * exported too. The camera has no colors, the shades are plain gray.
+ */
+pub fn export_camera_photos(save_path: &Path) -> Result<(), Box<dyn Error>> {
+    let save = std::fs::read(save_path)?;
+    if save.len() != CAMERA_SAVE_SIZE {
+        return Err("Not a Game Boy Camera save".into());
+    }
+
+    let stem = save_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "camera".to_string());
+
+    for slot in 0..PHOTO_COUNT {
+        let pixels = read_photo(&save, slot)
+            .ok_or("Photo slot is out of range")?
+            .iter()
+            .map(|shade| {
+                let value = GRAYSCALE_SHADES[*shade as usize];
+                Color32::from_rgb(value, value, value)
+            })
+            .collect::<Vec<Color32>>();
+
+        let path = save_path.with_file_name(format!("{}_photo{:02}.png", stem, slot + 1));
+        save_png(&path, PHOTO_WIDTH, PHOTO_HEIGHT, &pixels)?;
+    }
+
+    Ok(())
+}
+
+// Digits are zeros, everything else has to match as is
+const TIMESTAMP_PATTERN: &str = "0000-00-00_00-00-00";
+
+// Current UTC time formatted as YYYY-MM-DD_HH-MM-SS
+pub fn timestamp() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    format_timestamp(seconds)
+}
+
+/*
+ * Whether a file name is the stem, an underscore and a timestamp with the
+ * given extension. Checking the whole timestamp keeps "tetris" from
+ * matching the files of "tetris_dx".
+ */
+pub fn is_timestamped(name: &str, stem: &str, extension: &str) -> bool {
+    let time = name
+        .strip_prefix(stem)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .and_then(|rest| rest.strip_suffix(extension))
+        .and_then(|rest| rest.strip_suffix('.'));
+
+    time.is_some_and(|time| {
+        time.len() == TIMESTAMP_PATTERN.len()
+            && time
+                .bytes()
+                .zip(TIMESTAMP_PATTERN.bytes())
+                .all(|(byte, pattern)| match pattern {
+                    b'0' => byte.is_ascii_digit(),
+                    _ => byte == pattern,
+                })
+    })
+}
+
+fn format_timestamp(seconds: u64) -> String {
+    let (days, time_of_day) = (seconds / 86400, seconds % 86400);
+    let (hours, minutes, secs) = (
+        time_of_day / 3600,
+        (time_of_day % 3600) / 60,
+        time_of_day % 60,
+    );
+
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year, month, day, hours, minutes, secs
+    )
+}
+
+#[cfg(test)]
+mod timestamp_tests {
+    use super::*;
+
+    #[test]
+    fn unix_epoch() {
+        assert_eq!(format_timestamp(0), "1970-01-01_00-00-00");
+    }
+
+    #[test]
+    fn leap_day() {
+        assert_eq!(format_timestamp(1709210096), "2024-02-29_12-34-56");
+    }
+
+    #[test]
+    fn matches_whole_timestamp() {
+        let name = format!("tetris_{}.sav", format_timestamp(0));
+        assert!(is_timestamped(&name, "tetris", "sav"));
+        assert!(!is_timestamped(&name, "tetris", "png"));
+        assert!(!is_timestamped(&name, "tetri", "sav"));
+
+        for name in [
+            "tetris_dx_1970-01-01_00-00-00.sav",
+            "tetris_1970-01-01_00-00.sav",
+            "tetris_1970-01-01_00-00-0x.sav",
+        ] {
+            assert!(!is_timestamped(name, "tetris", "sav"));
+        }
+    }
+}
@@ -0,0 +1,365 @@
+/*
+ * @file    library.rs
+ * @brief   Keeps track of ROM directories and recently played games.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    cartridge::header::{global_checksum, CartridgeHeader},
+    paths::Paths,
+    rom_file,
+};
+
+const MAX_RECENT: usize = 10;
+// Subdirectories deeper than this are not scanned
+const MAX_DEPTH: usize = 4;
+
+// Lines of the library file, followed by a space and the value
+const DIRECTORY_KEY: &str = "directory";
+const PLAYED_KEY: &str = "played";
+
+pub struct RomEntry {
+    pub path: String,
+    pub title: String,
+    pub cartridge_type: &'static str,
+    pub cgb_support: &'static str,
+    pub last_played: Option<u64>,
+    pub has_save: bool,
+    pub thumbnail: Option<PathBuf>,
+}
+
+/*
+ * Directories and play times are written to the library file, so ROMs
+ * are stored by their absolute path. The entries are read from the ROM
+ * headers again on every scan, on a thread of its own so large
+ * directories don't stall the window.
+ */
+pub struct Library {
+    file: PathBuf,
+    pub directories: Vec<PathBuf>,
+    played: HashMap<String, u64>,
+    pub entries: Vec<RomEntry>,
+    pub scanned: bool,
+    // Entries of the scan that is still running
+    scan: Option<Receiver<Vec<RomEntry>>>,
+}
+
+impl Library {
+    pub fn new(file: PathBuf) -> Self {
+        Self {
+            file,
+            directories: Vec::new(),
+            played: HashMap::new(),
+            entries: Vec::new(),
+            scanned: false,
+            scan: None,
+        }
+    }
+
+    // A missing or unreadable library starts out empty
+    pub fn load(file: PathBuf) -> Self {
+        let text = fs::read_to_string(&file).unwrap_or_default();
+        let mut library = Self::new(file);
+        library.parse(&text);
+
+        library
+    }
+
+    fn parse(&mut self, text: &str) {
+        for line in text.lines() {
+            match line.split_once(' ') {
+                Some((DIRECTORY_KEY, directory)) => self.directories.push(PathBuf::from(directory)),
+                Some((PLAYED_KEY, value)) => {
+                    if let Some((seconds, rom_path)) = value.split_once(' ') {
+                        if let Ok(seconds) = seconds.parse() {
+                            self.played.insert(rom_path.to_owned(), seconds);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for directory in &self.directories {
+            text += &format!("{} {}\n", DIRECTORY_KEY, directory.display());
+        }
+
+        let mut played = self.played.iter().collect::<Vec<_>>();
+        played.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (rom_path, seconds) in played {
+            text += &format!("{} {} {}\n", PLAYED_KEY, seconds, rom_path);
+        }
+
+        text
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(directory) = self.file.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(&self.file, self.to_text())?;
+
+        Ok(())
+    }
+
+    // Most recently played first
+    pub fn recent(&self) -> Vec<String> {
+        let mut played = self.played.iter().collect::<Vec<_>>();
+        played.sort_by(|a, b| b.1.cmp(a.1));
+
+        played
+            .into_iter()
+            .take(MAX_RECENT)
+            .map(|(rom_path, _)| rom_path.clone())
+            .collect()
+    }
+
+    pub fn mark_played(&mut self, rom_path: &str) -> Result<(), Box<dyn Error>> {
+        let rom_path = absolute(rom_path);
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.path == rom_path) {
+            entry.last_played = Some(seconds);
+        }
+        self.played.insert(rom_path, seconds);
+
+        self.save()
+    }
+
+    pub fn clear_recent(&mut self) -> Result<(), Box<dyn Error>> {
+        self.played.clear();
+        for entry in &mut self.entries {
+            entry.last_played = None;
+        }
+
+        self.save()
+    }
+
+    pub fn add_directory(&mut self, directory: PathBuf) -> Result<(), Box<dyn Error>> {
+        if !self.directories.contains(&directory) {
+            self.directories.push(directory);
+        }
+
+        self.save()
+    }
+
+    pub fn remove_directory(&mut self, index: usize) -> Result<(), Box<dyn Error>> {
+        if index < self.directories.len() {
+            self.directories.remove(index);
+        }
+
+        self.save()
+    }
+
+    // Starts reading the directories again, a scan still running is dropped
+    pub fn scan(&mut self, paths: &Paths) {
+        let (sender, receiver) = mpsc::channel();
+        let directories = self.directories.clone();
+        let paths = paths.clone();
+
+        thread::spawn(move || {
+            // Fails if the scan was dropped in the meantime
+            let _ = sender.send(scan_directories(&directories, &paths));
+        });
+
+        self.scan = Some(receiver);
+    }
+
+    pub fn scanning(&self) -> bool {
+        self.scan.is_some()
+    }
+
+    // Takes over the entries once the scan is done
+    pub fn poll_scan(&mut self) {
+        let Some(receiver) = &self.scan else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok(mut entries) => {
+                for entry in &mut entries {
+                    entry.last_played = self.played.get(&entry.path).copied();
+                }
+
+                self.entries = entries;
+                self.scanned = true;
+                self.scan = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.scan = None,
+        }
+    }
+}
+
+// Reads the header of every ROM in the directories, unreadable files are left out
+fn scan_directories(directories: &[PathBuf], paths: &Paths) -> Vec<RomEntry> {
+    let mut rom_paths = Vec::new();
+    for directory in directories {
+        find_roms(directory, 0, &mut rom_paths);
+    }
+    rom_paths.sort();
+    rom_paths.dedup();
+
+    rom_paths
+        .iter()
+        .filter_map(|path| {
+            let rom_data = rom_file::unpack(path).ok()?;
+            let header = CartridgeHeader::parse(&rom_data).ok()?;
+            let rom_path = absolute(&path.to_string_lossy());
+
+            Some(RomEntry {
+                title: header.title.clone(),
+                cartridge_type: header.cartridge_type_name(),
+                cgb_support: header.cgb_support(),
+                last_played: None,
+                has_save: paths
+                    .existing_save_path(&rom_path, global_checksum(&rom_data))
+                    .exists(),
+                thumbnail: paths.latest_screenshot(&rom_path),
+                path: rom_path,
+            })
+        })
+        .collect()
+}
+
+fn find_roms(directory: &Path, depth: usize, rom_paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+
+        if path.is_dir() && !hidden && depth < MAX_DEPTH {
+            find_roms(&path, depth + 1, rom_paths);
+        } else if path.is_file() && rom_file::is_rom_file(&path) {
+            rom_paths.push(path);
+        }
+    }
+}
+
+// Recent ROMs keep working when the emulator is started from elsewhere
+fn absolute(rom_path: &str) -> String {
+    fs::canonicalize(rom_path)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| rom_path.to_owned())
+}
+
+#[cfg(test)]
+mod library_tests {
+    use super::*;
+
+    #[test]
+    fn keeps_directories_and_play_times() {
+        let mut library = Library::new(PathBuf::from("library.txt"));
+        library.parse(
+            "directory /home/player/roms\n\
+             played 100 /home/player/roms/a game.gb\n\
+             played 300 /home/player/roms/c.zip\n\
+             played 200 /home/player/roms/b.gbc\n\
+             played x /broken.gb\n",
+        );
+
+        assert_eq!(library.directories, [PathBuf::from("/home/player/roms")]);
+        assert_eq!(
+            library.recent(),
+            [
+                "/home/player/roms/c.zip",
+                "/home/player/roms/b.gbc",
+                "/home/player/roms/a game.gb"
+            ]
+        );
+
+        let mut reloaded = Library::new(PathBuf::from("library.txt"));
+        reloaded.parse(&library.to_text());
+        assert_eq!(reloaded.directories, library.directories);
+        assert_eq!(reloaded.played, library.played);
+    }
+
+    #[test]
+    fn finds_roms_in_subdirectories() {
+        let directory = std::env::temp_dir().join(format!("gaemboi_roms_{}", std::process::id()));
+        fs::create_dir_all(directory.join("gbc")).unwrap();
+        fs::create_dir_all(directory.join(".hidden")).unwrap();
+
+        for name in [
+            "tetris.gb",
+            "zelda.gb.gz",
+            "readme.txt.gz",
+            "notes.txt",
+            "gbc/pokemon.gbc",
+            ".hidden/mario.gb",
+        ] {
+            fs::write(directory.join(name), [0]).unwrap();
+        }
+
+        let mut rom_paths = Vec::new();
+        find_roms(&directory, 0, &mut rom_paths);
+        rom_paths.sort();
+
+        assert_eq!(
+            rom_paths,
+            [
+                directory.join("gbc/pokemon.gbc"),
+                directory.join("tetris.gb"),
+                directory.join("zelda.gb.gz"),
+            ]
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn finds_save_of_patched_rom() {
+        let directory =
+            std::env::temp_dir().join(format!("gaemboi_library_{}", std::process::id()));
+        let roms = directory.join("roms");
+        fs::create_dir_all(&roms).unwrap();
+        let paths = Paths::new(Some(directory.join("saves")));
+
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[0x147] = 0x03;
+        fs::write(roms.join("game.gb"), rom_data).unwrap();
+        // IPS patch writing 0x42 to 0x0200
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(&[0x00, 0x02, 0x00, 0x00, 0x01, 0x42]);
+        patch.extend_from_slice(b"EOF");
+        fs::write(roms.join("game.ips"), patch).unwrap();
+
+        // Saved the way the emulator does after loading the patched ROM
+        let rom_path = absolute(&roms.join("game.gb").to_string_lossy());
+        let rom = rom_file::read(&rom_path).unwrap();
+        let save_path = paths.save_path(&rom_path, rom.checksum);
+        fs::create_dir_all(save_path.parent().unwrap()).unwrap();
+        fs::write(&save_path, [0; 0x2000]).unwrap();
+
+        let entries = scan_directories(&[roms], &paths);
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].has_save);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
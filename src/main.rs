@@ -19,11 +19,18 @@ mod config;
 mod cpu;
 mod emulation;
 mod event_handler;
+mod export;
 mod interrupt;
 mod io;
+mod library;
 mod memory_bus;
+mod paths;
 mod ppu;
+mod recorder;
+mod rom_file;
 mod sync_bridge;
+#[cfg(test)]
+mod test_roms;
 mod ui;
 
 use egui_sdl2_gl::{
@@ -36,12 +43,16 @@ use ringbuf::{traits::Split, HeapRb};
 
 use {
     apu::ogg_player::create_audio_theme,
+    cartridge::gbs::{self, GbsHeader},
     emulation::{ComponentTick, Emulation, MemoryAccess},
+    event_handler::EventHandler,
+    library::Library,
+    paths::Paths,
     ppu::{colors::Colors, VIEWPORT_HEIGHT, VIEWPORT_WIDTH},
     ui::UIManager,
 };
 
-use std::{cell::RefCell, error::Error, io::Read, rc::Rc};
+use std::{cell::RefCell, error::Error, io::Read, path::Path, rc::Rc};
 
 const FRAME_DURATION_MS: f64 = 16.742706458499015;
 const FRAME_DURATION_MICROS: u64 = (FRAME_DURATION_MS * 1_000.0) as u64;
@@ -74,12 +85,20 @@ fn main() -> Result<(), Box<dyn Error>> {
     gl_attr.set_double_buffer(true);
     gl_attr.set_multisample_samples(4);
     gl_attr.set_framebuffer_srgb_compatible(true);
-    let audio_subsystem = sdl_context.audio()?;
+    // Machines without a sound card still run, just silently
+    let audio_subsystem = sdl_context
+        .audio()
+        .map_err(|e| eprintln!("Error initializing audio: {e}."))
+        .ok();
     let controller_subsystem = sdl_context.game_controller()?;
-    let _gamepad = initialize_gamepad(controller_subsystem);
+    let mut gamepad = initialize_gamepad(controller_subsystem);
 
     // Initialze EventSystem
-    let mut event_handler = event_handler::EventHandler::new();
+    let mut event_handler = EventHandler::new();
+    event_handler.paths = Paths::new(config.saves_directory.clone());
+    event_handler.library = Library::load(event_handler.paths.library_file.clone());
+    // Without directories there is nothing to pick from, the File menu still opens it
+    event_handler.library_opened = !event_handler.library.directories.is_empty();
     let mut event_pump: egui_sdl2_gl::sdl2::EventPump = sdl_context.event_pump()?;
 
     // Global colors
@@ -93,6 +112,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             VIEWPORT_HEIGHT as u32 * event_handler.window_scale + 20.0 as u32,
         )
         .opengl()
+        .resizable()
         .build()?;
 
     // Setup egui config
@@ -131,8 +151,26 @@ fn main() -> Result<(), Box<dyn Error>> {
     while !event_handler.quit {
         match event_handler.state {
             State::Splash => {
-                let audio_device = create_audio_theme(&audio_subsystem, &event_handler.volume)?;
-                audio_device.resume();
+                if let Err(e) = window.set_title("gaemboi") {
+                    eprintln!("Error setting window title: {e}.");
+                }
+
+                let _audio_device = audio_subsystem.as_ref().and_then(|audio_subsystem| {
+                    match create_audio_theme(
+                        audio_subsystem,
+                        &event_handler.audio_settings,
+                        &event_handler.volume,
+                    ) {
+                        Ok(audio_device) => {
+                            audio_device.resume();
+                            Some(audio_device)
+                        }
+                        Err(e) => {
+                            eprintln!("Error playing splash theme: {e}.");
+                            None
+                        }
+                    }
+                });
 
                 while !event_handler.quit {
                     let frame_start_time = std::time::Instant::now();
@@ -177,9 +215,13 @@ fn main() -> Result<(), Box<dyn Error>> {
             State::Play => {
                 let file_path = event_handler.file_path.clone().unwrap();
                 event_handler.file_path = None;
+                event_handler.rom_path = Some(file_path.clone());
 
-                let rom_data = match read_file(&file_path) {
-                    Ok(rom_data) => rom_data,
+                let rom_data = match rom_file::read(&file_path) {
+                    Ok(rom) => {
+                        event_handler.rom_checksum = rom.checksum;
+                        rom.data
+                    }
                     Err(error) => {
                         println!("{}", error);
                         event_handler.state = State::Splash;
@@ -187,7 +229,25 @@ fn main() -> Result<(), Box<dyn Error>> {
                     }
                 };
 
-                let ring_buffer = HeapRb::<u8>::new(512 * 16);
+                let is_gbs = gbs::is_gbs(&rom_data);
+                let rom_data = if is_gbs {
+                    match load_gbs(&rom_data, &mut event_handler) {
+                        Ok(rom_data) => rom_data,
+                        Err(error) => {
+                            println!("{}", error);
+                            event_handler.state = State::Splash;
+                            continue;
+                        }
+                    }
+                } else {
+                    event_handler.gbs_header = None;
+                    rom_data
+                };
+
+                colors.borrow_mut().load_for_rom(&file_path, &rom_data);
+
+                let ring_buffer =
+                    HeapRb::<f32>::new(event_handler.audio_settings.ring_buffer_capacity());
                 let (prod, cons) = ring_buffer.split();
 
                 let mut emulation = match Emulation::new(
@@ -204,9 +264,28 @@ fn main() -> Result<(), Box<dyn Error>> {
                     }
                 };
 
-                match read_file(&file_path.replace(".gb", ".sav")) {
-                    Ok(data) => emulation.cpu.memory_bus.load_game(data),
-                    Err(_) => println!("Couldn't load game progress."),
+                let title = match &event_handler.gbs_header {
+                    Some(header) => &header.title,
+                    None => &emulation.cpu.memory_bus.cartridge.header.title,
+                };
+                if let Err(e) = window.set_title(&format!("gaemboi - {}", title)) {
+                    eprintln!("Error setting window title: {e}.");
+                }
+
+                event_handler.library_opened = false;
+                if let Err(e) = event_handler.library.mark_played(&file_path) {
+                    eprintln!("Error saving ROM library: {e}.");
+                }
+
+                let rom_checksum = event_handler.rom_checksum;
+                let save_path = event_handler.paths.save_path(&file_path, rom_checksum);
+                if !is_gbs {
+                    let existing_save_path =
+                        event_handler.paths.existing_save_path(&file_path, rom_checksum);
+                    match read_file(&existing_save_path) {
+                        Ok(data) => emulation.cpu.memory_bus.load_game(data),
+                        Err(_) => println!("Couldn't load game progress."),
+                    }
                 }
 
                 emulation.run(
@@ -219,16 +298,22 @@ fn main() -> Result<(), Box<dyn Error>> {
                     &mut painter,
                     &mut ui_manager,
                     colors.clone(),
-                    &audio_subsystem,
+                    audio_subsystem.as_ref(),
+                    gamepad.as_mut(),
                     cons,
                 );
 
-                emulation
-                    .cpu
-                    .memory_bus
-                    .save_game(&file_path.replace(".gb", ".sav"));
+                if !is_gbs {
+                    emulation.cpu.memory_bus.save_game(&save_path);
+                }
 
-                event_handler.state = State::Splash;
+                // Switching GBS tracks reloads the file without going through the splash screen
+                event_handler.state = match event_handler.file_path {
+                    Some(_) => State::Play,
+                    None => State::Splash,
+                };
+                event_handler.library_opened = event_handler.file_path.is_none()
+                    && !event_handler.library.directories.is_empty();
                 event_handler.quit = false;
             }
         }
@@ -263,7 +348,26 @@ fn initialize_gamepad(controller_subsystem: GameControllerSubsystem) -> Option<G
     })
 }
 
-fn read_file(file_path: &String) -> Result<Vec<u8>, Box<dyn Error>> {
+// Builds a ROM image that plays the selected track, the first one for newly opened files
+fn load_gbs(data: &[u8], event_handler: &mut EventHandler) -> Result<Vec<u8>, Box<dyn Error>> {
+    let header = GbsHeader::parse(data)?;
+    let song = event_handler
+        .gbs_track
+        .take()
+        .filter(|song| *song < header.song_count)
+        .unwrap_or(header.first_song - 1);
+
+    let rom_data = gbs::build_image(data, &header, song);
+
+    event_handler.gbs_header = Some(header);
+    event_handler.gbs_song = song;
+    event_handler.gbs_elapsed = 0.0;
+    event_handler.gbs_player_opened = true;
+
+    Ok(rom_data)
+}
+
+fn read_file(file_path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
     let mut file = std::fs::File::open(file_path)?;
     let mut data = Vec::new();
     file.read_to_end(&mut data)?;
@@ -5,7 +5,7 @@
  * @date    May 28, 2024
  */
 
-use std::{cell::RefCell, error::Error, rc::Rc, sync::Arc};
+use std::{cell::RefCell, error::Error, path::Path, rc::Rc, sync::Arc};
 
 use ringbuf::{storage::Heap, wrap::caching::Caching, SharedRb};
 
@@ -14,6 +14,7 @@ use crate::{
     cartridge::Cartridge,
     io::{joypad::Joypad, timer::Timer},
     ppu::{colors::Colors, Ppu},
+    recorder::VgmLog,
     ComponentTick, MemoryAccess,
 };
 
@@ -61,8 +62,14 @@ const HRAM_END: u16 = 0xFFFE;
 
 const INTERRUPT_ENABLE: u16 = 0xFFFF;
 
+const SOUND_ON_OFF: u16 = 0xFF26;
+const WAVE_RAM_START: u16 = 0xFF30;
+// NR14, NR24, NR34 and NR44
+const CHANNEL_CONTROLS: [u16; 4] = [0xFF14, 0xFF19, 0xFF1E, 0xFF23];
+const TRIGGER_MASK: u8 = 0x80;
+
 pub struct MemoryBus {
-    cartridge: Cartridge,
+    pub cartridge: Cartridge,
     pub ppu: Ppu,
     pub apu: Apu,
     wram: [u8; 8192],
@@ -74,6 +81,13 @@ pub struct MemoryBus {
     serial_sb: u8,
     serial_sc: u8,
     speed_switch: u8,
+    // Last values written to the APU, the registers can't be fully read back
+    audio_registers: [u8; (AUDIO_END - AUDIO_START + 1) as usize],
+    pub vgm_log: Option<VgmLog>,
+    // M-cycles of the current instruction up to its last memory access
+    access_cycles: u8,
+    // M-cycles of the current instruction the APU already ran
+    apu_cycles: u8,
 }
 
 impl MemoryAccess for MemoryBus {
@@ -159,7 +173,14 @@ impl MemoryAccess for MemoryBus {
             // 0xFF0F (Interrupt Flag Register)
             INTERRUPT_FLAG => self.interrupt_flag = value,
             // 0xFF10 - 0xFF3F (APU)
-            AUDIO_START..=AUDIO_END => self.apu.write_byte(address, value),
+            AUDIO_START..=AUDIO_END => {
+                self.apu.write_byte(address, value);
+                self.audio_registers[(address - AUDIO_START) as usize] = value;
+
+                if let Some(vgm_log) = &mut self.vgm_log {
+                    vgm_log.write((address - AUDIO_START) as u8, value);
+                }
+            }
             // 0xFF40 - 0xFF45 (PPU Registers)
             PPU_IO_START..=LINE_Y_COMPARE => self.ppu.write_byte(address, value),
             // 0xFF46 DMA Transfer (Write Only)
@@ -192,7 +213,19 @@ impl ComponentTick for MemoryBus {
         self.interrupt_flag |= self.ppu.interrupts;
         self.ppu.reset_interrupts();
 
-        self.apu.tick(m_cycles);
+        // The rest of the instruction after the last APU access
+        let apu_cycles = m_cycles.saturating_sub(self.apu_cycles);
+        if apu_cycles > 0 {
+            self.apu.tick(apu_cycles);
+        }
+        self.access_cycles = 0;
+        self.apu_cycles = 0;
+
+        self.cartridge.mbc.tick(m_cycles);
+
+        if let Some(vgm_log) = &mut self.vgm_log {
+            vgm_log.tick(m_cycles);
+        }
     }
 }
 
@@ -201,7 +234,7 @@ impl MemoryBus {
         rom_data: Vec<u8>,
         colors: Rc<RefCell<Colors>>,
         fast_forward: Rc<RefCell<u8>>,
-        prod: Caching<Arc<SharedRb<Heap<u8>>>, true, false>
+        prod: Caching<Arc<SharedRb<Heap<f32>>>, true, false>
     ) -> Result<Self, Box<dyn Error>> {
         let cartridge = Cartridge::build(rom_data)?;
 
@@ -218,9 +251,41 @@ impl MemoryBus {
             serial_sc: 0x00,
             timer: Timer::new(),
             speed_switch: 0x00,
+            audio_registers: [0; (AUDIO_END - AUDIO_START + 1) as usize],
+            vgm_log: None,
+            access_cycles: 0,
+            apu_cycles: 0,
         })
     }
 
+    /*
+     * Memory accesses of the CPU, each taking one M-cycle of the current
+     * instruction. The other components catch up once the instruction is
+     * done, but wave RAM and the trigger timing of the channels depend on
+     * the exact cycle, so the APU is run up to the cycle of the access
+     * first. Instructions only take internal cycles after their accesses
+     * to I/O registers, so counting the accesses gives that cycle.
+     */
+    pub fn cpu_read(&mut self, address: u16) -> u8 {
+        self.access_cycle(address);
+        self.read_byte(address)
+    }
+
+    pub fn cpu_write(&mut self, address: u16, value: u8) {
+        self.access_cycle(address);
+        self.write_byte(address, value);
+    }
+
+    fn access_cycle(&mut self, address: u16) {
+        self.access_cycles += 1;
+
+        if (AUDIO_START..=AUDIO_END).contains(&address) {
+            let apu_cycles = self.access_cycles - self.apu_cycles;
+            self.apu.tick(apu_cycles);
+            self.apu_cycles = self.access_cycles;
+        }
+    }
+
     pub fn get_interrupt_flag(&mut self) -> u8 {
         self.interrupt_flag
     }
@@ -237,14 +302,88 @@ impl MemoryBus {
         }
     }
 
+    /*
+     * A log can start in the middle of a song, so it begins with the current
+     * APU state: power, wave RAM, then every other register. Channels that
+     * are playing right now are triggered again to resume their notes.
+     */
+    pub fn start_vgm_log(&mut self) {
+        let mut vgm_log = VgmLog::new();
+        let sound_on_off = self.apu.read_byte(SOUND_ON_OFF);
+
+        let mut write = |address: u16, value: u8| {
+            vgm_log.write((address - AUDIO_START) as u8, value);
+        };
+
+        write(SOUND_ON_OFF, sound_on_off & 0x80);
+
+        for address in (WAVE_RAM_START..=AUDIO_END).chain(AUDIO_START..SOUND_ON_OFF) {
+            let mut value = self.audio_registers[(address - AUDIO_START) as usize];
+
+            if let Some(channel) = CHANNEL_CONTROLS.iter().position(|&a| a == address) {
+                value &= !TRIGGER_MASK;
+                if sound_on_off & (1 << channel) != 0 {
+                    value |= TRIGGER_MASK;
+                }
+            }
+
+            write(address, value);
+        }
+
+        self.vgm_log = Some(vgm_log);
+    }
+
     pub fn load_game(&mut self, ram_data: Vec<u8>) {
         self.cartridge.load_game(ram_data);
     }
 
-    pub fn save_game(&self, file_path: &str) {
+    pub fn save_game(&mut self, file_path: &Path) {
         match self.cartridge.save_game(file_path) {
-            Ok(_) => println!("Game saved."),
+            Ok(true) => println!("Game saved."),
+            Ok(false) => {}
             Err(e) => eprintln!("Error saving game: {e}."),
         }
     }
 }
+
+#[cfg(test)]
+mod memory_bus_tests {
+    use super::*;
+    use ringbuf::{traits::Split, HeapRb};
+
+    fn memory_bus() -> MemoryBus {
+        let (prod, _cons) = HeapRb::<f32>::new(16).split();
+        MemoryBus::new(
+            vec![0; 0x8000],
+            Rc::new(RefCell::new(Colors::new())),
+            Rc::new(RefCell::new(1)),
+            prod,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn wave_ram_access_in_its_cycle() {
+        let mut memory_bus = memory_bus();
+        memory_bus.cpu_write(0xFF1A, 0x80);
+        memory_bus.cpu_write(0xFF1D, 0xFC);
+        memory_bus.tick(2);
+
+        // LDH (n),A triggers in its third cycle, a sample is read every 2 M-cycles
+        memory_bus.cpu_read(0x0000);
+        memory_bus.cpu_read(0x0001);
+        memory_bus.cpu_write(0xFF1E, 0x87);
+        memory_bus.tick(3);
+
+        // LD A,(C) reads in its second cycle, right when the channel reads
+        memory_bus.cpu_read(0x0000);
+        assert_eq!(memory_bus.cpu_read(0xFF30), 0x84);
+        memory_bus.tick(2);
+
+        // LDH A,(n) reads in its third cycle, after the channel read
+        memory_bus.cpu_read(0x0000);
+        memory_bus.cpu_read(0x0001);
+        assert_eq!(memory_bus.cpu_read(0xFF30), 0xFF);
+        memory_bus.tick(3);
+    }
+}
@@ -0,0 +1,260 @@
+/*
+ * @file    paths.rs
+ * @brief   Decides where saves, screenshots and the ROM library are written.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    export::{is_timestamped, timestamp},
+    rom_file,
+};
+
+const APP_DIRECTORY: &str = "gaemboi";
+const SAVES_DIRECTORY: &str = "saves";
+const SCREENSHOTS_DIRECTORY: &str = "screenshots";
+const LIBRARY_FILE: &str = "library.txt";
+
+/*
+ * Files go to the XDG data directory on Linux, and next to the ROM on
+ * other systems or when no home directory is set. Saves from before are
+ * still found next to the ROM, and move over with the next save. In the
+ * saves directory the checksum of the unpatched ROM is part of the name,
+ * so ROMs with the same file name from different folders keep their own
+ * saves and backups, while a patch can be updated without losing the save.
+ */
+#[derive(Clone)]
+pub struct Paths {
+    saves_directory: Option<PathBuf>,
+    screenshots_directory: Option<PathBuf>,
+    // In the working directory without a data directory
+    pub library_file: PathBuf,
+}
+
+impl Paths {
+    pub fn new(saves_directory: Option<PathBuf>) -> Self {
+        let data_directory = data_directory();
+
+        Self {
+            saves_directory: saves_directory.or_else(|| {
+                data_directory
+                    .as_ref()
+                    .map(|directory| directory.join(SAVES_DIRECTORY))
+            }),
+            screenshots_directory: data_directory
+                .as_ref()
+                .map(|directory| directory.join(SCREENSHOTS_DIRECTORY)),
+            library_file: data_directory.unwrap_or_default().join(LIBRARY_FILE),
+        }
+    }
+
+    pub fn save_path(&self, rom_path: &str, rom_checksum: u16) -> PathBuf {
+        match &self.saves_directory {
+            Some(directory) => {
+                directory.join(format!("{}_{:04X}.sav", rom_stem(rom_path), rom_checksum))
+            }
+            None => save_next_to_rom(rom_path),
+        }
+    }
+
+    // The save to load, which may still be next to the ROM
+    pub fn existing_save_path(&self, rom_path: &str, rom_checksum: u16) -> PathBuf {
+        let save_path = self.save_path(rom_path, rom_checksum);
+        let legacy_path = save_next_to_rom(rom_path);
+
+        if !save_path.exists() && legacy_path.exists() {
+            return legacy_path;
+        }
+
+        save_path
+    }
+
+    // Timestamped PNG in a directory of its own for every ROM
+    pub fn screenshot_path(&self, rom_path: &str) -> PathBuf {
+        let stem = rom_stem(rom_path);
+
+        match &self.screenshots_directory {
+            Some(directory) => directory
+                .join(&stem)
+                .join(format!("{}_{}.png", stem, timestamp())),
+            None => Path::new(rom_path).with_file_name(format!("{}_{}.png", stem, timestamp())),
+        }
+    }
+
+    // Timestamps in the names sort screenshots by age
+    pub fn latest_screenshot(&self, rom_path: &str) -> Option<PathBuf> {
+        let screenshot_path = self.screenshot_path(rom_path);
+        let directory = screenshot_path
+            .parent()
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let stem = rom_stem(rom_path);
+
+        std::fs::read_dir(directory)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy())
+                    .is_some_and(|name| is_timestamped(&name, &stem, "png"))
+            })
+            .max()
+    }
+}
+
+// ROM file name without archive and ROM extensions
+pub fn rom_stem(rom_path: &str) -> String {
+    rom_file::base_path(rom_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "game".to_string())
+}
+
+fn save_next_to_rom(rom_path: &str) -> PathBuf {
+    rom_file::add_extension(&rom_file::base_path(rom_path), "sav")
+}
+
+// $XDG_DATA_HOME/gaemboi, which defaults to ~/.local/share/gaemboi
+#[cfg(target_os = "linux")]
+fn data_directory() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .filter(|home| !home.is_empty())
+                .map(|home| Path::new(&home).join(".local").join("share"))
+        })?;
+
+    Some(data_home.join(APP_DIRECTORY))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn data_directory() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod paths_tests {
+    use super::*;
+
+    #[test]
+    fn places_files_by_rom_stem() {
+        let paths = Paths {
+            saves_directory: Some(PathBuf::from("/data/saves")),
+            screenshots_directory: None,
+            library_file: PathBuf::from(LIBRARY_FILE),
+        };
+
+        assert_eq!(
+            paths.save_path("roms/Pokemon Red.gb", 0x0012),
+            Path::new("/data/saves/Pokemon Red_0012.sav")
+        );
+        assert_eq!(
+            paths.save_path("/games/v1.1 hack.gbc.gz", 0x0034),
+            Path::new("/data/saves/v1.1 hack_0034.sav")
+        );
+        assert!(paths
+            .screenshot_path("roms/tetris.zip")
+            .to_string_lossy()
+            .starts_with("roms/tetris_"));
+
+        let paths = Paths {
+            saves_directory: None,
+            screenshots_directory: Some(PathBuf::from("/data/screenshots")),
+            library_file: PathBuf::from(LIBRARY_FILE),
+        };
+
+        assert_eq!(
+            paths.save_path("roms/tetris.gb", 0x0012),
+            Path::new("roms/tetris.sav")
+        );
+        assert!(paths
+            .screenshot_path("roms/tetris.gb")
+            .starts_with("/data/screenshots/tetris"));
+    }
+
+    #[test]
+    fn keeps_same_named_roms_apart() {
+        let paths = Paths {
+            saves_directory: Some(PathBuf::from("/data/saves")),
+            screenshots_directory: None,
+            library_file: PathBuf::from(LIBRARY_FILE),
+        };
+
+        assert_ne!(
+            paths.save_path("usa/Tetris.gb", 0x0001),
+            paths.save_path("japan/Tetris.gb", 0x0002)
+        );
+        assert_eq!(
+            paths.save_path("usa/Tetris.gb", 0x0001),
+            paths.save_path("copy/Tetris.gb", 0x0001)
+        );
+    }
+
+    #[test]
+    fn finds_latest_screenshot() {
+        let directory =
+            std::env::temp_dir().join(format!("gaemboi_screenshots_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let paths = Paths {
+            saves_directory: None,
+            screenshots_directory: None,
+            library_file: PathBuf::from(LIBRARY_FILE),
+        };
+        let rom_path = directory.join("tetris.gb").to_string_lossy().into_owned();
+        assert_eq!(paths.latest_screenshot(&rom_path), None);
+
+        // Next to the ROM, the screenshots of other games sort after the own ones
+        for name in [
+            "tetris_2026-01-01_00-00-00.png",
+            "tetris_2026-02-01_00-00-00.png",
+            "tetris_dx_2026-03-01_00-00-00.png",
+            "tetris_notes.png",
+        ] {
+            std::fs::write(directory.join(name), [0]).unwrap();
+        }
+
+        assert_eq!(
+            paths.latest_screenshot(&rom_path),
+            Some(directory.join("tetris_2026-02-01_00-00-00.png"))
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn keeps_save_when_patched() {
+        let directory =
+            std::env::temp_dir().join(format!("gaemboi_patched_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let paths = Paths {
+            saves_directory: Some(PathBuf::from("/data/saves")),
+            screenshots_directory: None,
+            library_file: PathBuf::from(LIBRARY_FILE),
+        };
+        let rom_path = directory.join("game.gb").to_string_lossy().into_owned();
+        std::fs::write(&rom_path, vec![0; 0x8000]).unwrap();
+        let rom = rom_file::read(&rom_path).unwrap();
+
+        // IPS patch writing 0x42 to 0x0200
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(&[0x00, 0x02, 0x00, 0x00, 0x01, 0x42]);
+        patch.extend_from_slice(b"EOF");
+        std::fs::write(directory.join("game.ips"), patch).unwrap();
+        let patched = rom_file::read(&rom_path).unwrap();
+
+        assert_ne!(patched.data, rom.data);
+        assert_eq!(
+            paths.save_path(&rom_path, patched.checksum),
+            paths.save_path(&rom_path, rom.checksum)
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,342 @@
+/*
+ * @file    ppu/cgb_palettes.rs
+ * @brief   Built-in palettes the CGB boot ROM uses to colorize DMG games.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use crate::ppu::colors::Palette;
+
+const TITLE_START: usize = 0x0134;
+const TITLE_END: usize = 0x0143;
+const NEW_LICENSEE_CODE: usize = 0x0144;
+const OLD_LICENSEE_CODE: usize = 0x014B;
+
+/*
+ * Palette data of the boot ROM, four shades per palette. The boot ROM
+ * stores them as RGB555, these are the same colors as 0xRRGGBB.
+ */
+#[rustfmt::skip]
+const COLORS: [u32; 120] = [
+    0xFFFFFF, 0xFFAD63, 0x843100, 0x000000,
+    0xFFE6C5, 0xCE9C84, 0x846B29, 0x5A3108,
+    0xFFFFFF, 0x8C8CDE, 0x52528C, 0x000000,
+    0xFFFFFF, 0x7BFF31, 0x008400, 0x000000,
+    0xFFFFFF, 0xFF8484, 0x943A3A, 0x000000,
+    0xFFFFFF, 0xA5A5A5, 0x525252, 0x000000,
+    0xFFFFFF, 0xFFFF00, 0x7B4A00, 0x000000,
+    0xFFFFFF, 0x7BFF00, 0xB57300, 0x000000,
+    0xFFFFFF, 0xADAD84, 0x42737B, 0x000000,
+    0xA59CFF, 0xFFFF00, 0x006300, 0x000000,
+    0xFFFFCE, 0x63EFEF, 0x9C8431, 0x5A5A5A,
+    0xB5B5FF, 0xFFFF94, 0xAD5A42, 0x000000,
+    0xFFFFA5, 0xFF9494, 0x9494FF, 0x000000,
+    0xFFFF9C, 0x94B5FF, 0x639473, 0x003A3A,
+    0x6BFF00, 0xFFFFFF, 0xFF524A, 0x000000,
+    0x52DE00, 0xFF8400, 0xFFFF00, 0xFFFFFF,
+    0xFFFFFF, 0xFF7300, 0x944200, 0x000000,
+    0xFFC542, 0xFFD600, 0x943A00, 0x4A0000,
+    0xFFFFFF, 0x52FF00, 0xFF4200, 0x000000,
+    0xFF6352, 0xD60000, 0x630000, 0x000000,
+    0xFFFFFF, 0xFF9C00, 0xFF0000, 0x000000,
+    0xFFFFFF, 0x00FF00, 0x318400, 0x004A00,
+    0xFFFFFF, 0x5ABDFF, 0xFF0000, 0x0000FF,
+    0xFFFFFF, 0xFFFF7B, 0x0084FF, 0xFF0000,
+    0xFFFFFF, 0xFFFF00, 0xFF0000, 0x000000,
+    0xFFFF00, 0xFF0000, 0x630000, 0x000000,
+    0xFFFFFF, 0xFFCE00, 0x9C6300, 0x000000,
+    0x000000, 0x008484, 0xFFDE00, 0xFFFFFF,
+    0xFFFFFF, 0x63A5FF, 0x0000FF, 0x000000,
+    0xFFFFFF, 0x7BFF31, 0x0063C5, 0x000000,
+];
+
+/*
+ * OBJ0, OBJ1 and BG palette of every combination, as offsets into COLORS.
+ * Three of them don't start at a palette boundary and mix the shades of
+ * two neighbouring palettes, which the boot ROM does just the same.
+ */
+const COMBINATIONS: [(usize, usize, usize); 51] = [
+    (16, 16, 116),
+    (72, 72, 72),
+    (80, 80, 80),
+    (96, 96, 96),
+    (36, 36, 36),
+    (0, 0, 0),
+    (108, 108, 108),
+    (20, 20, 20),
+    (48, 48, 48),
+    (104, 104, 104),
+    (64, 32, 32),
+    (16, 112, 112),
+    (16, 8, 8),
+    (12, 16, 16),
+    (16, 116, 116),
+    (112, 16, 112),
+    (8, 68, 8),
+    (64, 64, 32),
+    (16, 16, 28),
+    (16, 16, 72),
+    (16, 16, 80),
+    (76, 76, 36),
+    (15, 15, 44),
+    (68, 68, 8),
+    (16, 16, 8),
+    (16, 16, 12),
+    (112, 112, 0),
+    (12, 12, 0),
+    (0, 0, 4),
+    (72, 88, 72),
+    (80, 88, 80),
+    (96, 88, 96),
+    (64, 88, 32),
+    (68, 16, 52),
+    (111, 0, 56),
+    (111, 16, 60),
+    (76, 88, 36),
+    (64, 112, 40),
+    (16, 92, 112),
+    (68, 88, 8),
+    (16, 0, 8),
+    (16, 112, 12),
+    (112, 12, 0),
+    (12, 112, 16),
+    (84, 112, 16),
+    (12, 112, 0),
+    (100, 12, 112),
+    (0, 112, 32),
+    (16, 12, 112),
+    (112, 12, 24),
+    (16, 112, 116),
+];
+
+// BG, OBJ0 and OBJ1 used for games that aren't part of the table
+pub const DEFAULT: [Palette; 3] = combination(0);
+
+// Palettes selectable by holding a button combination while the boot logo is shown
+pub const KEY_COMBOS: [(&str, [Palette; 3]); 12] = [
+    ("Up", combination(5)),
+    ("Up + A", combination(43)),
+    ("Up + B", combination(28)),
+    ("Left", combination(48)),
+    ("Left + A", combination(40)),
+    ("Left + B", combination(7)),
+    ("Down", combination(8)),
+    ("Down + A", combination(3)),
+    ("Down + B", combination(49)),
+    ("Right", combination(1)),
+    ("Right + A", combination(0)),
+    ("Right + B", combination(6)),
+];
+
+/*
+ * The boot ROM sums up the title bytes and looks the result up in its
+ * table, together with the combination to use. Some checksums are shared
+ * by several games, so the later entries are told apart by the fourth
+ * letter of the title. Games that aren't found get the default palettes.
+ */
+const TITLE_CHECKSUMS: [(u8, usize); 94] = [
+    (0x00, 0),  // Titles that sum up to zero
+    (0x88, 4),  // ALLEY WAY
+    (0x16, 5),  // YAKUMAN
+    (0x36, 35), // BASEBALL, Game and Watch 2
+    (0xD1, 34), // TENNIS
+    (0xDB, 3),  // TETRIS
+    (0xF2, 31), // QIX
+    (0x3C, 15), // DR.MARIO
+    (0x8C, 10), // RADARMISSION
+    (0x92, 5),  // F1RACE
+    (0x3D, 19), // YOSSY NO TAMAGO
+    (0x5C, 36),
+    (0x58, 7),  // X
+    (0xC9, 37), // MARIOLAND2
+    (0x3E, 30), // YOSSY NO COOKIE
+    (0x70, 44), // ZELDA
+    (0x1D, 21),
+    (0x59, 32),
+    (0x69, 31), // TETRIS FLASH
+    (0x19, 20), // DONKEY KONG
+    (0x35, 5),  // MARIO'S PICROSS
+    (0xA8, 33),
+    (0x14, 13), // POKEMON RED, GAMEBOYCAMERA G
+    (0xAA, 14), // POKEMON GREEN
+    (0x75, 5),  // PICROSS 2
+    (0x95, 29), // YOSSY NO PANEPON
+    (0x99, 5),  // KIRAKIRA KIDS
+    (0x34, 18), // GAMEBOY GALLERY
+    (0x6F, 9),  // POCKETCAMERA
+    (0x15, 3),
+    (0xFF, 2),  // BALLOON KID
+    (0x97, 26), // KINGOFTHEZOO
+    (0x4B, 25), // DMG FOOTBALL
+    (0x90, 25), // WORLD CUP
+    (0x17, 41), // OTHELLO
+    (0x10, 42), // SUPER RC PRO-AM
+    (0x39, 26), // DYNABLASTER
+    (0xF7, 45), // BOY AND BLOB GB2
+    (0xF6, 42), // MEGAMAN
+    (0xA2, 45), // STAR WARS-NOA
+    (0x49, 36),
+    (0x4E, 38), // WAVERACE
+    (0x43, 26),
+    (0x68, 42), // LOLO2
+    (0xE0, 30), // YOSHI'S COOKIE
+    (0x8B, 41), // MYSTIC QUEST
+    (0xF0, 34),
+    (0xCE, 34), // TOPRANKINGTENNIS
+    (0x0C, 5),  // MANSELL
+    (0x29, 42), // MEGAMAN3
+    (0xE8, 6),  // SPACE INVADERS
+    (0xB7, 5),  // GAME&WATCH
+    (0x86, 33), // DONKEYKONGLAND95
+    (0x9A, 25), // ASTEROIDS/MISCMD
+    (0x52, 42), // STREET FIGHTER 2
+    (0x01, 42), // DEFENDER/JOUST
+    (0x9D, 40), // KILLERINSTINCT95
+    (0x71, 2),  // TETRIS BLAST
+    (0x9C, 16), // PINOCCHIO
+    (0xBD, 25),
+    (0x5D, 42), // BA.TOSHINDEN
+    (0x6D, 42), // NETTOU KOF 95
+    (0x67, 5),
+    (0x3F, 0),  // TETRIS PLUS
+    (0x6B, 39), // DONKEYKONGLAND 3
+    // From here on the fourth letter of the title has to match too
+    (0xB3, 36),
+    (0x46, 22), // SUPER MARIOLAND
+    (0x28, 25), // GOLF
+    (0xA5, 6),  // SOLARSTRIKER
+    (0xC6, 32), // GBWARS
+    (0xD3, 12), // KAERUNOTAMENI
+    (0x27, 36),
+    (0x61, 11), // POKEMON BLUE
+    (0x18, 39), // DONKEYKONGLAND
+    (0x66, 18), // GAMEBOY GALLERY2
+    (0x6A, 39), // DONKEYKONGLAND 2
+    (0xBF, 24), // KID ICARUS
+    (0x0D, 31), // TETRIS2
+    (0xF4, 50),
+    (0xB3, 17), // MOGURANYA
+    (0x46, 46),
+    (0x28, 6),  // GALAGA&GALAXIAN
+    (0xA5, 27), // BT2RAGNAROKWORLD
+    (0xC6, 0),  // KEN GRIFFEY JR
+    (0xD3, 47),
+    (0x27, 41), // MAGNETIC SOCCER
+    (0x61, 41), // VEGAS STAKES
+    (0x18, 0),
+    (0x66, 0),  // MILLI/CENTI/PEDE
+    (0x6A, 19), // MARIO & YOSHI
+    (0xBF, 34), // SOCCER
+    (0x0D, 23), // POKEBOM
+    (0xF4, 18), // G&W GALLERY
+    (0xB3, 29), // TETRIS ATTACK
+];
+
+// Index of the first entry that needs the fourth letter to match
+const FIRST_AMBIGUOUS: usize = 65;
+// One letter for every entry from FIRST_AMBIGUOUS on
+const FOURTH_LETTERS: &[u8; 29] = b"BEFAARBEKEK R-URAR INAILICE R";
+
+pub fn lookup(rom_data: &[u8]) -> [Palette; 3] {
+    if rom_data.len() <= OLD_LICENSEE_CODE || !is_nintendo(rom_data) {
+        return DEFAULT;
+    }
+
+    let checksum = title_checksum(rom_data);
+    let fourth_letter = rom_data[TITLE_START + 3];
+
+    TITLE_CHECKSUMS
+        .iter()
+        .enumerate()
+        .find(|(index, (title_checksum, _))| {
+            *title_checksum == checksum
+                && (*index < FIRST_AMBIGUOUS
+                    || FOURTH_LETTERS[index - FIRST_AMBIGUOUS] == fourth_letter)
+        })
+        .map(|(_, (_, index))| combination(*index))
+        .unwrap_or(DEFAULT)
+}
+
+// BG, OBJ0 and OBJ1 palettes of a combination
+const fn combination(index: usize) -> [Palette; 3] {
+    let (obj0, obj1, bg) = COMBINATIONS[index];
+
+    [palette(bg), palette(obj0), palette(obj1)]
+}
+
+const fn palette(offset: usize) -> Palette {
+    Palette::from_hex([
+        COLORS[offset],
+        COLORS[offset + 1],
+        COLORS[offset + 2],
+        COLORS[offset + 3],
+    ])
+}
+
+// The table only applies to games published by Nintendo
+fn is_nintendo(rom_data: &[u8]) -> bool {
+    match rom_data[OLD_LICENSEE_CODE] {
+        0x01 => true,
+        0x33 => &rom_data[NEW_LICENSEE_CODE..NEW_LICENSEE_CODE + 2] == b"01",
+        _ => false,
+    }
+}
+
+fn title_checksum(rom_data: &[u8]) -> u8 {
+    rom_data[TITLE_START..=TITLE_END]
+        .iter()
+        .fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+#[cfg(test)]
+mod cgb_palettes_tests {
+    use super::*;
+    use egui_sdl2_gl::egui::Color32;
+
+    fn rom(title: &str, licensee: u8) -> Vec<u8> {
+        let mut rom_data = vec![0; 0x150];
+        rom_data[TITLE_START..TITLE_START + title.len()].copy_from_slice(title.as_bytes());
+        rom_data[OLD_LICENSEE_CODE] = licensee;
+        rom_data
+    }
+
+    #[test]
+    fn tells_colliding_checksums_apart() {
+        // Both pairs share a checksum, only the fourth letter differs
+        assert_eq!(title_checksum(&rom("POKEMON BLUE", 0x01)), 0x61);
+        assert_eq!(title_checksum(&rom("VEGAS STAKES", 0x01)), 0x61);
+        assert!(lookup(&rom("POKEMON BLUE", 0x01)) == combination(11));
+        assert!(lookup(&rom("VEGAS STAKES", 0x01)) == combination(41));
+
+        assert_eq!(title_checksum(&rom("MOGURANYA", 0x01)), 0xB3);
+        assert_eq!(title_checksum(&rom("TETRIS ATTACK", 0x01)), 0xB3);
+        assert!(lookup(&rom("MOGURANYA", 0x01)) == combination(17));
+        assert!(lookup(&rom("TETRIS ATTACK", 0x01)) == combination(29));
+
+        // A shared checksum with a fourth letter that isn't in the table
+        assert_eq!(title_checksum(&rom("POKDMON BLUF", 0x01)), 0x61);
+        assert!(lookup(&rom("POKDMON BLUF", 0x01)) == DEFAULT);
+    }
+
+    #[test]
+    fn only_colorizes_nintendo_games() {
+        let mut rom_data = rom("POKEMON RED", 0x33);
+        rom_data[NEW_LICENSEE_CODE..NEW_LICENSEE_CODE + 2].copy_from_slice(b"01");
+        assert!(lookup(&rom_data) == combination(13));
+
+        assert!(lookup(&rom("TETRIS", 0x01)) == combination(3));
+        assert!(lookup(&rom("TETRIS", 0x08)) == DEFAULT);
+        assert!(lookup(&rom("NOT IN THE TABLE", 0x01)) == DEFAULT);
+    }
+
+    #[test]
+    fn reads_unaligned_combinations() {
+        // Super Mario Land's objects start at the last shade of the green palette
+        let [bg, obj0, obj1] = lookup(&rom("SUPER MARIOLAND", 0x01));
+        assert_eq!(bg.shades(), palette(44).shades());
+        assert_eq!(obj0.white, Color32::BLACK);
+        assert_eq!(obj0.light, Color32::WHITE);
+        assert_eq!(obj0.dark, Color32::from_rgb(0xFF, 0x84, 0x84));
+        assert!(obj1 == obj0);
+    }
+}
@@ -1,19 +1,189 @@
+use std::{error::Error, path::PathBuf};
+
 use egui_sdl2_gl::egui::Color32;
 
-pub struct Colors {
-    pub black: Color32,
-    pub dark: Color32,
-    pub light: Color32,
+use crate::{
+    ppu::{cgb_palettes, palette_file},
+    rom_file,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Palette {
     pub white: Color32,
+    pub light: Color32,
+    pub dark: Color32,
+    pub black: Color32,
+}
+
+impl Palette {
+    // Shades ordered from white to black, as 0xRRGGBB
+    pub const fn from_hex(shades: [u32; 4]) -> Self {
+        Self {
+            white: hex(shades[0]),
+            light: hex(shades[1]),
+            dark: hex(shades[2]),
+            black: hex(shades[3]),
+        }
+    }
+
+    pub fn shades(&self) -> [Color32; 4] {
+        [self.white, self.light, self.dark, self.black]
+    }
+
+    pub fn shade(&self, shade: u8) -> Color32 {
+        match shade {
+            0b00 => self.white,
+            0b01 => self.light,
+            0b10 => self.dark,
+            0b11 => self.black,
+            _ => unreachable!(),
+        }
+    }
+}
+
+const fn hex(color: u32) -> Color32 {
+    Color32::from_rgb((color >> 16) as u8, (color >> 8) as u8, color as u8)
+}
+
+pub const DEFAULT_PALETTE: Palette = Palette::from_hex([0xE0F8D0, 0x88C070, 0x346856, 0x081820]);
+
+pub const PRESETS: [(&str, Palette); 11] = [
+    ("Default", DEFAULT_PALETTE),
+    (
+        "Green",
+        Palette::from_hex([0xF8E8F8, 0xA0D080, 0x48A058, 0x181010]),
+    ),
+    (
+        "Red",
+        Palette::from_hex([0xF8E8F8, 0xF8A050, 0xD05030, 0x181010]),
+    ),
+    (
+        "Cyan",
+        Palette::from_hex([0xF8E8F8, 0xA8C8E8, 0x7098C8, 0x181010]),
+    ),
+    (
+        "Yellow",
+        Palette::from_hex([0xF8E8F8, 0xF8E070, 0xD0A000, 0x181010]),
+    ),
+    (
+        "Brown",
+        Palette::from_hex([0xF8E8F8, 0xE0A078, 0xA87048, 0x181010]),
+    ),
+    (
+        "Gray",
+        Palette::from_hex([0xF8E8F8, 0xD0A8B0, 0x787890, 0x181010]),
+    ),
+    (
+        "Purple",
+        Palette::from_hex([0xF8E8F8, 0xD8B0C0, 0xA878B8, 0x181010]),
+    ),
+    (
+        "Blue",
+        Palette::from_hex([0xF8E8F8, 0x90A0D8, 0x5878B8, 0x181010]),
+    ),
+    (
+        "Pink",
+        Palette::from_hex([0xF8E8F8, 0xF0B0C0, 0xE078A8, 0x181010]),
+    ),
+    (
+        "Mew",
+        Palette::from_hex([0xF8E8F8, 0xF0B088, 0x807098, 0x181010]),
+    ),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Layer {
+    Background,
+    Object0,
+    Object1,
+}
+
+/*
+ * Background and both object palettes are colored separately, the same way
+ * the CGB boot ROM colorizes DMG games. A palette stored next to the ROM
+ * takes precedence over the CGB table when a game is loaded.
+ */
+pub struct Colors {
+    pub bg: Palette,
+    pub obj0: Palette,
+    pub obj1: Palette,
+    pub cgb_colorization: bool,
+    // Palettes the CGB boot ROM would pick for the running game
+    pub cgb_palettes: [Palette; 3],
+    // Whether the current palettes were loaded from a per-game file
+    rom_palette: bool,
 }
 
 impl Colors {
     pub fn new() -> Self {
         Self {
-            black: Color32::from_rgb(8, 24, 32),
-            dark: Color32::from_rgb(52, 104, 86),
-            light: Color32::from_rgb(136, 192, 112),
-            white: Color32::from_rgb(224, 248, 208),
+            bg: DEFAULT_PALETTE,
+            obj0: DEFAULT_PALETTE,
+            obj1: DEFAULT_PALETTE,
+            cgb_colorization: false,
+            cgb_palettes: cgb_palettes::DEFAULT,
+            rom_palette: false,
         }
     }
+
+    pub fn palette(&self, layer: Layer) -> &Palette {
+        match layer {
+            Layer::Background => &self.bg,
+            Layer::Object0 => &self.obj0,
+            Layer::Object1 => &self.obj1,
+        }
+    }
+
+    pub fn palettes(&self) -> [Palette; 3] {
+        [self.bg, self.obj0, self.obj1]
+    }
+
+    pub fn set_palettes(&mut self, palettes: [Palette; 3]) {
+        [self.bg, self.obj0, self.obj1] = palettes;
+    }
+
+    pub fn set_all(&mut self, palette: Palette) {
+        self.set_palettes([palette; 3]);
+    }
+
+    pub fn apply_cgb_palettes(&mut self) {
+        self.set_palettes(self.cgb_palettes);
+    }
+
+    pub fn load_for_rom(&mut self, rom_path: &str, rom_data: &[u8]) {
+        self.cgb_palettes = cgb_palettes::lookup(rom_data);
+
+        let path = rom_palette_path(rom_path);
+        if path.exists() {
+            match palette_file::import(&path) {
+                Ok(palettes) => {
+                    self.set_palettes(palettes);
+                    self.rom_palette = true;
+                    return;
+                }
+                Err(e) => eprintln!("Error loading palette: {e}."),
+            }
+        }
+
+        // Keep the current palettes unless they belonged to the previous game
+        if self.cgb_colorization {
+            self.apply_cgb_palettes();
+        } else if self.rom_palette {
+            self.set_all(DEFAULT_PALETTE);
+        }
+
+        self.rom_palette = false;
+    }
+
+    pub fn save_for_rom(&self, rom_path: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let path = rom_palette_path(rom_path);
+        palette_file::export(&path, &self.palettes())?;
+
+        Ok(path)
+    }
+}
+
+// The per-game palette is stored next to the ROM, e.g. tetris.gb -> tetris.pal
+fn rom_palette_path(rom_path: &str) -> PathBuf {
+    rom_file::add_extension(&rom_file::base_path(rom_path), "pal")
 }
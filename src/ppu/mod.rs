@@ -6,16 +6,18 @@
  */
 
 mod background;
+pub mod cgb_palettes;
 pub mod colors;
 mod lcd_control;
 mod lcd_status;
 mod oam;
+pub mod palette_file;
 mod tile;
 mod window;
 
 use std::{cell::RefCell, rc::Rc};
 
-use colors::Colors;
+use colors::{Colors, Layer};
 use egui_sdl2_gl::egui::Color32;
 
 use crate::{
@@ -33,7 +35,7 @@ use crate::{
 };
 
 pub const VRAM_SIZE: usize = 8 * 1024;
-const OAM_SIZE: usize = 40;
+pub const OAM_SIZE: usize = 40;
 
 const TILETABLE_DATA_START: u16 = VRAM_START;
 const TILETABLE_DATA_END: u16 = 0x97FF;
@@ -63,7 +65,7 @@ const CYCLES_VBLANK: u16 = 456;
 const LINES_Y: u8 = 143;
 const MAX_LINES_Y: u8 = 153;
 
-const TILE_WIDTH: u8 = 8;
+pub const TILE_WIDTH: u8 = 8;
 const TILE_HEIGHT: u8 = TILE_WIDTH;
 const TILE_HEIGHT_BIG: u8 = TILE_HEIGHT * 2;
 
@@ -111,6 +113,8 @@ pub struct Ppu {
     counter: u16,
     pub overlap_map: [bool; OVERLAP_MAP_SIZE],
     pub viewport_buffer: [Color32; BUFFER_SIZE],
+    // Gray shade (0 = white, 3 = black) of each viewport pixel, before colors are applied
+    pub shade_buffer: [u8; BUFFER_SIZE],
     pub should_draw: bool,
     colors: Rc<RefCell<Colors>>,
 }
@@ -319,6 +323,7 @@ impl Ppu {
             counter: 0,
             overlap_map: [false; OVERLAP_MAP_SIZE],
             viewport_buffer: [Color32::from_rgb(224, 248, 208); BUFFER_SIZE],
+            shade_buffer: [0; BUFFER_SIZE],
             should_draw: false,
             colors,
         }
@@ -412,11 +417,12 @@ impl Ppu {
             let overlap_offset = self.scan_y as usize + FULL_WIDTH * scan_x as usize;
             self.overlap_map[overlap_offset] = color_index != 0;
 
-            let pixel = self.pixel_color(&self.bg_palette, &color_index);
+            let pixel = self.pixel_color(Layer::Background, &self.bg_palette, &color_index);
 
             // Calculate the offset for the current pixel and update the viewport buffer
             let offset = scan_x as usize + base_offset;
             self.viewport_buffer[offset] = pixel;
+            self.shade_buffer[offset] = shade(&self.bg_palette, &color_index);
         }
     }
 
@@ -477,17 +483,13 @@ impl Ppu {
                     continue;
                 }
 
-                let sprite_palette = if oam_entry.attributes.dmg_palette_enabled() {
-                    self.sprite_palette1
-                } else {
-                    self.sprite_palette0
-                };
-
-                let pixel = self.pixel_color(&sprite_palette, &color_index);
+                let (sprite_palette, layer) = self.object_palette(&oam_entry);
+                let pixel = self.pixel_color(layer, &sprite_palette, &color_index);
 
                 // Calculate the offset for the current pixel and update the viewport buffer
                 let offset = x_offset as usize + base_offset;
                 self.viewport_buffer[offset] = pixel;
+                self.shade_buffer[offset] = shade(&sprite_palette, &color_index);
             }
         }
     }
@@ -540,21 +542,24 @@ impl Ppu {
         self.interrupts = 0;
     }
 
-    fn pixel_color(&self, palette: &u8, color_index: &u8) -> Color32 {
+    fn pixel_color(&self, layer: Layer, palette: &u8, color_index: &u8) -> Color32 {
         let colors = self.colors.as_ref().borrow();
 
-        match (palette >> (color_index << 1)) & 0b11 {
-            0b00 => colors.white,
-            0b01 => colors.light,
-            0b10 => colors.dark,
-            0b11 => colors.black,
-            _ => unreachable!(),
+        colors.palette(layer).shade(shade(palette, color_index))
+    }
+
+    // OBP0 or OBP1, depending on the DMG palette attribute of the object
+    fn object_palette(&self, oam_entry: &OAM) -> (u8, Layer) {
+        if oam_entry.attributes.dmg_palette_enabled() {
+            (self.sprite_palette1, Layer::Object1)
+        } else {
+            (self.sprite_palette0, Layer::Object0)
         }
     }
 
     pub fn tiletable(&self) -> [Color32; TILETABLE_WIDTH * TILETABLE_HEIGHT] {
         let mut tiletable_buffer =
-            [self.colors.as_ref().borrow().white; TILETABLE_WIDTH * TILETABLE_HEIGHT];
+            [self.colors.as_ref().borrow().bg.white; TILETABLE_WIDTH * TILETABLE_HEIGHT];
 
         // Grid vertical lines
         for i in 0..=16 {
@@ -600,13 +605,86 @@ impl Ppu {
         tiletable_buffer
     }
 
+    // Raw tile data (0x8000 - 0x97FF) in the native 2bpp format
+    pub fn tile_data(&self) -> &[u8] {
+        &self.video_ram[..=(TILETABLE_DATA_END - VRAM_START) as usize]
+    }
+
+    // The 256 tiles addressable by a tilemap, ordered by tile index according
+    // to the currently selected addressing mode (LCDC bit 4)
+    pub fn indexed_tile_data(&self) -> Vec<u8> {
+        (0..=255)
+            .map(|index| self.lcd_control.get_address(index))
+            .flat_map(|address| (0..16).map(move |i| self.read_byte(address + i)))
+            .collect()
+    }
+
+    pub fn tilemap_indices(&self, start_address: u16, end_address: u16) -> Vec<u8> {
+        (start_address..=end_address)
+            .map(|address| self.read_byte(address))
+            .collect()
+    }
+
+    pub fn sprite_height(&self) -> usize {
+        if self.lcd_control.object_size() {
+            TILE_HEIGHT_BIG as usize
+        } else {
+            TILE_HEIGHT as usize
+        }
+    }
+
+    // Renders a single OAM entry with its palette and flip attributes applied.
+    // Color index 0 is transparent for objects.
+    pub fn sprite(&self, oam_index: usize) -> Vec<Color32> {
+        let oam_entry = self.oam[oam_index];
+        let height = self.sprite_height();
+        let mut sprite_buffer = vec![Color32::TRANSPARENT; TILE_WIDTH as usize * height];
+
+        let mut tile_index = oam_entry.get_tile_index();
+        if height == TILE_HEIGHT_BIG as usize {
+            tile_index &= 0b1111_1110;
+        }
+
+        let tile_start_address = TILETABLE_DATA_START + (tile_index as u16 * 16);
+        let (sprite_palette, layer) = self.object_palette(&oam_entry);
+
+        for row in 0..height {
+            let line_offset = if oam_entry.attributes.y_flip_enabled() {
+                height - 1 - row
+            } else {
+                row
+            };
+
+            let (first_byte, second_byte) =
+                self.get_tile_bytes(tile_start_address + line_offset as u16 * 2);
+
+            for col in 0..TILE_WIDTH {
+                let pixel_index = if oam_entry.attributes.x_flip_enabled() {
+                    col
+                } else {
+                    7 - col
+                };
+
+                let color_index = color_index(first_byte, second_byte, pixel_index);
+                if color_index == 0 {
+                    continue;
+                }
+
+                sprite_buffer[row * TILE_WIDTH as usize + col as usize] =
+                    self.pixel_color(layer, &sprite_palette, &color_index);
+            }
+        }
+
+        sprite_buffer
+    }
+
     pub fn tilemap(
         &self,
         start_address: u16,
         end_address: u16,
     ) -> [Color32; TILEMAP_WIDTH * TILEMAP_HEIGHT] {
         let mut tilemap_buffer =
-            [self.colors.as_ref().borrow().white; TILEMAP_WIDTH * TILEMAP_HEIGHT];
+            [self.colors.as_ref().borrow().bg.white; TILEMAP_WIDTH * TILEMAP_HEIGHT];
 
         let tiles = (start_address..=end_address)
             .map(|i| self.lcd_control.get_address(self.read_byte(i)))
@@ -646,6 +724,11 @@ fn calculate_address(base_address: u16, x: u8, y: u8) -> u16 {
     base_address + offset
 }
 
+// Maps a color ID to its gray shade through the given palette register
+fn shade(palette: &u8, color_index: &u8) -> u8 {
+    (palette >> (color_index << 1)) & 0b11
+}
+
 // The first byte specifies the least significant bit of the color ID of
 // each pixel, and the second byte specifies the most significant bit
 fn color_index(first_byte: u8, second_byte: u8, pixel_index: u8) -> u8 {
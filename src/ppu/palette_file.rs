@@ -0,0 +1,167 @@
+/*
+ * @file    ppu/palette_file.rs
+ * @brief   Imports and exports palettes as .pal, .gpl and hex files.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{error::Error, fs, path::Path};
+
+use egui_sdl2_gl::egui::Color32;
+
+use crate::ppu::colors::Palette;
+
+/*
+ * Supported formats are JASC-PAL and RIFF .pal files, GIMP .gpl palettes and
+ * plain lists of hex colors as offered by Lospec. Twelve colors are read as
+ * BG, OBJ0 and OBJ1, four colors are used for all three palettes.
+ */
+pub fn import(path: &Path) -> Result<[Palette; 3], Box<dyn Error>> {
+    let data = fs::read(path)?;
+
+    if data.starts_with(b"RIFF") {
+        return to_palettes(parse_riff(&data)?, false);
+    }
+
+    let text = String::from_utf8_lossy(&data);
+    let first_line = text.lines().next().unwrap_or_default().trim();
+
+    match first_line {
+        "JASC-PAL" => to_palettes(parse_jasc(&text)?, false),
+        "GIMP Palette" => to_palettes(parse_gpl(&text)?, true),
+        _ => to_palettes(parse_hex(&text)?, true),
+    }
+}
+
+// Writes a JASC-PAL file with the shades of BG, OBJ0 and OBJ1 from white to black
+pub fn export(path: &Path, palettes: &[Palette; 3]) -> Result<(), Box<dyn Error>> {
+    let mut text = String::from("JASC-PAL\r\n0100\r\n12\r\n");
+
+    for color in palettes.iter().flat_map(|palette| palette.shades()) {
+        text.push_str(&format!("{} {} {}\r\n", color.r(), color.g(), color.b()));
+    }
+
+    fs::write(path, text)?;
+
+    Ok(())
+}
+
+/*
+ * Files written by this emulator keep their shade order. Hex and GIMP
+ * palettes are often listed from dark to light, so those are flipped if
+ * their first color is darker than the last one.
+ */
+fn to_palettes(colors: Vec<Color32>, detect_order: bool) -> Result<[Palette; 3], Box<dyn Error>> {
+    let groups = match colors.len() {
+        length if length >= 12 => [&colors[0..4], &colors[4..8], &colors[8..12]],
+        length if length >= 4 => [&colors[0..4]; 3],
+        length => return Err(format!("Palette needs at least 4 colors, found {length}").into()),
+    };
+
+    Ok(groups.map(|group| {
+        let mut shades = [group[0], group[1], group[2], group[3]];
+        if detect_order && luminance(shades[0]) < luminance(shades[3]) {
+            shades.reverse();
+        }
+
+        Palette {
+            white: shades[0],
+            light: shades[1],
+            dark: shades[2],
+            black: shades[3],
+        }
+    }))
+}
+
+fn luminance(color: Color32) -> u32 {
+    color.r() as u32 * 299 + color.g() as u32 * 587 + color.b() as u32 * 114
+}
+
+fn parse_rgb<'a>(mut values: impl Iterator<Item = &'a str>) -> Option<Color32> {
+    let mut channel = || values.next()?.parse::<u8>().ok();
+    Some(Color32::from_rgb(channel()?, channel()?, channel()?))
+}
+
+// JASC-PAL, version and color count, followed by one "R G B" line per color
+fn parse_jasc(text: &str) -> Result<Vec<Color32>, Box<dyn Error>> {
+    let mut lines = text.lines().skip(2);
+    let count = lines
+        .next()
+        .and_then(|line| line.trim().parse::<usize>().ok())
+        .ok_or("Invalid JASC-PAL color count")?;
+
+    lines
+        .take(count)
+        .map(|line| {
+            parse_rgb(line.split_whitespace()).ok_or_else(|| "Invalid JASC-PAL color".into())
+        })
+        .collect()
+}
+
+// GIMP palettes have a header, comments and an optional name after each color
+fn parse_gpl(text: &str) -> Result<Vec<Color32>, Box<dyn Error>> {
+    Ok(text
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|line| !line.starts_with("Name:") && !line.starts_with("Columns:"))
+        .filter_map(|line| parse_rgb(line.split_whitespace()))
+        .collect())
+}
+
+// One RRGGBB color per line, with or without a leading '#'
+fn parse_hex(text: &str) -> Result<Vec<Color32>, Box<dyn Error>> {
+    text.lines()
+        .map(|line| line.trim().trim_start_matches('#'))
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let value = u32::from_str_radix(line.get(0..6).ok_or("Invalid hex color")?, 16)?;
+            Ok(Color32::from_rgb(
+                (value >> 16) as u8,
+                (value >> 8) as u8,
+                value as u8,
+            ))
+        })
+        .collect()
+}
+
+// Microsoft RIFF palette: a "PAL " form with a data chunk of R, G, B, flags entries
+fn parse_riff(data: &[u8]) -> Result<Vec<Color32>, Box<dyn Error>> {
+    if data.len() < 24 || &data[8..12] != b"PAL " || &data[12..16] != b"data" {
+        return Err("Invalid RIFF palette".into());
+    }
+
+    let count = u16::from_le_bytes([data[22], data[23]]) as usize;
+    let entries = data
+        .get(24..24 + count * 4)
+        .ok_or("Truncated RIFF palette")?;
+
+    Ok(entries
+        .chunks_exact(4)
+        .map(|entry| Color32::from_rgb(entry[0], entry[1], entry[2]))
+        .collect())
+}
+
+#[cfg(test)]
+mod palette_file_tests {
+    use super::*;
+
+    #[test]
+    fn hex_dark_to_light() {
+        let colors = parse_hex("#081820\n346856\n#88c070\n#e0f8d0\n").unwrap();
+        let palettes = to_palettes(colors, true).unwrap();
+
+        assert_eq!(palettes[0].white, Color32::from_rgb(0xE0, 0xF8, 0xD0));
+        assert_eq!(palettes[2].black, Color32::from_rgb(0x08, 0x18, 0x20));
+    }
+
+    #[test]
+    fn gpl_with_names() {
+        let text = "GIMP Palette\nName: Test\nColumns: 4\n# comment\n255 255 255\tWhite\n170 170 170\n85 85 85\n0 0 0 Black\n";
+        let colors = parse_gpl(text).unwrap();
+
+        assert_eq!(colors.len(), 4);
+        assert_eq!(colors[1], Color32::from_rgb(170, 170, 170));
+    }
+}
@@ -20,7 +20,7 @@ pub struct Tile {
 
 impl Tile {
     pub fn new(bytes: &[u8], colors: Rc<RefCell<Colors>>) -> Self {
-        let colors = colors.as_ref().borrow();
+        let colors = colors.as_ref().borrow().bg;
         let mut data = [[colors.white; TILE_WIDTH]; TILE_HEIGHT];
 
         for row in 0..TILE_HEIGHT {
@@ -0,0 +1,217 @@
+/*
+ * @file    recorder/apng.rs
+ * @brief   Minimal animated PNG encoder.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use egui_sdl2_gl::egui::Color32;
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::recorder::Encoder;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+// Signature (8) + IHDR chunk (25) + acTL length and type (8)
+const ACTL_DATA_OFFSET: u64 = 41;
+
+// The frame delay has to fit into two u16, 400/23891 approximates 70224/4194304 seconds
+const DELAY_NUMERATOR: u16 = 400;
+const DELAY_DENOMINATOR: u16 = 23891;
+
+pub struct ApngEncoder {
+    writer: BufWriter<File>,
+    width: usize,
+    height: usize,
+    frames: u32,
+    sequence: u32,
+}
+
+impl ApngEncoder {
+    pub fn create(path: &Path, width: usize, height: usize) -> Result<Self, Box<dyn Error>> {
+        let mut encoder = Self {
+            writer: BufWriter::new(File::create(path)?),
+            width,
+            height,
+            frames: 0,
+            sequence: 0,
+        };
+
+        encoder.writer.write_all(&PNG_SIGNATURE)?;
+
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+        // Bit depth 8, color type RGB, default compression, filter and no interlace
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+        encoder.write_chunk(b"IHDR", &ihdr)?;
+
+        // Frame count gets patched in finish(), 0 plays indefinitely
+        encoder.write_chunk(b"acTL", &[0; 8])?;
+
+        Ok(encoder)
+    }
+
+    fn write_chunk(&mut self, kind: &[u8; 4], data: &[u8]) -> Result<(), Box<dyn Error>> {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(kind);
+        hasher.update(data);
+
+        self.writer.write_all(&(data.len() as u32).to_be_bytes())?;
+        self.writer.write_all(kind)?;
+        self.writer.write_all(data)?;
+        self.writer.write_all(&hasher.finalize().to_be_bytes())?;
+
+        Ok(())
+    }
+
+    fn next_sequence(&mut self) -> u32 {
+        let sequence = self.sequence;
+        self.sequence += 1;
+
+        sequence
+    }
+
+    fn compress(&self, pixels: &[Color32]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::fast());
+
+        for row in pixels.chunks(self.width).take(self.height) {
+            // Filter type None
+            let mut scanline = Vec::with_capacity(1 + self.width * 3);
+            scanline.push(0);
+            for color in row {
+                scanline.extend_from_slice(&[color.r(), color.g(), color.b()]);
+            }
+            zlib.write_all(&scanline)?;
+        }
+
+        Ok(zlib.finish()?)
+    }
+}
+
+impl Encoder for ApngEncoder {
+    fn write_frame(&mut self, pixels: &[Color32]) -> Result<(), Box<dyn Error>> {
+        let mut fctl = Vec::with_capacity(26);
+        fctl.extend_from_slice(&self.next_sequence().to_be_bytes());
+        fctl.extend_from_slice(&(self.width as u32).to_be_bytes());
+        fctl.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // x and y offset
+        fctl.extend_from_slice(&[0; 8]);
+        fctl.extend_from_slice(&DELAY_NUMERATOR.to_be_bytes());
+        fctl.extend_from_slice(&DELAY_DENOMINATOR.to_be_bytes());
+        // Dispose op none, blend op source
+        fctl.extend_from_slice(&[0, 0]);
+        self.write_chunk(b"fcTL", &fctl)?;
+
+        let data = self.compress(pixels)?;
+
+        // The first frame doubles as the default image for plain PNG decoders
+        if self.frames == 0 {
+            self.write_chunk(b"IDAT", &data)?;
+        } else {
+            let mut fdat = Vec::with_capacity(4 + data.len());
+            fdat.extend_from_slice(&self.next_sequence().to_be_bytes());
+            fdat.extend_from_slice(&data);
+            self.write_chunk(b"fdAT", &fdat)?;
+        }
+
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    // APNG doesn't carry audio
+    fn write_audio(&mut self, _samples: &[i16]) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.write_chunk(b"IEND", &[])?;
+
+        let mut actl = Vec::with_capacity(8);
+        actl.extend_from_slice(&self.frames.to_be_bytes());
+        actl.extend_from_slice(&0_u32.to_be_bytes());
+
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(b"acTL");
+        hasher.update(&actl);
+
+        self.writer.seek(SeekFrom::Start(ACTL_DATA_OFFSET))?;
+        self.writer.write_all(&actl)?;
+        self.writer.write_all(&hasher.finalize().to_be_bytes())?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod apng_tests {
+    use super::*;
+
+    // Type and data of every chunk, after checking its CRC
+    fn chunks(data: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        let mut chunks = Vec::new();
+        let mut offset = PNG_SIGNATURE.len();
+
+        while offset < data.len() {
+            let length = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+            let kind: [u8; 4] = data[offset + 4..offset + 8].try_into().unwrap();
+            let chunk_data = data[offset + 8..offset + 8 + length].to_vec();
+            let crc = u32::from_be_bytes(
+                data[offset + 8 + length..offset + 12 + length]
+                    .try_into()
+                    .unwrap(),
+            );
+            assert_eq!(crc, crc32fast::hash(&data[offset + 4..offset + 8 + length]));
+
+            chunks.push((kind, chunk_data));
+            offset += 12 + length;
+        }
+
+        chunks
+    }
+
+    fn sequence(data: &[u8]) -> u32 {
+        u32::from_be_bytes(data[0..4].try_into().unwrap())
+    }
+
+    #[test]
+    fn writes_numbered_frames() {
+        let path = std::env::temp_dir().join(format!("gaemboi_{}.png", std::process::id()));
+        let mut encoder = ApngEncoder::create(&path, 4, 2).unwrap();
+        for _ in 0..3 {
+            encoder.write_frame(&[Color32::RED; 8]).unwrap();
+        }
+        encoder.finish().unwrap();
+        drop(encoder);
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data[..8], PNG_SIGNATURE);
+
+        let chunks = chunks(&data);
+        let kinds = chunks.iter().map(|(kind, _)| kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [b"IHDR", b"acTL", b"fcTL", b"IDAT", b"fcTL", b"fdAT", b"fcTL", b"fdAT", b"IEND"]
+        );
+
+        // Frame count and plays
+        assert_eq!(chunks[1].1, [0, 0, 0, 3, 0, 0, 0, 0]);
+
+        // fcTL and fdAT share one sequence, IDAT has no number
+        let sequences = chunks
+            .iter()
+            .filter(|(kind, _)| kind == b"fcTL" || kind == b"fdAT")
+            .map(|(_, data)| sequence(data))
+            .collect::<Vec<_>>();
+        assert_eq!(sequences, [0, 1, 2, 3, 4]);
+    }
+}
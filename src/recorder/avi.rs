@@ -0,0 +1,404 @@
+/*
+ * @file    recorder/avi.rs
+ * @brief   Uncompressed AVI muxer with a 24-bit video and a 16-bit PCM audio stream.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use egui_sdl2_gl::egui::Color32;
+
+use crate::{
+    cpu::clock::CPU_CLOCK_SPEED,
+    recorder::{Encoder, FRAME_T_CYCLES},
+};
+
+const AUDIO_CHANNELS: u16 = 2;
+const BITS_PER_SAMPLE: u16 = 16;
+const BLOCK_ALIGN: u16 = AUDIO_CHANNELS * BITS_PER_SAMPLE / 8;
+
+const AVIF_HASINDEX: u32 = 0x10;
+const AVIIF_KEYFRAME: u32 = 0x10;
+
+// Offsets of the fields that are only known once recording stops
+struct Placeholders {
+    total_frames: u64,
+    video_length: u64,
+    audio_length: u64,
+    movi_size: u64,
+}
+
+struct IndexEntry {
+    id: [u8; 4],
+    offset: u32,
+    size: u32,
+}
+
+pub struct AviEncoder {
+    writer: BufWriter<File>,
+    width: usize,
+    height: usize,
+    placeholders: Placeholders,
+    // File position of the 'movi' list type, which index offsets are relative to
+    movi_start: u64,
+    position: u64,
+    index: Vec<IndexEntry>,
+    frames: u32,
+    audio_blocks: u32,
+}
+
+impl AviEncoder {
+    pub fn create(
+        path: &Path,
+        width: usize,
+        height: usize,
+        sample_rate: u32,
+    ) -> Result<Self, Box<dyn Error>> {
+        let frame_size = (stride(width) * height) as u32;
+        let micro_seconds_per_frame =
+            (FRAME_T_CYCLES as u64 * 1_000_000 / CPU_CLOCK_SPEED as u64) as u32;
+
+        let mut header = Vec::new();
+        header.extend_from_slice(b"RIFF");
+        push_u32(&mut header, 0);
+        header.extend_from_slice(b"AVI ");
+
+        header.extend_from_slice(b"LIST");
+        let hdrl_size = header.len();
+        push_u32(&mut header, 0);
+        header.extend_from_slice(b"hdrl");
+
+        // Main header
+        header.extend_from_slice(b"avih");
+        push_u32(&mut header, 56);
+        push_u32(&mut header, micro_seconds_per_frame);
+        push_u32(
+            &mut header,
+            frame_size * 60 + sample_rate * BLOCK_ALIGN as u32,
+        );
+        push_u32(&mut header, 0);
+        push_u32(&mut header, AVIF_HASINDEX);
+        let total_frames = header.len() as u64;
+        push_u32(&mut header, 0);
+        push_u32(&mut header, 0);
+        push_u32(&mut header, 2);
+        push_u32(&mut header, frame_size);
+        push_u32(&mut header, width as u32);
+        push_u32(&mut header, height as u32);
+        header.extend_from_slice(&[0; 16]);
+
+        // Video stream, one frame every 70224 T-cycles
+        let video_list = header.len();
+        let video_length = push_stream_header(
+            &mut header,
+            b"vids",
+            b"DIB ",
+            FRAME_T_CYCLES,
+            CPU_CLOCK_SPEED,
+            frame_size,
+            0,
+            (width as u16, height as u16),
+        );
+        header.extend_from_slice(b"strf");
+        push_u32(&mut header, 40);
+        push_u32(&mut header, 40);
+        push_u32(&mut header, width as u32);
+        // A positive height stores rows bottom-up
+        push_u32(&mut header, height as u32);
+        push_u16(&mut header, 1);
+        push_u16(&mut header, 24);
+        push_u32(&mut header, 0);
+        push_u32(&mut header, frame_size);
+        header.extend_from_slice(&[0; 16]);
+        patch_list_size(&mut header, video_list);
+
+        // Audio stream, one block per stereo sample
+        let audio_list = header.len();
+        let audio_length = push_stream_header(
+            &mut header,
+            b"auds",
+            &[0; 4],
+            1,
+            sample_rate,
+            sample_rate * BLOCK_ALIGN as u32,
+            BLOCK_ALIGN as u32,
+            (0, 0),
+        );
+        header.extend_from_slice(b"strf");
+        push_u32(&mut header, 16);
+        // WAVE_FORMAT_PCM
+        push_u16(&mut header, 1);
+        push_u16(&mut header, AUDIO_CHANNELS);
+        push_u32(&mut header, sample_rate);
+        push_u32(&mut header, sample_rate * BLOCK_ALIGN as u32);
+        push_u16(&mut header, BLOCK_ALIGN);
+        push_u16(&mut header, BITS_PER_SAMPLE);
+        patch_list_size(&mut header, audio_list);
+
+        let size = (header.len() - hdrl_size - 4) as u32;
+        header[hdrl_size..hdrl_size + 4].copy_from_slice(&size.to_le_bytes());
+
+        header.extend_from_slice(b"LIST");
+        let movi_size = header.len() as u64;
+        push_u32(&mut header, 0);
+        let movi_start = header.len() as u64;
+        header.extend_from_slice(b"movi");
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&header)?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            placeholders: Placeholders {
+                total_frames,
+                video_length,
+                audio_length,
+                movi_size,
+            },
+            movi_start,
+            position: header.len() as u64,
+            index: Vec::new(),
+            frames: 0,
+            audio_blocks: 0,
+        })
+    }
+
+    fn write_chunk(&mut self, id: &[u8; 4], data: &[u8]) -> Result<(), Box<dyn Error>> {
+        let padding = data.len() % 2;
+        let end = self.position + 8 + data.len() as u64 + padding as u64;
+
+        // Index entries and the RIFF size are 32-bit, leave room for the index itself
+        let index_size = (self.index.len() as u64 + 1) * 16 + 8;
+        if end + index_size > u32::MAX as u64 {
+            return Err("AVI file size limit of 4 GiB reached".into());
+        }
+
+        self.index.push(IndexEntry {
+            id: *id,
+            offset: (self.position - self.movi_start) as u32,
+            size: data.len() as u32,
+        });
+
+        self.writer.write_all(id)?;
+        self.writer.write_all(&(data.len() as u32).to_le_bytes())?;
+        self.writer.write_all(data)?;
+        if padding != 0 {
+            self.writer.write_all(&[0])?;
+        }
+
+        self.position = end;
+
+        Ok(())
+    }
+
+    fn patch(&mut self, offset: u64, value: u32) -> Result<(), Box<dyn Error>> {
+        self.writer.seek(SeekFrom::Start(offset))?;
+        self.writer.write_all(&value.to_le_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl Encoder for AviEncoder {
+    fn write_frame(&mut self, pixels: &[Color32]) -> Result<(), Box<dyn Error>> {
+        let stride = stride(self.width);
+        let mut data = vec![0; stride * self.height];
+
+        for (y, row) in pixels.chunks(self.width).take(self.height).enumerate() {
+            let line = &mut data[(self.height - 1 - y) * stride..];
+            for (x, color) in row.iter().enumerate() {
+                line[x * 3..x * 3 + 3].copy_from_slice(&[color.b(), color.g(), color.r()]);
+            }
+        }
+
+        self.write_chunk(b"00db", &data)?;
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    fn write_audio(&mut self, samples: &[i16]) -> Result<(), Box<dyn Error>> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        let data = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        self.write_chunk(b"01wb", &data)?;
+        self.audio_blocks += (samples.len() / AUDIO_CHANNELS as usize) as u32;
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        let movi_size = (self.position - self.movi_start) as u32;
+
+        let mut idx1 = Vec::with_capacity(8 + self.index.len() * 16);
+        idx1.extend_from_slice(b"idx1");
+        push_u32(&mut idx1, (self.index.len() * 16) as u32);
+        for entry in self.index.iter() {
+            idx1.extend_from_slice(&entry.id);
+            push_u32(&mut idx1, AVIIF_KEYFRAME);
+            push_u32(&mut idx1, entry.offset);
+            push_u32(&mut idx1, entry.size);
+        }
+        self.writer.write_all(&idx1)?;
+
+        let riff_size = (self.position + idx1.len() as u64 - 8) as u32;
+
+        self.patch(4, riff_size)?;
+        self.patch(self.placeholders.total_frames, self.frames)?;
+        self.patch(self.placeholders.video_length, self.frames)?;
+        self.patch(self.placeholders.audio_length, self.audio_blocks)?;
+        self.patch(self.placeholders.movi_size, movi_size)?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+// Rows of a DIB are padded to four bytes
+fn stride(width: usize) -> usize {
+    (width * 3 + 3) & !3
+}
+
+fn push_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+/*
+ * Writes a 'strl' list with its 'strh' chunk. Returns the file offset of
+ * dwLength, which is patched once the recording is finished.
+ */
+#[allow(clippy::too_many_arguments)]
+fn push_stream_header(
+    buffer: &mut Vec<u8>,
+    kind: &[u8; 4],
+    handler: &[u8; 4],
+    scale: u32,
+    rate: u32,
+    buffer_size: u32,
+    sample_size: u32,
+    frame: (u16, u16),
+) -> u64 {
+    buffer.extend_from_slice(b"LIST");
+    push_u32(buffer, 0);
+    buffer.extend_from_slice(b"strl");
+
+    buffer.extend_from_slice(b"strh");
+    push_u32(buffer, 56);
+    buffer.extend_from_slice(kind);
+    buffer.extend_from_slice(handler);
+    // Flags, priority and language
+    push_u32(buffer, 0);
+    push_u32(buffer, 0);
+    // Initial frames
+    push_u32(buffer, 0);
+    push_u32(buffer, scale);
+    push_u32(buffer, rate);
+    // Start
+    push_u32(buffer, 0);
+    let length = buffer.len() as u64;
+    push_u32(buffer, 0);
+    push_u32(buffer, buffer_size);
+    // Quality, -1 is the default
+    push_u32(buffer, u32::MAX);
+    push_u32(buffer, sample_size);
+    // Frame rectangle
+    push_u16(buffer, 0);
+    push_u16(buffer, 0);
+    push_u16(buffer, frame.0);
+    push_u16(buffer, frame.1);
+
+    length
+}
+
+// Fills in the size of the 'strl' list that starts at the given offset
+fn patch_list_size(buffer: &mut [u8], start: usize) {
+    let size = (buffer.len() - start - 8) as u32;
+    buffer[start + 4..start + 8].copy_from_slice(&size.to_le_bytes());
+}
+
+#[cfg(test)]
+mod avi_tests {
+    use super::*;
+
+    fn read_u32(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn find(data: &[u8], id: &[u8; 4]) -> Vec<usize> {
+        data.windows(4)
+            .enumerate()
+            .filter(|(_, window)| window == id)
+            .map(|(offset, _)| offset)
+            .collect()
+    }
+
+    #[test]
+    fn writes_sizes_and_index() {
+        let path = std::env::temp_dir().join(format!("gaemboi_{}.avi", std::process::id()));
+        // 3 pixels need a padded stride of 12 bytes
+        let mut encoder = AviEncoder::create(&path, 3, 2, 48000).unwrap();
+        encoder.write_frame(&[Color32::RED; 6]).unwrap();
+        encoder.write_audio(&[1, 2, 3, 4, 5, 6]).unwrap();
+        encoder.write_frame(&[Color32::BLUE; 6]).unwrap();
+        encoder.write_audio(&[]).unwrap();
+        encoder.finish().unwrap();
+        drop(encoder);
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(read_u32(&data, 4) as usize, data.len() - 8);
+        assert_eq!(&data[8..12], b"AVI ");
+
+        // dwTotalFrames of the main header
+        let avih = find(&data, b"avih")[0];
+        assert_eq!(read_u32(&data, avih + 8 + 16), 2);
+
+        // dwLength of the video stream in frames and of the audio stream in blocks
+        let strh = find(&data, b"strh");
+        assert_eq!(&data[strh[0] + 8..strh[0] + 12], b"vids");
+        assert_eq!(read_u32(&data, strh[0] + 8 + 32), 2);
+        assert_eq!(&data[strh[1] + 8..strh[1] + 12], b"auds");
+        assert_eq!(read_u32(&data, strh[1] + 8 + 32), 3);
+
+        let movi = find(&data, b"movi")[0];
+        let idx1 = find(&data, b"idx1")[0];
+        assert_eq!(read_u32(&data, movi - 4) as usize, idx1 - movi);
+
+        // Three chunks, the empty audio block isn't written
+        assert_eq!(read_u32(&data, idx1 + 4), 3 * 16);
+        assert_eq!(idx1 + 8 + 3 * 16, data.len());
+
+        let expected: [(&[u8; 4], u32); 3] = [(b"00db", 24), (b"01wb", 12), (b"00db", 24)];
+        for (index, (id, size)) in expected.iter().enumerate() {
+            let entry = idx1 + 8 + index * 16;
+            assert_eq!(&data[entry..entry + 4], *id);
+            assert_eq!(read_u32(&data, entry + 4), AVIIF_KEYFRAME);
+            assert_eq!(read_u32(&data, entry + 12), *size);
+
+            // Offsets point at the chunk header, relative to the 'movi' type
+            let chunk = movi + read_u32(&data, entry + 8) as usize;
+            assert_eq!(&data[chunk..chunk + 4], *id);
+            assert_eq!(read_u32(&data, chunk + 4), *size);
+        }
+    }
+}
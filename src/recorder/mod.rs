@@ -0,0 +1,191 @@
+/*
+ * @file    recorder/mod.rs
+ * @brief   Records emulated frames and audio to lossless video files.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+mod apng;
+mod avi;
+mod vgm;
+mod wav;
+
+pub use vgm::VgmLog;
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use egui_sdl2_gl::egui::Color32;
+
+use crate::{
+    apu::audio::SAMPLING_FREQUENCY,
+    cpu::clock::CPU_CLOCK_SPEED,
+    export::timestamp,
+    recorder::{apng::ApngEncoder, avi::AviEncoder, wav::WavWriter},
+};
+
+// Exact length of a frame, the clock derives its frame budget from the rounded FPS
+pub const FRAME_T_CYCLES: u32 = 70224;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RecordingFormat {
+    // Animated PNG, video only
+    Apng,
+    // Uncompressed RGB video with 16-bit PCM stereo audio
+    Avi,
+}
+
+pub trait Encoder {
+    fn write_frame(&mut self, pixels: &[Color32]) -> Result<(), Box<dyn Error>>;
+    fn write_audio(&mut self, samples: &[i16]) -> Result<(), Box<dyn Error>>;
+    fn finish(&mut self) -> Result<(), Box<dyn Error>>;
+}
+
+/*
+ * Frames are handed over every time the PPU finishes one, together with the
+ * audio samples that were generated during that frame. Both are produced in
+ * emulated time, so fast forward or frame drops on the host don't affect the
+ * timing of the recorded file.
+ */
+pub struct Recorder {
+    encoder: Box<dyn Encoder>,
+    pub path: PathBuf,
+    pub frames: u32,
+}
+
+impl Recorder {
+    pub fn build(
+        format: RecordingFormat,
+        rom_path: &str,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        let extension = match format {
+            RecordingFormat::Apng => "png",
+            RecordingFormat::Avi => "avi",
+        };
+
+        let path = output_path(rom_path, &timestamp(), extension);
+
+        let encoder: Box<dyn Encoder> = match format {
+            RecordingFormat::Apng => Box::new(ApngEncoder::create(&path, width, height)?),
+            RecordingFormat::Avi => Box::new(AviEncoder::create(
+                &path,
+                width,
+                height,
+                SAMPLING_FREQUENCY as u32,
+            )?),
+        };
+
+        Ok(Self {
+            encoder,
+            path,
+            frames: 0,
+        })
+    }
+
+    pub fn capture(&mut self, pixels: &[Color32], samples: &[i16]) -> Result<(), Box<dyn Error>> {
+        self.encoder.write_frame(pixels)?;
+        self.encoder.write_audio(samples)?;
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.encoder.finish()
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.frames as f32 * FRAME_T_CYCLES as f32 / CPU_CLOCK_SPEED as f32
+    }
+}
+
+/*
+ * Writes the final stereo mix to a WAV file, and optionally one additional
+ * file per channel. Samples come from the APU's capture in emulated time,
+ * unaffected by fast forward, frame drops or the host's audio device.
+ */
+pub struct AudioRecorder {
+    mix: WavWriter,
+    stems: Option<[WavWriter; 4]>,
+    pub path: PathBuf,
+    samples: u64,
+}
+
+impl AudioRecorder {
+    pub fn build(rom_path: &str, stems: bool) -> Result<Self, Box<dyn Error>> {
+        let timestamp = timestamp();
+        let path = output_path(rom_path, &timestamp, "wav");
+        let mix = WavWriter::create(&path, SAMPLING_FREQUENCY as u32)?;
+
+        let stems = if stems {
+            let mut writers = Vec::with_capacity(4);
+            for channel in 1..=4 {
+                let path = output_path(rom_path, &format!("{timestamp}_ch{channel}"), "wav");
+                writers.push(WavWriter::create(&path, SAMPLING_FREQUENCY as u32)?);
+            }
+
+            writers.try_into().ok()
+        } else {
+            None
+        };
+
+        Ok(Self {
+            mix,
+            stems,
+            path,
+            samples: 0,
+        })
+    }
+
+    pub fn has_stems(&self) -> bool {
+        self.stems.is_some()
+    }
+
+    pub fn capture(
+        &mut self,
+        samples: &[i16],
+        stems: &[Vec<i16>; 4],
+    ) -> Result<(), Box<dyn Error>> {
+        self.mix.write(samples)?;
+        self.samples += samples.len() as u64;
+
+        if let Some(writers) = &mut self.stems {
+            for (writer, samples) in writers.iter_mut().zip(stems) {
+                writer.write(samples)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.mix.finish()?;
+
+        if let Some(writers) = &mut self.stems {
+            for writer in writers.iter_mut() {
+                writer.finish()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.samples as f32 / 2.0 / SAMPLING_FREQUENCY as f32
+    }
+}
+
+// <rom>_<suffix>.<extension> next to the ROM
+pub fn output_path(rom_path: &str, suffix: &str, extension: &str) -> PathBuf {
+    let rom_path = Path::new(rom_path);
+    let stem = rom_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "recording".to_string());
+
+    rom_path.with_file_name(format!("{stem}_{suffix}.{extension}"))
+}
@@ -0,0 +1,138 @@
+/*
+ * @file    recorder/vgm.rs
+ * @brief   Logs APU register writes and exports them as VGM 1.71 files.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{error::Error, fs, path::Path};
+
+use crate::cpu::clock::CPU_CLOCK_SPEED;
+
+// VGM timestamps are always counted in samples at 44100 Hz
+const VGM_SAMPLE_RATE: u64 = 44100;
+const VERSION: u32 = 0x0171;
+const HEADER_SIZE: usize = 0x100;
+
+// Header offsets
+const EOF_OFFSET: usize = 0x04;
+const VERSION_OFFSET: usize = 0x08;
+const TOTAL_SAMPLES_OFFSET: usize = 0x18;
+const DATA_OFFSET: usize = 0x34;
+const DMG_CLOCK_OFFSET: usize = 0x80;
+
+// Commands
+const DMG_WRITE: u8 = 0xB3;
+const WAIT_N: u8 = 0x61;
+const WAIT_NTSC_FRAME: u8 = 0x62;
+const WAIT_PAL_FRAME: u8 = 0x63;
+const WAIT_SHORT: u8 = 0x70;
+const END_OF_DATA: u8 = 0x66;
+
+/*
+ * Every write is stored as a DMG register write command, preceded by the
+ * wait since the previous write. The wait is derived from the emulated
+ * cycles that passed, so the log is unaffected by fast forward.
+ * https://vgmrips.net/wiki/VGM_Specification
+ */
+pub struct VgmLog {
+    commands: Vec<u8>,
+    cycles: u64,
+    samples: u64,
+}
+
+impl VgmLog {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            cycles: 0,
+            samples: 0,
+        }
+    }
+
+    pub fn tick(&mut self, m_cycles: u8) {
+        self.cycles += m_cycles as u64 * 4;
+    }
+
+    // Register offset relative to 0xFF10 (NR10)
+    pub fn write(&mut self, register: u8, value: u8) {
+        self.flush_wait();
+        self.commands
+            .extend_from_slice(&[DMG_WRITE, register, value]);
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.cycles as f32 / CPU_CLOCK_SPEED as f32
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        self.flush_wait();
+
+        let mut data = vec![0; HEADER_SIZE];
+        data.extend_from_slice(&self.commands);
+        data.push(END_OF_DATA);
+
+        let mut patch = |offset: usize, value: u32| {
+            data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        patch(0x00, u32::from_le_bytes(*b"Vgm "));
+        patch(VERSION_OFFSET, VERSION);
+        patch(TOTAL_SAMPLES_OFFSET, self.samples as u32);
+        // Relative to the field itself
+        patch(DATA_OFFSET, (HEADER_SIZE - DATA_OFFSET) as u32);
+        patch(DMG_CLOCK_OFFSET, CPU_CLOCK_SPEED);
+
+        let eof = (data.len() - EOF_OFFSET) as u32;
+        data[EOF_OFFSET..EOF_OFFSET + 4].copy_from_slice(&eof.to_le_bytes());
+
+        fs::write(path, data)?;
+
+        Ok(())
+    }
+
+    // Emits the wait commands that bring the log up to the current cycle
+    fn flush_wait(&mut self) {
+        let target = self.cycles * VGM_SAMPLE_RATE / CPU_CLOCK_SPEED as u64;
+        let mut wait = target - self.samples;
+        self.samples = target;
+
+        while wait > 0 {
+            let samples = wait.min(u16::MAX as u64) as u16;
+
+            match samples {
+                735 => self.commands.push(WAIT_NTSC_FRAME),
+                882 => self.commands.push(WAIT_PAL_FRAME),
+                1..=16 => self.commands.push(WAIT_SHORT + samples as u8 - 1),
+                _ => {
+                    self.commands.push(WAIT_N);
+                    self.commands.extend_from_slice(&samples.to_le_bytes());
+                }
+            }
+
+            wait -= samples as u64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod vgm_tests {
+    use super::*;
+
+    #[test]
+    fn waits_between_writes() {
+        let mut log = VgmLog::new();
+
+        log.write(0x16, 0x80);
+        // One second
+        for _ in 0..CPU_CLOCK_SPEED / 4 {
+            log.tick(1);
+        }
+        log.write(0x00, 0x12);
+
+        assert_eq!(
+            log.commands,
+            [DMG_WRITE, 0x16, 0x80, WAIT_N, 0x44, 0xAC, DMG_WRITE, 0x00, 0x12]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * @file    recorder/wav.rs
+ * @brief   Writes 16-bit PCM stereo WAV files.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+const CHANNELS: u16 = 2;
+const BITS_PER_SAMPLE: u16 = 16;
+const BLOCK_ALIGN: u16 = CHANNELS * BITS_PER_SAMPLE / 8;
+const HEADER_SIZE: u32 = 44;
+// Offsets of the RIFF and data chunk sizes, which are patched when finished
+const RIFF_SIZE_OFFSET: u64 = 4;
+const DATA_SIZE_OFFSET: u64 = 40;
+
+pub struct WavWriter {
+    writer: BufWriter<File>,
+    data_size: u32,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, sample_rate: u32) -> Result<Self, Box<dyn Error>> {
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(b"WAVE");
+
+        header.extend_from_slice(b"fmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        // WAVE_FORMAT_PCM
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&CHANNELS.to_le_bytes());
+        header.extend_from_slice(&sample_rate.to_le_bytes());
+        header.extend_from_slice(&(sample_rate * BLOCK_ALIGN as u32).to_le_bytes());
+        header.extend_from_slice(&BLOCK_ALIGN.to_le_bytes());
+        header.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&header)?;
+
+        Ok(Self {
+            writer,
+            data_size: 0,
+        })
+    }
+
+    // Interleaved left and right samples
+    pub fn write(&mut self, samples: &[i16]) -> Result<(), Box<dyn Error>> {
+        let size = (samples.len() * 2) as u32;
+        if self.data_size.checked_add(size + HEADER_SIZE).is_none() {
+            return Err("WAV files are limited to 4 GiB".into());
+        }
+
+        let data = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        self.writer.write_all(&data)?;
+        self.data_size += size;
+
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.writer.seek(SeekFrom::Start(RIFF_SIZE_OFFSET))?;
+        self.writer
+            .write_all(&(self.data_size + HEADER_SIZE - 8).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(DATA_SIZE_OFFSET))?;
+        self.writer.write_all(&self.data_size.to_le_bytes())?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
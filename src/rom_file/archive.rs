@@ -0,0 +1,202 @@
+/*
+ * @file    rom_file/archive.rs
+ * @brief   Unpacks ROMs from ZIP and gzip archives.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{error::Error, io::Read};
+
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+
+use crate::rom_file::ROM_EXTENSIONS;
+
+// https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4B50;
+const CENTRAL_DIRECTORY_ENTRY: u32 = 0x0201_4B50;
+const LOCAL_FILE_HEADER: u32 = 0x0403_4B50;
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+const CENTRAL_DIRECTORY_ENTRY_SIZE: usize = 46;
+const LOCAL_FILE_HEADER_SIZE: usize = 30;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut rom_data = Vec::new();
+    MultiGzDecoder::new(data).read_to_end(&mut rom_data)?;
+
+    Ok(rom_data)
+}
+
+/*
+ * Walks the central directory at the end of the archive and unpacks the
+ * first entry with a ROM extension. Only the stored and deflate methods
+ * are supported, which is what ROM sets use.
+ */
+pub fn unzip_rom(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let end = find_end_of_central_directory(data).ok_or("Not a ZIP archive")?;
+    let entries = read_u16(data, end + 10)? as usize;
+    let mut offset = read_u32(data, end + 16)? as usize;
+
+    for _ in 0..entries {
+        if read_u32(data, offset)? != CENTRAL_DIRECTORY_ENTRY {
+            return Err("Corrupt ZIP central directory".into());
+        }
+
+        let method = read_u16(data, offset + 10)?;
+        let crc = read_u32(data, offset + 16)?;
+        let compressed_size = read_u32(data, offset + 20)? as usize;
+        let name_length = read_u16(data, offset + 28)? as usize;
+        let extra_length = read_u16(data, offset + 30)? as usize;
+        let comment_length = read_u16(data, offset + 32)? as usize;
+        let header_offset = read_u32(data, offset + 42)? as usize;
+
+        let name_start = offset + CENTRAL_DIRECTORY_ENTRY_SIZE;
+        let name = data
+            .get(name_start..name_start + name_length)
+            .map(String::from_utf8_lossy)
+            .ok_or("Corrupt ZIP central directory")?
+            .to_lowercase();
+        offset = name_start + name_length + extra_length + comment_length;
+
+        let is_rom = ROM_EXTENSIONS
+            .iter()
+            .any(|extension| name.ends_with(&format!(".{}", extension)));
+        if !is_rom {
+            continue;
+        }
+
+        // Name and extra field of the local header may differ from the central directory
+        if read_u32(data, header_offset)? != LOCAL_FILE_HEADER {
+            return Err("Corrupt ZIP local file header".into());
+        }
+        let data_start = header_offset
+            + LOCAL_FILE_HEADER_SIZE
+            + read_u16(data, header_offset + 26)? as usize
+            + read_u16(data, header_offset + 28)? as usize;
+        let compressed = data
+            .get(data_start..data_start + compressed_size)
+            .ok_or("ZIP entry is cut off")?;
+
+        let rom_data = match method {
+            METHOD_STORED => compressed.to_vec(),
+            METHOD_DEFLATED => {
+                let mut rom_data = Vec::new();
+                DeflateDecoder::new(compressed).read_to_end(&mut rom_data)?;
+                rom_data
+            }
+            _ => return Err(format!("ZIP compression method {} not supported", method).into()),
+        };
+
+        if crc32fast::hash(&rom_data) != crc {
+            return Err(format!("CRC mismatch in {}", name).into());
+        }
+
+        return Ok(rom_data);
+    }
+
+    Err("No .gb or .gbc file in the ZIP archive".into())
+}
+
+// The record is followed by a comment of up to 64 KiB, so it is searched from the back
+fn find_end_of_central_directory(data: &[u8]) -> Option<usize> {
+    let last = data.len().checked_sub(END_OF_CENTRAL_DIRECTORY_SIZE)?;
+    let first = last.saturating_sub(u16::MAX as usize);
+
+    (first..=last)
+        .rev()
+        .find(|&offset| read_u32(data, offset).ok() == Some(END_OF_CENTRAL_DIRECTORY))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Box<dyn Error>> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .ok_or("ZIP archive is cut off")?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Box<dyn Error>> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .ok_or("ZIP archive is cut off")?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod archive_tests {
+    use super::*;
+    use flate2::{
+        write::{DeflateEncoder, GzEncoder},
+        Compression,
+    };
+    use std::io::Write;
+
+    // A minimal archive with every entry deflated
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut central_directory = Vec::new();
+
+        for (name, content) in entries {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(content).unwrap();
+            let compressed = encoder.finish().unwrap();
+            let crc = crc32fast::hash(content);
+
+            let mut fields = Vec::new();
+            fields.extend_from_slice(&METHOD_DEFLATED.to_le_bytes());
+            fields.extend_from_slice(&[0; 4]);
+            fields.extend_from_slice(&crc.to_le_bytes());
+            fields.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            fields.extend_from_slice(&[0; 2]);
+
+            central_directory.extend_from_slice(&CENTRAL_DIRECTORY_ENTRY.to_le_bytes());
+            central_directory.extend_from_slice(&[0; 6]);
+            central_directory.extend_from_slice(&fields);
+            central_directory.extend_from_slice(&[0; 10]);
+            central_directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            central_directory.extend_from_slice(name.as_bytes());
+
+            data.extend_from_slice(&LOCAL_FILE_HEADER.to_le_bytes());
+            data.extend_from_slice(&[0; 4]);
+            data.extend_from_slice(&fields);
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(&compressed);
+        }
+
+        let central_directory_offset = data.len() as u32;
+        data.extend_from_slice(&central_directory);
+        data.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+        data.extend_from_slice(&central_directory_offset.to_le_bytes());
+        data.extend_from_slice(&[0; 2]);
+        data
+    }
+
+    #[test]
+    fn picks_first_rom_in_zip() {
+        let archive = zip(&[
+            ("readme.txt", b"not a rom"),
+            ("Game/Game.GBC", &[0x42; 1000]),
+            ("other.gb", &[0x24; 1000]),
+        ]);
+
+        assert_eq!(unzip_rom(&archive).unwrap(), vec![0x42; 1000]);
+        assert!(unzip_rom(&zip(&[("readme.txt", b"no rom")])).is_err());
+    }
+
+    #[test]
+    fn unpacks_gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[0x42; 1000]).unwrap();
+
+        assert_eq!(
+            gunzip(&encoder.finish().unwrap()).unwrap(),
+            vec![0x42; 1000]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+/*
+ * @file    rom_file/mod.rs
+ * @brief   Reads ROMs from disk, unpacking archives and applying patches.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+mod archive;
+mod patch;
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use crate::cartridge::header::global_checksum;
+
+const ARCHIVE_EXTENSIONS: [&str; 2] = ["zip", "gz"];
+const ROM_EXTENSIONS: [&str; 2] = ["gb", "gbc"];
+// Looked for next to the ROM in this order, the first one found is applied
+const PATCH_EXTENSIONS: [&str; 3] = ["ips", "ups", "bps"];
+
+pub struct Rom {
+    pub data: Vec<u8>,
+    // Of the ROM before patching, so updating a patch keeps the save
+    pub checksum: u16,
+}
+
+pub fn read(file_path: &str) -> Result<Rom, Box<dyn Error>> {
+    let mut rom_data = unpack(Path::new(file_path))?;
+    let checksum = global_checksum(&rom_data);
+
+    let base_path = base_path(file_path);
+    let patch_path = PATCH_EXTENSIONS
+        .iter()
+        .map(|extension| add_extension(&base_path, extension))
+        .find(|path| path.exists());
+
+    if let Some(patch_path) = patch_path {
+        let patch_data = std::fs::read(&patch_path)?;
+        rom_data = patch::apply(&rom_data, &patch_data)
+            .map_err(|e| format!("Error applying {}: {e}", patch_path.display()))?;
+        println!("Patch {} applied.", patch_path.display());
+    }
+
+    Ok(Rom {
+        data: rom_data,
+        checksum,
+    })
+}
+
+// The ROM as it is stored, without patches
+pub fn unpack(file_path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    let data = std::fs::read(file_path)?;
+
+    match extension(file_path).as_deref() {
+        Some("zip") => archive::unzip_rom(&data),
+        Some("gz") => archive::gunzip(&data),
+        _ => Ok(data),
+    }
+}
+
+// ROMs and archives that may hold one, gzip only compresses a single file so its name tells
+pub fn is_rom_file(path: &Path) -> bool {
+    let is_rom = |path: &Path| {
+        extension(path).is_some_and(|extension| ROM_EXTENSIONS.contains(&extension.as_str()))
+    };
+
+    match extension(path).as_deref() {
+        Some("zip") => true,
+        Some("gz") => is_rom(&path.with_extension("")),
+        _ => is_rom(path),
+    }
+}
+
+// The ROM path without archive and ROM extensions, which saves and patches are named after
+pub fn base_path(file_path: &str) -> PathBuf {
+    let mut path = PathBuf::from(file_path);
+
+    for extensions in [&ARCHIVE_EXTENSIONS[..], &ROM_EXTENSIONS[..]] {
+        if extension(&path).is_some_and(|extension| extensions.contains(&extension.as_str())) {
+            path.set_extension("");
+        }
+    }
+
+    path
+}
+
+// Unlike with_extension, keeps dots in names like "v1.1 hack"
+pub fn add_extension(base_path: &Path, extension: &str) -> PathBuf {
+    let mut path = base_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+
+    PathBuf::from(path)
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+#[cfg(test)]
+mod rom_file_tests {
+    use super::*;
+
+    #[test]
+    fn strips_archive_and_rom_extensions() {
+        assert_eq!(base_path("roms/tetris.gb"), Path::new("roms/tetris"));
+        assert_eq!(base_path("roms/tetris.GBC"), Path::new("roms/tetris"));
+        assert_eq!(base_path("roms/tetris.gb.gz"), Path::new("roms/tetris"));
+        assert_eq!(base_path("roms/tetris.zip"), Path::new("roms/tetris"));
+        assert_eq!(
+            add_extension(&base_path("roms/v1.1 hack.zip"), "sav"),
+            Path::new("roms/v1.1 hack.sav")
+        );
+    }
+
+    #[test]
+    fn recognizes_rom_files() {
+        assert!(is_rom_file(Path::new("roms/tetris.gb")));
+        assert!(is_rom_file(Path::new("roms/tetris.GBC")));
+        assert!(is_rom_file(Path::new("roms/tetris.zip")));
+        assert!(is_rom_file(Path::new("roms/tetris.gb.gz")));
+        assert!(!is_rom_file(Path::new("roms/readme.txt.gz")));
+        assert!(!is_rom_file(Path::new("roms/tetris.gz")));
+        assert!(!is_rom_file(Path::new("roms/tetris.sav")));
+    }
+}
@@ -0,0 +1,411 @@
+/*
+ * @file    rom_file/patch.rs
+ * @brief   Applies IPS, UPS and BPS patches to ROMs.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::error::Error;
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_END: &[u8] = b"EOF";
+const UPS_MAGIC: &[u8] = b"UPS1";
+const BPS_MAGIC: &[u8] = b"BPS1";
+
+// UPS and BPS end with the CRC32 of the source, the target and the patch itself
+const CHECKSUMS_SIZE: usize = 12;
+// Largest cartridge ROM, anything bigger is rejected before allocating it
+const MAX_TARGET_SIZE: usize = 8 * 1024 * 1024;
+
+const OVERFLOW_ERROR: &str = "Patch holds a number that is too large";
+
+pub fn apply(rom_data: &[u8], patch: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(rom_data, patch)
+    } else if patch.starts_with(UPS_MAGIC) {
+        apply_ups(rom_data, patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(rom_data, patch)
+    } else {
+        Err("Unknown patch format".into())
+    }
+}
+
+/*
+ * Records of a 24-bit offset and a 16-bit length followed by the data,
+ * or by a 16-bit count and a byte to repeat when the length is zero.
+ * IPS has no checksums, and may truncate the ROM after its end marker.
+ */
+fn apply_ips(rom_data: &[u8], patch: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut target = rom_data.to_vec();
+    let mut reader = Reader::new(patch, IPS_MAGIC.len());
+
+    loop {
+        if patch.get(reader.position..reader.position + 3) == Some(IPS_END) {
+            reader.position += IPS_END.len();
+            break;
+        }
+
+        let offset = reader.read_be(3)?;
+        let length = reader.read_be(2)?;
+
+        let data = if length == 0 {
+            let count = reader.read_be(2)?;
+            vec![reader.read_byte()?; count]
+        } else {
+            reader.read_bytes(length)?.to_vec()
+        };
+
+        if target.len() < offset + data.len() {
+            target.resize(offset + data.len(), 0);
+        }
+        target[offset..offset + data.len()].copy_from_slice(&data);
+    }
+
+    if let Ok(size) = reader.read_be(3) {
+        target.truncate(size);
+    }
+
+    Ok(target)
+}
+
+// Runs of bytes XORed onto the source, each after a number of unchanged bytes
+fn apply_ups(rom_data: &[u8], patch: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (source_crc, target_crc) = verify_patch(patch)?;
+    if crc32fast::hash(rom_data) != source_crc {
+        return Err("ROM doesn't match the source checksum of the patch".into());
+    }
+
+    let mut reader = Reader::new(&patch[..patch.len() - CHECKSUMS_SIZE], UPS_MAGIC.len());
+    let _source_size = reader.read_number()?;
+    let target_size = read_target_size(&mut reader)?;
+
+    let mut target = rom_data.to_vec();
+    target.resize(target_size, 0);
+
+    let mut offset: usize = 0;
+    while !reader.is_done() {
+        offset = offset
+            .checked_add(reader.read_number()?)
+            .ok_or(OVERFLOW_ERROR)?;
+
+        loop {
+            let value = reader.read_byte()?;
+            if let Some(byte) = target.get_mut(offset) {
+                *byte ^= value;
+            }
+            offset = offset.checked_add(1).ok_or(OVERFLOW_ERROR)?;
+
+            if value == 0 {
+                break;
+            }
+        }
+    }
+
+    verify_target(target, target_crc)
+}
+
+/*
+ * Builds the target from commands that copy from the source at the same
+ * offset, take bytes from the patch, or copy from anywhere in the source
+ * or in the target written so far.
+ */
+fn apply_bps(rom_data: &[u8], patch: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (source_crc, target_crc) = verify_patch(patch)?;
+    if crc32fast::hash(rom_data) != source_crc {
+        return Err("ROM doesn't match the source checksum of the patch".into());
+    }
+
+    let mut reader = Reader::new(&patch[..patch.len() - CHECKSUMS_SIZE], BPS_MAGIC.len());
+    let _source_size = reader.read_number()?;
+    let target_size = read_target_size(&mut reader)?;
+    let metadata_size = reader.read_number()?;
+    reader.read_bytes(metadata_size)?;
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_offset = 0;
+    let mut target_offset = 0;
+
+    while !reader.is_done() {
+        let command = reader.read_number()?;
+        let length = (command >> 2) + 1;
+
+        // Also keeps target copies from growing the target without bounds
+        if length > target_size - target.len() {
+            return Err("BPS patch writes past the end of the target".into());
+        }
+
+        match command & 0b11 {
+            // Source read
+            0 => {
+                let start = target.len();
+                let bytes = rom_data
+                    .get(start..start + length)
+                    .ok_or("BPS patch reads past the end of the ROM")?;
+                target.extend_from_slice(bytes);
+            }
+            // Target read
+            1 => target.extend_from_slice(reader.read_bytes(length)?),
+            // Source copy
+            2 => {
+                source_offset = reader.read_offset(source_offset)?;
+                let end = source_offset.checked_add(length).ok_or(OVERFLOW_ERROR)?;
+                let bytes = rom_data
+                    .get(source_offset..end)
+                    .ok_or("BPS patch copies past the end of the ROM")?;
+                target.extend_from_slice(bytes);
+                source_offset += length;
+            }
+            // Target copy, byte by byte as the copy may overlap what it writes
+            _ => {
+                target_offset = reader.read_offset(target_offset)?;
+                for _ in 0..length {
+                    let byte = *target
+                        .get(target_offset)
+                        .ok_or("BPS patch copies past the end of the target")?;
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+    }
+
+    if target.len() != target_size {
+        return Err("Patched ROM has the wrong size".into());
+    }
+
+    verify_target(target, target_crc)
+}
+
+fn read_target_size(reader: &mut Reader) -> Result<usize, Box<dyn Error>> {
+    let target_size = reader.read_number()?;
+    if target_size > MAX_TARGET_SIZE {
+        return Err("Patched ROM would be larger than any cartridge".into());
+    }
+
+    Ok(target_size)
+}
+
+// Returns the source and target checksums once the patch itself checks out
+fn verify_patch(patch: &[u8]) -> Result<(u32, u32), Box<dyn Error>> {
+    let checksums_start = patch
+        .len()
+        .checked_sub(CHECKSUMS_SIZE)
+        .ok_or("Patch is cut off")?;
+    let checksum = |index: usize| {
+        let start = checksums_start + index * 4;
+        u32::from_le_bytes(patch[start..start + 4].try_into().unwrap())
+    };
+
+    if crc32fast::hash(&patch[..patch.len() - 4]) != checksum(2) {
+        return Err("Patch is corrupt".into());
+    }
+
+    Ok((checksum(0), checksum(1)))
+}
+
+fn verify_target(target: Vec<u8>, target_crc: u32) -> Result<Vec<u8>, Box<dyn Error>> {
+    if crc32fast::hash(&target) != target_crc {
+        return Err("Patched ROM doesn't match the target checksum".into());
+    }
+
+    Ok(target)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], position: usize) -> Self {
+        Self { data, position }
+    }
+
+    fn is_done(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn read_byte(&mut self) -> Result<u8, Box<dyn Error>> {
+        let byte = *self.data.get(self.position).ok_or("Patch is cut off")?;
+        self.position += 1;
+
+        Ok(byte)
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let end = self
+            .position
+            .checked_add(length)
+            .ok_or("Patch is cut off")?;
+        let bytes = self
+            .data
+            .get(self.position..end)
+            .ok_or("Patch is cut off")?;
+        self.position += length;
+
+        Ok(bytes)
+    }
+
+    // Big-endian number as used by IPS
+    fn read_be(&mut self, length: usize) -> Result<usize, Box<dyn Error>> {
+        Ok(self
+            .read_bytes(length)?
+            .iter()
+            .fold(0, |value, &byte| (value << 8) | byte as usize))
+    }
+
+    // Variable-length number of UPS and BPS, 7 bits per byte until the high bit is set
+    fn read_number(&mut self) -> Result<usize, Box<dyn Error>> {
+        let mut value: usize = 0;
+        let mut shift: usize = 1;
+
+        loop {
+            let byte = self.read_byte()?;
+            value = ((byte & 0x7F) as usize)
+                .checked_mul(shift)
+                .and_then(|bits| value.checked_add(bits))
+                .ok_or(OVERFLOW_ERROR)?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+
+            shift = shift.checked_mul(0x80).ok_or(OVERFLOW_ERROR)?;
+            value = value.checked_add(shift).ok_or(OVERFLOW_ERROR)?;
+        }
+    }
+
+    // BPS copy offsets are relative, with the sign in the lowest bit
+    fn read_offset(&mut self, offset: usize) -> Result<usize, Box<dyn Error>> {
+        let number = self.read_number()?;
+        let distance = number >> 1;
+
+        let offset = if number & 0b1 == 0 {
+            offset.checked_add(distance)
+        } else {
+            offset.checked_sub(distance)
+        };
+
+        offset.ok_or_else(|| "BPS patch copies from before the start".into())
+    }
+}
+
+#[cfg(test)]
+mod patch_tests {
+    use super::*;
+
+    fn number(mut value: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte | 0x80);
+                return bytes;
+            }
+
+            bytes.push(byte);
+            value -= 1;
+        }
+    }
+
+    fn with_checksums(mut patch: Vec<u8>, source: &[u8], target: &[u8]) -> Vec<u8> {
+        patch.extend_from_slice(&crc32fast::hash(source).to_le_bytes());
+        patch.extend_from_slice(&crc32fast::hash(target).to_le_bytes());
+        patch.extend_from_slice(&crc32fast::hash(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn applies_ips() {
+        let mut patch = IPS_MAGIC.to_vec();
+        patch.extend_from_slice(&[0x00, 0x00, 0x02, 0x00, 0x02, 0xAA, 0xBB]);
+        // Run of four 0xCC past the end of the ROM
+        patch.extend_from_slice(&[0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x04, 0xCC]);
+        patch.extend_from_slice(IPS_END);
+
+        let target = apply(&[0; 4], &patch).unwrap();
+        assert_eq!(target, [0, 0, 0xAA, 0xBB, 0, 0, 0xCC, 0xCC, 0xCC, 0xCC]);
+    }
+
+    #[test]
+    fn applies_ups() {
+        let source = [1, 2, 3, 4];
+        let target = [1, 7, 3, 4, 5];
+
+        let mut patch = UPS_MAGIC.to_vec();
+        patch.extend(number(source.len()));
+        patch.extend(number(target.len()));
+        patch.extend(number(1));
+        patch.extend_from_slice(&[2 ^ 7, 0x00]);
+        patch.extend(number(1));
+        patch.extend_from_slice(&[5, 0x00]);
+        let patch = with_checksums(patch, &source, &target);
+
+        assert_eq!(apply(&source, &patch).unwrap(), target);
+        assert!(apply(&[1, 2, 3, 5], &patch).is_err());
+    }
+
+    #[test]
+    fn applies_bps() {
+        let source = [1, 2, 3, 4];
+        let target = [1, 2, 9, 9, 9, 9, 3, 4];
+
+        let mut patch = BPS_MAGIC.to_vec();
+        patch.extend(number(source.len()));
+        patch.extend(number(target.len()));
+        patch.extend(number(0));
+        // Source read of 2, target read of 1, target copy of 3, source copy of 2
+        patch.extend(number(1 << 2));
+        patch.extend(number(1));
+        patch.push(9);
+        patch.extend(number((2 << 2) | 3));
+        patch.extend(number(2 << 1));
+        patch.extend(number((1 << 2) | 2));
+        patch.extend(number(2 << 1));
+        let patch = with_checksums(patch, &source, &target);
+
+        assert_eq!(apply(&source, &patch).unwrap(), target);
+    }
+
+    #[test]
+    fn rejects_oversized_numbers() {
+        let source = [1, 2, 3, 4];
+
+        // Eleven continuation bytes no longer fit into 64 bits
+        let mut patch = UPS_MAGIC.to_vec();
+        patch.extend(number(source.len()));
+        patch.extend_from_slice(&[0x7F; 11]);
+        patch.push(0x80);
+        let patch = with_checksums(patch, &source, &source);
+        let error = apply(&source, &patch).unwrap_err();
+        assert_eq!(error.to_string(), OVERFLOW_ERROR);
+
+        let mut patch = BPS_MAGIC.to_vec();
+        patch.extend(number(source.len()));
+        patch.extend(number(MAX_TARGET_SIZE + 1));
+        patch.extend(number(0));
+        let patch = with_checksums(patch, &source, &source);
+        let error = apply(&source, &patch).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Patched ROM would be larger than any cartridge"
+        );
+
+        // A target copy longer than the target
+        let mut patch = BPS_MAGIC.to_vec();
+        patch.extend(number(source.len()));
+        patch.extend(number(source.len()));
+        patch.extend(number(0));
+        patch.extend(number(0));
+        patch.extend(number((usize::MAX >> 3 << 2) | 3));
+        patch.extend(number(0));
+        let patch = with_checksums(patch, &source, &source);
+        let error = apply(&source, &patch).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "BPS patch writes past the end of the target"
+        );
+    }
+}
@@ -5,19 +5,37 @@ use std::{
 
 use ringbuf::{storage::Heap, traits::Observer, SharedRb};
 
-use crate::{apu::audio::SAMPLING_RATE, FRAME_DURATION, FRAME_DURATION_MICROS};
-
-const THRESHOLD_MIN: usize = SAMPLING_RATE as usize * 4;
-const THRESHOLD_MAX: usize = SAMPLING_RATE as usize * 8;
-
+use crate::{apu::time_stretch::BLOCK_LENGTH, FRAME_DURATION_MS};
+
+// Share of the ring buffer the rate control steers towards, three device buffers
+const TARGET_FILL: f32 = 3.0 / 8.0;
+// Stretched audio arrives one stereo block at a time
+const STRETCH_MARGIN: usize = BLOCK_LENGTH * 2;
+// Below this fill, frames run without waiting until the buffer has recovered
+const CATCH_UP_FILL: f32 = 0.5;
+// Largest change of the resampling ratio, far below what is audible
+const MAX_ADJUSTMENT: f64 = 0.005;
+
+/*
+ * Video is paced to the Game Boy's 59.7275 Hz by sleeping or spinning,
+ * audio follows with dynamic rate control: the resampling ratio is nudged
+ * up while the ring buffer is below its target and down while it's above,
+ * so the two clocks never drift apart far enough to under- or overrun.
+ * https://docs.libretro.com/development/cores/dynamic-rate-control/
+ */
 pub struct SyncBridge {
     last_difference_duration: Duration,
+    // Ring buffer fill relative to the target, 1.0 is right on it
+    pub buffer_fill: f32,
+    pub rate_adjustment: f64,
 }
 
 impl SyncBridge {
     pub fn new() -> Self {
         Self {
             last_difference_duration: Duration::from_micros(0),
+            buffer_fill: 0.0,
+            rate_adjustment: 1.0,
         }
     }
 
@@ -27,23 +45,26 @@ impl SyncBridge {
         fast_forward: &u8,
         performance_mode: bool,
         apu_enabled: bool,
-        ring_buffer_ref: Arc<SharedRb<Heap<u8>>>,
+        audio_stretched: bool,
+        ring_buffer_ref: Arc<SharedRb<Heap<f32>>>,
     ) {
+        let margin = if audio_stretched { STRETCH_MARGIN } else { 0 };
+        let target = ring_buffer_ref.capacity().get() as f32 * TARGET_FILL + margin as f32;
+
         if apu_enabled {
-            if ring_buffer_ref.occupied_len() > THRESHOLD_MIN {
-                if performance_mode {
-                    self.sleep(frame_start_time, fast_forward);
-
-                    balance_buffer(&ring_buffer_ref, || {
-                        std::thread::sleep(std::time::Duration::from_millis(1))
-                    });
-                } else {
-                    spin(frame_start_time, fast_forward);
-
-                    balance_buffer(&ring_buffer_ref, || std::hint::spin_loop());
-                }
-            }
-        } else if performance_mode {
+            self.buffer_fill = ring_buffer_ref.occupied_len() as f32 / target;
+            self.rate_adjustment = rate_adjustment(self.buffer_fill);
+        } else {
+            self.buffer_fill = 0.0;
+            self.rate_adjustment = 1.0;
+        }
+
+        // Rate control is too gentle to refill the buffer after startup or a stall
+        if apu_enabled && self.buffer_fill < CATCH_UP_FILL {
+            return;
+        }
+
+        if performance_mode {
             self.sleep(frame_start_time, fast_forward);
         } else {
             spin(frame_start_time, fast_forward);
@@ -52,7 +73,7 @@ impl SyncBridge {
 
     fn sleep(&mut self, frame_start_time: &Instant, fast_forward: &u8) {
         let elapsed = frame_start_time.elapsed();
-        let frame_duration = Duration::from_micros(FRAME_DURATION_MICROS / *fast_forward as u64);
+        let frame_duration = frame_duration(fast_forward);
 
         let base_target_duration = if elapsed < frame_duration {
             frame_duration - elapsed
@@ -87,20 +108,33 @@ impl SyncBridge {
 }
 
 fn spin(frame_start_time: &Instant, fast_forward: &u8) {
-    while frame_start_time.elapsed().as_micros()
-        < FRAME_DURATION.as_micros() / *fast_forward as u128
-    {
+    let frame_duration = frame_duration(fast_forward);
+
+    while frame_start_time.elapsed() < frame_duration {
         std::hint::spin_loop();
     }
 }
 
-fn balance_buffer<F>(ring_buffer_ref: &Arc<SharedRb<Heap<u8>>>, wait_action: F)
-where
-    F: Fn(),
-{
-    if ring_buffer_ref.occupied_len() > THRESHOLD_MAX {
-        while ring_buffer_ref.occupied_len() > THRESHOLD_MIN {
-            wait_action();
-        }
+// Nanosecond precision keeps the frame rate at 59.7275 Hz instead of rounding it
+fn frame_duration(fast_forward: &u8) -> Duration {
+    Duration::from_secs_f64(FRAME_DURATION_MS / 1_000.0 / *fast_forward as f64)
+}
+
+fn rate_adjustment(buffer_fill: f32) -> f64 {
+    let deviation = (1.0 - buffer_fill as f64).clamp(-1.0, 1.0);
+
+    1.0 + deviation * MAX_ADJUSTMENT
+}
+
+#[cfg(test)]
+mod sync_bridge_tests {
+    use super::*;
+
+    #[test]
+    fn adjustment_follows_fill() {
+        assert_eq!(rate_adjustment(1.0), 1.0);
+        assert_eq!(rate_adjustment(0.0), 1.0 + MAX_ADJUSTMENT);
+        assert_eq!(rate_adjustment(4.0), 1.0 - MAX_ADJUSTMENT);
+        assert!(rate_adjustment(1.5) < 1.0);
     }
 }
@@ -0,0 +1,212 @@
+/*
+ * @file    test_roms.rs
+ * @brief   Runs test ROM suites headless and checks how they report results.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+/*
+ * The ROMs are not part of the repository. They are looked up in the
+ * directory set by GAEMBOI_TEST_ROMS, or in test_roms/ next to Cargo.toml,
+ * using the layout of the released suites. The tests are ignored by
+ * default and run with `cargo test --release -- --ignored`.
+ */
+
+use std::{
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    rc::Rc,
+};
+
+use ringbuf::{traits::Split, HeapRb};
+
+use crate::{cpu::Cpu, ppu::colors::Colors, ComponentTick, MemoryAccess};
+
+// Emulated seconds before a ROM counts as hung
+const TIMEOUT_SECONDS: u64 = 60;
+const M_CYCLES_PER_SECOND: u64 = 1_048_576;
+
+const DMG_SOUND: [&str; 12] = [
+    "01-registers",
+    "02-len ctr",
+    "03-trigger",
+    "04-sweep",
+    "05-sweep details",
+    "06-overflow on trigger",
+    "07-len sweep period sync",
+    "08-len ctr during power",
+    "09-wave read while on",
+    "10-wave trigger while on",
+    "11-regs after power",
+    "12-wave write while on",
+];
+
+const MOONEYE_MBC1: [&str; 13] = [
+    "bits_bank1",
+    "bits_bank2",
+    "bits_mode",
+    "bits_ramg",
+    "multicart_rom_8Mb",
+    "ram_256kb",
+    "ram_64kb",
+    "rom_16Mb",
+    "rom_1Mb",
+    "rom_2Mb",
+    "rom_4Mb",
+    "rom_512kb",
+    "rom_8Mb",
+];
+
+// Blargg's ROMs write their state to cartridge RAM behind this signature
+const BLARGG_SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+const BLARGG_RUNNING: u8 = 0x80;
+
+// Mooneye's ROMs stop at LD B,B with B, C, D, E, H and L set to these on success
+const MOONEYE_BREAKPOINT: u8 = 0x40;
+const MOONEYE_SIGNATURE: [u8; 6] = [3, 5, 8, 13, 21, 34];
+
+fn rom_directory() -> PathBuf {
+    std::env::var_os("GAEMBOI_TEST_ROMS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_roms"))
+}
+
+fn build_cpu(path: &PathBuf) -> Cpu {
+    let rom_data =
+        std::fs::read(path).unwrap_or_else(|e| panic!("Error reading {}: {e}", path.display()));
+    let (prod, _cons) = HeapRb::<f32>::new(16).split();
+
+    Cpu::new(
+        rom_data,
+        Rc::new(RefCell::new(Colors::new())),
+        Rc::new(RefCell::new(1)),
+        prod,
+    )
+    .unwrap()
+}
+
+// Steps the CPU until finished returns a result, or fails after the timeout
+fn run(path: &PathBuf, mut finished: impl FnMut(&mut Cpu) -> Option<Result<(), String>>) {
+    let mut cpu = build_cpu(path);
+    let mut m_cycles = 0;
+
+    while m_cycles < TIMEOUT_SECONDS * M_CYCLES_PER_SECOND {
+        if let Some(result) = finished(&mut cpu) {
+            return result.unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        }
+
+        let cycles = cpu.step();
+        cpu.memory_bus.tick(cycles);
+        m_cycles += cycles as u64;
+    }
+
+    panic!("{}: timed out", path.display());
+}
+
+/*
+ * Status 0x00 means passed, the text after the signature explains failures.
+ * RAM starts out cleared, so a status only counts once the ROM reported
+ * that it is running.
+ */
+fn blargg() -> impl FnMut(&mut Cpu) -> Option<Result<(), String>> {
+    let mut running = false;
+
+    move |cpu| {
+        let memory_bus = &cpu.memory_bus;
+        let signature = [0xA001, 0xA002, 0xA003].map(|address| memory_bus.read_byte(address));
+        let status = memory_bus.read_byte(0xA000);
+
+        if signature != BLARGG_SIGNATURE {
+            return None;
+        }
+
+        if status == BLARGG_RUNNING {
+            running = true;
+        }
+        if !running || status == BLARGG_RUNNING {
+            return None;
+        }
+
+        if status == 0x00 {
+            return Some(Ok(()));
+        }
+
+        let text = (0xA004..0xB000)
+            .map(|address| memory_bus.read_byte(address))
+            .take_while(|&byte| byte != 0)
+            .map(char::from)
+            .collect::<String>();
+
+        Some(Err(format!("failed with {status:#04X}: {}", text.trim())))
+    }
+}
+
+fn mooneye() -> impl FnMut(&mut Cpu) -> Option<Result<(), String>> {
+    |cpu| {
+        let opcode = cpu.memory_bus.read_byte(cpu.program_counter.get());
+        if opcode != MOONEYE_BREAKPOINT {
+            return None;
+        }
+
+        let registers = &cpu.registers;
+        let values = [
+            registers.get_b(),
+            registers.get_c(),
+            registers.get_d(),
+            registers.get_e(),
+            registers.get_h(),
+            registers.get_l(),
+        ];
+
+        if values == MOONEYE_SIGNATURE {
+            Some(Ok(()))
+        } else {
+            Some(Err(format!("failed with registers {values:02X?}")))
+        }
+    }
+}
+
+// Runs every ROM of a suite, and reports all failures at once
+fn run_suite<F>(paths: Vec<PathBuf>, finished: impl Fn() -> F)
+where
+    F: FnMut(&mut Cpu) -> Option<Result<(), String>>,
+{
+    let failures = paths
+        .iter()
+        .filter_map(|path| {
+            panic::catch_unwind(AssertUnwindSafe(|| run(path, finished())))
+                .err()
+                .map(|_| path.display().to_string())
+        })
+        .collect::<Vec<String>>();
+
+    assert!(failures.is_empty(), "Failed: {}", failures.join(", "));
+}
+
+#[test]
+#[ignore = "needs the dmg_sound ROMs"]
+fn dmg_sound() {
+    let directory = rom_directory().join("dmg_sound").join("rom_singles");
+    let paths = DMG_SOUND
+        .iter()
+        .map(|name| directory.join(format!("{name}.gb")))
+        .collect();
+
+    run_suite(paths, blargg);
+}
+
+#[test]
+#[ignore = "needs the mooneye ROMs"]
+fn mooneye_mbc1() {
+    let directory = rom_directory()
+        .join("mooneye")
+        .join("emulator-only")
+        .join("mbc1");
+    let paths = MOONEYE_MBC1
+        .iter()
+        .map(|name| directory.join(format!("{name}.gb")))
+        .collect();
+
+    run_suite(paths, mooneye);
+}
@@ -9,7 +9,7 @@ use std::f32;
 use std::{cell::RefCell, rc::Rc};
 
 use egui_sdl2_gl::{
-    egui::{load::SizedTexture, Color32, Image, TextureId, Vec2},
+    egui::{load::SizedTexture, Align2, Color32, FontId, Image, Rect, TextureId, Vec2},
     painter::Painter,
 };
 
@@ -24,12 +24,16 @@ use crate::{
         TILEMAP_START_1, TILEMAP_WIDTH, TILETABLE_HEIGHT, TILETABLE_WIDTH, VIEWPORT_HEIGHT,
         VIEWPORT_WIDTH,
     },
+    ui::filters::{FilterPipeline, ScalingMode},
     View,
 };
 
 pub struct CentralPanel {
     pub game_background: Vec<Color32>,
-    pub game_texture_id: TextureId,
+    // One texture per output factor of the filter pipeline, indexed by factor - 1
+    pub game_texture_ids: [TextureId; 3],
+    filter_pipeline: FilterPipeline,
+    colors: Rc<RefCell<Colors>>,
     pub tiletable_texture_id: TextureId,
     pub tilemap_texture_id: TextureId,
     pub splash_frames: Vec<Vec<Color32>>, // Store each frame as Color32 data
@@ -71,32 +75,38 @@ impl CentralPanel {
             Vec::with_capacity(VIEWPORT_WIDTH * VIEWPORT_HEIGHT);
         let borrowed_colors = colors.as_ref().borrow();
         game_background.fill(Color32::from_rgb(
-            borrowed_colors.white.r(),
-            borrowed_colors.white.g(),
-            borrowed_colors.white.b(),
+            borrowed_colors.bg.white.r(),
+            borrowed_colors.bg.white.g(),
+            borrowed_colors.bg.white.b(),
         ));
 
-        let game_texture_id = painter.new_user_texture(
-            (VIEWPORT_WIDTH, VIEWPORT_HEIGHT),
-            &vec![borrowed_colors.black; VIEWPORT_WIDTH * VIEWPORT_HEIGHT],
-            false,
-        );
+        let game_texture_ids = [1, 2, 3].map(|factor| {
+            painter.new_user_texture(
+                (VIEWPORT_WIDTH * factor, VIEWPORT_HEIGHT * factor),
+                &vec![borrowed_colors.bg.black; VIEWPORT_WIDTH * VIEWPORT_HEIGHT * factor * factor],
+                false,
+            )
+        });
 
         let tiletable_texture_id = painter.new_user_texture(
             (TILETABLE_WIDTH, TILETABLE_HEIGHT),
-            &vec![borrowed_colors.black; TILETABLE_WIDTH * TILETABLE_HEIGHT],
+            &vec![borrowed_colors.bg.black; TILETABLE_WIDTH * TILETABLE_HEIGHT],
             false,
         );
 
         let tilemap_texture_id = painter.new_user_texture(
             (TILEMAP_WIDTH, TILEMAP_HEIGHT),
-            &vec![borrowed_colors.black; TILEMAP_WIDTH * TILEMAP_HEIGHT],
+            &vec![borrowed_colors.bg.black; TILEMAP_WIDTH * TILEMAP_HEIGHT],
             false,
         );
 
+        drop(borrowed_colors);
+
         Self {
             game_background,
-            game_texture_id,
+            game_texture_ids,
+            filter_pipeline: FilterPipeline::new(),
+            colors,
             tiletable_texture_id,
             tilemap_texture_id,
             splash_frames,
@@ -116,32 +126,50 @@ impl CentralPanel {
         painter: &mut Painter,
     ) {
         egui_sdl2_gl::egui::CentralPanel::default()
-            .frame(egui_sdl2_gl::egui::Frame::none())
+            .frame(egui_sdl2_gl::egui::Frame::none().fill(Color32::BLACK))
             .show(egui_ctx, |ui| {
                 if let Some(cpu) = cpu {
                     match current_view {
                         View::Viewport => {
-                            self.game_background = cpu
-                                .memory_bus
-                                .ppu
-                                .viewport_buffer
-                                .iter()
-                                .map(|color| Color32::from_rgb(color.r(), color.g(), color.b()))
-                                .collect();
+                            let grid_color = self.colors.borrow().bg.white;
+                            let (factor, output) = self.filter_pipeline.apply(
+                                &cpu.memory_bus.ppu.viewport_buffer,
+                                event_handler.ghosting,
+                                event_handler.scaler,
+                                event_handler.lcd_grid,
+                                grid_color,
+                            );
+                            self.game_background = output;
 
-                            painter.update_user_texture_data(
-                                self.game_texture_id,
-                                &self.game_background,
+                            let texture_id = self.game_texture_ids[factor - 1];
+                            painter.update_user_texture_data(texture_id, &self.game_background);
+
+                            let available = ui.available_rect_before_wrap();
+                            let scale = match event_handler.scaling_mode {
+                                ScalingMode::Integer => viewport_scale(available.size()).floor(),
+                                ScalingMode::Aspect => viewport_scale(available.size()),
+                            }
+                            .max(1.0);
+                            let size = Vec2::new(
+                                VIEWPORT_WIDTH as f32 * scale,
+                                VIEWPORT_HEIGHT as f32 * scale,
                             );
-                            let game_image = Image::new(SizedTexture::new(
-                                self.game_texture_id,
-                                Vec2::new(
-                                    VIEWPORT_WIDTH as f32 * event_handler.window_scale as f32,
-                                    VIEWPORT_HEIGHT as f32 * event_handler.window_scale as f32,
-                                ),
-                            ))
-                            .maintain_aspect_ratio(true);
-                            ui.add(game_image);
+
+                            let game_image = Image::new(SizedTexture::new(texture_id, size));
+                            let game_rect = Rect::from_center_size(available.center(), size);
+                            ui.put(game_rect, game_image);
+
+                            // Stands in for the motor when there is no gamepad to shake
+                            if event_handler.rumble > 0.0 && !event_handler.haptics_available {
+                                let alpha = (96.0 + event_handler.rumble * 159.0) as u8;
+                                ui.painter().text(
+                                    game_rect.right_top() + Vec2::new(-6.0, 6.0),
+                                    Align2::RIGHT_TOP,
+                                    "RUMBLE",
+                                    FontId::proportional(14.0),
+                                    Color32::from_rgba_unmultiplied(255, 220, 0, alpha),
+                                );
+                            }
 
                             cpu.memory_bus.ppu.clear_screen();
                         }
@@ -241,6 +269,11 @@ impl CentralPanel {
     }
 }
 
+// Largest factor the viewport can be scaled by without exceeding the available space
+fn viewport_scale(available: Vec2) -> f32 {
+    (available.x / VIEWPORT_WIDTH as f32).min(available.y / VIEWPORT_HEIGHT as f32)
+}
+
 macro_rules! include_splash_frames {
     ($($i:expr),+) => {
         vec![
@@ -0,0 +1,405 @@
+/*
+ * @file    ui/filters.rs
+ * @brief   CPU-side post-processing between the viewport buffer and the game texture.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use egui_sdl2_gl::egui::Color32;
+
+use crate::ppu::{VIEWPORT_HEIGHT, VIEWPORT_WIDTH};
+
+// Weight of the previous frame when emulating the slow response of the DMG LCD
+const LCD_PERSISTENCE: f32 = 0.55;
+// How far the gaps between LCD dots are lifted towards the unlit screen color
+const GRID_INTENSITY: f32 = 0.5;
+// Scale used for the pixel grid when no upscaler is selected
+const GRID_FACTOR: usize = 3;
+
+// YUV thresholds used by the HQx family to decide whether two colors are alike
+const THRESHOLD_Y: i32 = 48;
+const THRESHOLD_U: i32 = 7;
+const THRESHOLD_V: i32 = 6;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scaler {
+    None,
+    Scale2x,
+    Scale3x,
+    Hq2x,
+}
+
+impl Scaler {
+    pub fn factor(&self) -> usize {
+        match self {
+            Scaler::None => 1,
+            Scaler::Scale2x | Scaler::Hq2x => 2,
+            Scaler::Scale3x => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Ghosting {
+    Off,
+    // Averages the current and the previous frame, turns 30 Hz flicker into transparency
+    Blend,
+    // Exponential decay that smears moving objects like the original screen
+    Lcd,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScalingMode {
+    // Largest whole multiple of 160x144 that fits the window
+    Integer,
+    // Fills the window while keeping the 10:9 aspect ratio
+    Aspect,
+}
+
+pub struct FilterPipeline {
+    previous: Vec<Color32>,
+    accumulated: Vec<[f32; 3]>,
+}
+
+impl FilterPipeline {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            accumulated: Vec::new(),
+        }
+    }
+
+    /*
+     * Runs the selected filters on a 160x144 frame and returns the output
+     * together with the factor it was scaled by. Ghosting works on the
+     * native resolution, the upscaler follows and the pixel grid is drawn
+     * last, on the borders of every upscaled dot.
+     */
+    pub fn apply(
+        &mut self,
+        frame: &[Color32],
+        ghosting: Ghosting,
+        scaler: Scaler,
+        lcd_grid: bool,
+        grid_color: Color32,
+    ) -> (usize, Vec<Color32>) {
+        let frame = self.ghost(frame, ghosting);
+
+        let mut factor = scaler.factor();
+        let mut output = match scaler {
+            Scaler::None => frame,
+            Scaler::Scale2x => scale2x(&frame, VIEWPORT_WIDTH, VIEWPORT_HEIGHT),
+            Scaler::Scale3x => scale3x(&frame, VIEWPORT_WIDTH, VIEWPORT_HEIGHT),
+            Scaler::Hq2x => hq2x(&frame, VIEWPORT_WIDTH, VIEWPORT_HEIGHT),
+        };
+
+        if lcd_grid {
+            if factor == 1 {
+                output = nearest(&output, VIEWPORT_WIDTH, VIEWPORT_HEIGHT, GRID_FACTOR);
+                factor = GRID_FACTOR;
+            }
+
+            draw_grid(&mut output, VIEWPORT_WIDTH * factor, factor, grid_color);
+        }
+
+        (factor, output)
+    }
+
+    fn ghost(&mut self, frame: &[Color32], ghosting: Ghosting) -> Vec<Color32> {
+        match ghosting {
+            Ghosting::Off => {
+                self.previous.clear();
+                self.accumulated.clear();
+                frame.to_vec()
+            }
+            Ghosting::Blend => {
+                self.accumulated.clear();
+
+                let output = if self.previous.len() == frame.len() {
+                    frame
+                        .iter()
+                        .zip(self.previous.iter())
+                        .map(|(current, previous)| average(*current, *previous))
+                        .collect()
+                } else {
+                    frame.to_vec()
+                };
+
+                self.previous = frame.to_vec();
+                output
+            }
+            Ghosting::Lcd => {
+                self.previous.clear();
+
+                if self.accumulated.len() != frame.len() {
+                    self.accumulated = frame
+                        .iter()
+                        .map(|color| [color.r() as f32, color.g() as f32, color.b() as f32])
+                        .collect();
+                }
+
+                frame
+                    .iter()
+                    .zip(self.accumulated.iter_mut())
+                    .map(|(color, accumulated)| {
+                        let current = [color.r(), color.g(), color.b()];
+                        for (channel, value) in accumulated.iter_mut().zip(current) {
+                            *channel =
+                                *channel * LCD_PERSISTENCE + value as f32 * (1.0 - LCD_PERSISTENCE);
+                        }
+
+                        Color32::from_rgb(
+                            accumulated[0].round() as u8,
+                            accumulated[1].round() as u8,
+                            accumulated[2].round() as u8,
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn average(a: Color32, b: Color32) -> Color32 {
+    Color32::from_rgb(
+        ((a.r() as u16 + b.r() as u16) / 2) as u8,
+        ((a.g() as u16 + b.g() as u16) / 2) as u8,
+        ((a.b() as u16 + b.b() as u16) / 2) as u8,
+    )
+}
+
+// Weighted average of up to four colors, the weights have to add up to a power of two
+fn interpolate(colors: &[(Color32, u32)], shift: u32) -> Color32 {
+    let (mut r, mut g, mut b) = (0, 0, 0);
+
+    for (color, weight) in colors {
+        r += color.r() as u32 * weight;
+        g += color.g() as u32 * weight;
+        b += color.b() as u32 * weight;
+    }
+
+    Color32::from_rgb((r >> shift) as u8, (g >> shift) as u8, (b >> shift) as u8)
+}
+
+// Returns the 3x3 neighbourhood of a pixel, with edges clamped
+fn neighbourhood(
+    frame: &[Color32],
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+) -> [Color32; 9] {
+    let mut pixels = [Color32::BLACK; 9];
+
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let nx = (x + i % 3).saturating_sub(1).min(width - 1);
+        let ny = (y + i / 3).saturating_sub(1).min(height - 1);
+        *pixel = frame[ny * width + nx];
+    }
+
+    pixels
+}
+
+fn nearest(frame: &[Color32], width: usize, height: usize, factor: usize) -> Vec<Color32> {
+    let mut output = vec![Color32::BLACK; width * height * factor * factor];
+    let output_width = width * factor;
+
+    for (i, pixel) in output.iter_mut().enumerate() {
+        let (x, y) = ((i % output_width) / factor, (i / output_width) / factor);
+        *pixel = frame[y * width + x];
+    }
+
+    output
+}
+
+// Writes a factor x factor block into the output buffer, row by row
+fn put_block(
+    output: &mut [Color32],
+    width: usize,
+    x: usize,
+    y: usize,
+    factor: usize,
+    block: &[Color32],
+) {
+    let output_width = width * factor;
+
+    for (i, color) in block.iter().enumerate() {
+        let (dx, dy) = (i % factor, i / factor);
+        output[(y * factor + dy) * output_width + x * factor + dx] = *color;
+    }
+}
+
+// https://www.scale2x.it/algorithm
+fn scale2x(frame: &[Color32], width: usize, height: usize) -> Vec<Color32> {
+    let mut output = vec![Color32::BLACK; width * height * 4];
+
+    for y in 0..height {
+        for x in 0..width {
+            let [_, b, _, d, e, f, _, h, _] = neighbourhood(frame, width, height, x, y);
+
+            let block = if b != h && d != f {
+                [
+                    if d == b { d } else { e },
+                    if b == f { f } else { e },
+                    if d == h { d } else { e },
+                    if h == f { f } else { e },
+                ]
+            } else {
+                [e; 4]
+            };
+
+            put_block(&mut output, width, x, y, 2, &block);
+        }
+    }
+
+    output
+}
+
+fn scale3x(frame: &[Color32], width: usize, height: usize) -> Vec<Color32> {
+    let mut output = vec![Color32::BLACK; width * height * 9];
+
+    for y in 0..height {
+        for x in 0..width {
+            let [a, b, c, d, e, f, g, h, i] = neighbourhood(frame, width, height, x, y);
+
+            let block = if b != h && d != f {
+                [
+                    if d == b { d } else { e },
+                    if (d == b && e != c) || (b == f && e != a) {
+                        b
+                    } else {
+                        e
+                    },
+                    if b == f { f } else { e },
+                    if (d == b && e != g) || (d == h && e != a) {
+                        d
+                    } else {
+                        e
+                    },
+                    e,
+                    if (b == f && e != i) || (h == f && e != c) {
+                        f
+                    } else {
+                        e
+                    },
+                    if d == h { d } else { e },
+                    if (d == h && e != i) || (h == f && e != g) {
+                        h
+                    } else {
+                        e
+                    },
+                    if h == f { f } else { e },
+                ]
+            } else {
+                [e; 9]
+            };
+
+            put_block(&mut output, width, x, y, 3, &block);
+        }
+    }
+
+    output
+}
+
+fn to_yuv(color: Color32) -> (i32, i32, i32) {
+    let (r, g, b) = (color.r() as i32, color.g() as i32, color.b() as i32);
+
+    (
+        (r * 299 + g * 587 + b * 114) / 1000,
+        (-r * 169 - g * 331 + b * 500) / 1000 + 128,
+        (r * 500 - g * 419 - b * 81) / 1000 + 128,
+    )
+}
+
+fn similar(a: Color32, b: Color32) -> bool {
+    let (ya, ua, va) = to_yuv(a);
+    let (yb, ub, vb) = to_yuv(b);
+
+    (ya - yb).abs() <= THRESHOLD_Y
+        && (ua - ub).abs() <= THRESHOLD_U
+        && (va - vb).abs() <= THRESHOLD_V
+}
+
+/*
+ * A reduced take on HQ2x: instead of the full 256 pattern lookup table, every
+ * corner of the output block only looks at its diagonal and the two adjacent
+ * edges. Corners on a diagonal edge get blended with both neighbours, corners
+ * that merely touch a different color get a light anti-aliasing blend.
+ */
+fn hq2x(frame: &[Color32], width: usize, height: usize) -> Vec<Color32> {
+    let mut output = vec![Color32::BLACK; width * height * 4];
+
+    for y in 0..height {
+        for x in 0..width {
+            let [a, b, c, d, e, f, g, h, i] = neighbourhood(frame, width, height, x, y);
+
+            // Diagonal and the two edges for each corner: top-left, top-right, bottom-left, bottom-right
+            let corners = [(a, b, d), (c, b, f), (g, h, d), (i, h, f)];
+            let mut block = [e; 4];
+
+            for (pixel, (diagonal, vertical, horizontal)) in block.iter_mut().zip(corners) {
+                *pixel = if similar(vertical, horizontal) && !similar(e, vertical) {
+                    interpolate(&[(e, 2), (vertical, 1), (horizontal, 1)], 2)
+                } else if !similar(e, diagonal) {
+                    interpolate(&[(e, 3), (diagonal, 1)], 2)
+                } else {
+                    e
+                };
+            }
+
+            put_block(&mut output, width, x, y, 2, &block);
+        }
+    }
+
+    output
+}
+
+// Lifts the right and bottom border of every dot towards the unlit screen color
+fn draw_grid(output: &mut [Color32], width: usize, factor: usize, grid_color: Color32) {
+    let weight = (GRID_INTENSITY * 256.0) as u32;
+
+    for (i, pixel) in output.iter_mut().enumerate() {
+        let (x, y) = (i % width, i / width);
+
+        if x % factor == factor - 1 || y % factor == factor - 1 {
+            *pixel = interpolate(&[(*pixel, 256 - weight), (grid_color, weight)], 8);
+        }
+    }
+}
+
+#[cfg(test)]
+mod filters_tests {
+    use super::*;
+
+    const W: Color32 = Color32::WHITE;
+    const K: Color32 = Color32::BLACK;
+
+    #[test]
+    fn scale2x_rounds_diagonal() {
+        let frame = [K, W, W, K];
+        let output = scale2x(&frame, 2, 2);
+
+        // Top-left pixel keeps its outer corner, the corner facing the diagonal turns white
+        assert_eq!(&output[0..2], &[K, K]);
+        assert_eq!(&output[4..6], &[K, W]);
+    }
+
+    #[test]
+    fn scale3x_keeps_flat_areas() {
+        let frame = [W; 4];
+        assert!(scale3x(&frame, 2, 2).iter().all(|pixel| *pixel == W));
+    }
+
+    #[test]
+    fn blend_averages_frames() {
+        let mut pipeline = FilterPipeline::new();
+        let white = vec![W; VIEWPORT_WIDTH * VIEWPORT_HEIGHT];
+        let black = vec![K; VIEWPORT_WIDTH * VIEWPORT_HEIGHT];
+
+        pipeline.apply(&white, Ghosting::Blend, Scaler::None, false, W);
+        let (factor, output) = pipeline.apply(&black, Ghosting::Blend, Scaler::None, false, W);
+
+        assert_eq!(factor, 1);
+        assert_eq!(output[0], Color32::from_rgb(127, 127, 127));
+    }
+}
@@ -0,0 +1,206 @@
+/*
+ * @file    ui/library_window.rs
+ * @brief   Lists the ROMs of the library directories to pick a game from.
+ * @author  Mario Hess
+ * @date    October 18, 2026
+ */
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use egui_sdl2_gl::egui::{
+    load::SizedTexture, ColorImage, Context, Grid, Image, ScrollArea, TextureHandle,
+    TextureOptions, Ui, Vec2,
+};
+use image::imageops::FilterType;
+use rfd::FileDialog;
+
+use crate::{
+    event_handler::EventHandler,
+    ppu::{VIEWPORT_HEIGHT, VIEWPORT_WIDTH},
+};
+
+// Half the size of the viewport
+const THUMBNAIL_SIZE: Vec2 = Vec2::new(VIEWPORT_WIDTH as f32 / 2.0, VIEWPORT_HEIGHT as f32 / 2.0);
+
+pub struct LibraryWindow {
+    // Screenshots that failed to load are kept as None so they aren't read again every frame
+    thumbnails: HashMap<PathBuf, Option<TextureHandle>>,
+}
+
+impl LibraryWindow {
+    pub fn new() -> Self {
+        Self {
+            thumbnails: HashMap::new(),
+        }
+    }
+
+    pub fn draw(&mut self, egui_ctx: &Context, event_handler: &mut EventHandler) {
+        if !event_handler.library_opened {
+            return;
+        }
+
+        if !event_handler.library.scanned && !event_handler.library.scanning() {
+            event_handler.library.scan(&event_handler.paths);
+        }
+        event_handler.library.poll_scan();
+
+        let mut opened = event_handler.library_opened;
+        egui_sdl2_gl::egui::Window::new("ROM Library")
+            .open(&mut opened)
+            .default_width(520.0)
+            .default_height(360.0)
+            .show(egui_ctx, |ui| {
+                self.draw_directories(ui, event_handler);
+                ui.separator();
+                self.draw_entries(ui, egui_ctx, event_handler);
+            });
+        event_handler.library_opened = opened && event_handler.library_opened;
+    }
+
+    fn draw_directories(&mut self, ui: &mut Ui, event_handler: &mut EventHandler) {
+        let mut removed = None;
+
+        for (index, directory) in event_handler.library.directories.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("x").clicked() {
+                    removed = Some(index);
+                }
+                ui.label(directory.display().to_string());
+            });
+        }
+
+        if let Some(index) = removed {
+            if let Err(e) = event_handler.library.remove_directory(index) {
+                eprintln!("Error saving ROM library: {e}.");
+            }
+            self.rescan(event_handler);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Add Directory").clicked() {
+                if let Some(directory) = FileDialog::new().set_directory("../").pick_folder() {
+                    if let Err(e) = event_handler.library.add_directory(directory) {
+                        eprintln!("Error saving ROM library: {e}.");
+                    }
+                    self.rescan(event_handler);
+                }
+            }
+
+            if ui.button("Rescan").clicked() {
+                self.rescan(event_handler);
+            }
+        });
+    }
+
+    fn draw_entries(&mut self, ui: &mut Ui, egui_ctx: &Context, event_handler: &mut EventHandler) {
+        if event_handler.library.scanning() {
+            ui.label("Scanning...");
+        }
+
+        if event_handler.library.entries.is_empty() {
+            if event_handler.library.scanned {
+                ui.label("No ROMs found. Add a directory with .gb, .gbc, .zip or .gz files.");
+            }
+            return;
+        }
+
+        let mut picked = None;
+
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("library_grid")
+                .num_columns(3)
+                .spacing([10.0, 8.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for entry in &event_handler.library.entries {
+                        let thumbnail = entry
+                            .thumbnail
+                            .as_ref()
+                            .and_then(|path| self.thumbnail(egui_ctx, path));
+
+                        if let Some(texture) = thumbnail {
+                            ui.add(Image::new(SizedTexture::new(texture.id(), THUMBNAIL_SIZE)));
+                        } else {
+                            ui.allocate_space(THUMBNAIL_SIZE);
+                        }
+
+                        ui.vertical(|ui| {
+                            let file_name = Path::new(&entry.path)
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or_default();
+
+                            ui.strong(&entry.title);
+                            ui.label(file_name);
+                            ui.label(format!(
+                                "{} - CGB: {}",
+                                entry.cartridge_type, entry.cgb_support
+                            ));
+                            ui.label(format!(
+                                "Last played: {} - Save: {}",
+                                entry.last_played.map_or("Never".to_owned(), time_ago),
+                                if entry.has_save { "Yes" } else { "No" }
+                            ));
+                        });
+
+                        if ui.button("Play").clicked() {
+                            picked = Some(entry.path.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if picked.is_some() {
+            event_handler.file_path = picked;
+            event_handler.library_opened = false;
+        }
+    }
+
+    // Loaded the first time the entry is shown
+    fn thumbnail(&mut self, egui_ctx: &Context, path: &Path) -> Option<&TextureHandle> {
+        self.thumbnails
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                let image = image::open(path)
+                    .ok()?
+                    .resize_exact(
+                        THUMBNAIL_SIZE.x as u32,
+                        THUMBNAIL_SIZE.y as u32,
+                        FilterType::Triangle,
+                    )
+                    .to_rgba8();
+                let image = ColorImage::from_rgba_unmultiplied(
+                    [image.width() as usize, image.height() as usize],
+                    image.as_raw(),
+                );
+
+                Some(egui_ctx.load_texture(path.to_string_lossy(), image, TextureOptions::NEAREST))
+            })
+            .as_ref()
+    }
+
+    fn rescan(&mut self, event_handler: &mut EventHandler) {
+        self.thumbnails.clear();
+        event_handler.library.scan(&event_handler.paths);
+    }
+}
+
+fn time_ago(seconds: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let elapsed = now.saturating_sub(seconds);
+
+    match elapsed {
+        0..=59 => "Just now".to_owned(),
+        60..=3599 => format!("{} min ago", elapsed / 60),
+        3600..=86399 => format!("{} h ago", elapsed / 3600),
+        _ => format!("{} days ago", elapsed / 86400),
+    }
+}
@@ -6,10 +6,13 @@
  */
 
 mod central_panel;
+pub mod filters;
+mod library_window;
 mod top_panel;
 
-use std::{cell::RefCell, rc::Rc};
+use std::{borrow::Cow, cell::RefCell, rc::Rc};
 
+use arboard::{Clipboard, ImageData};
 use central_panel::CentralPanel;
 use egui_sdl2_gl::{
     egui::{Align, Color32, Context, FullOutput, Grid, Hyperlink, Pos2, Rect, Stroke, Ui, Vec2},
@@ -17,14 +20,24 @@ use egui_sdl2_gl::{
     sdl2::video::Window,
     EguiStateHandler,
 };
+use library_window::LibraryWindow;
+use rfd::FileDialog;
 use top_panel::TopPanel;
 
 use crate::{
-    apu::channel::square_channel::{SquareChannel, DUTY_TABLE},
+    apu::{
+        audio::{BUFFER_SIZES, SAMPLE_RATES},
+        channel::square_channel::{SquareChannel, DUTY_TABLE},
+        oscilloscope::{Oscilloscope, Trace},
+    },
+    cartridge::save_format::SaveTransfer,
     cpu::Cpu,
     event_handler::EventHandler,
+    export::{export_vram, save_screenshot, screenshot_image},
     ppu::{
-        colors::Colors, TILEMAP_HEIGHT, TILEMAP_WIDTH, TILETABLE_HEIGHT, TILETABLE_WIDTH,
+        cgb_palettes::KEY_COMBOS,
+        colors::{Colors, PRESETS},
+        palette_file, TILEMAP_HEIGHT, TILEMAP_WIDTH, TILETABLE_HEIGHT, TILETABLE_WIDTH,
         VIEWPORT_HEIGHT, VIEWPORT_WIDTH,
     },
     State, View,
@@ -33,6 +46,9 @@ use crate::{
 pub struct UIManager {
     top_panel: TopPanel,
     central_panel: CentralPanel,
+    library_window: LibraryWindow,
+    // Kept open, on X11 the copied image is only served while it exists
+    clipboard: Option<Clipboard>,
     pub current_view: View,
     pub previous_view: View,
 }
@@ -42,6 +58,8 @@ impl UIManager {
         Self {
             top_panel: TopPanel::new(),
             central_panel: CentralPanel::new(painter, colors),
+            library_window: LibraryWindow::new(),
+            clipboard: None,
             current_view: View::Viewport,
             previous_view: View::Viewport,
         }
@@ -65,6 +83,7 @@ impl UIManager {
         );
         self.central_panel
             .draw(egui_ctx, event_handler, None, &self.current_view, painter);
+        self.library_window.draw(egui_ctx, event_handler);
         self.finish_frame(egui_ctx, window, state, painter);
     }
 
@@ -92,6 +111,37 @@ impl UIManager {
             &mut self.current_view,
             State::Play,
         );
+
+        if let Some((export, directory)) = event_handler.vram_export.take() {
+            match export_vram(&cpu.memory_bus.ppu, export, &directory) {
+                Ok(_) => println!("VRAM exported to {}.", directory.display()),
+                Err(e) => eprintln!("Error exporting VRAM: {e}."),
+            }
+        }
+
+        if let Some((transfer, path)) = event_handler.save_transfer.take() {
+            let cartridge = &mut cpu.memory_bus.cartridge;
+
+            match transfer {
+                SaveTransfer::Import => match cartridge.import_save(&path) {
+                    Ok(_) => println!("Save imported from {}.", path.display()),
+                    Err(e) => eprintln!("Error importing save: {e}."),
+                },
+                SaveTransfer::Export(format) => match cartridge.export_save(&path, format) {
+                    Ok(_) => println!("Save exported to {}.", path.display()),
+                    Err(e) => eprintln!("Error exporting save: {e}."),
+                },
+            }
+        }
+
+        if event_handler.screenshot_requested {
+            if event_handler.screenshot_to_clipboard {
+                self.copy_screenshot(event_handler, cpu);
+            } else {
+                take_screenshot(event_handler, cpu);
+            }
+        }
+
         self.central_panel.draw(
             egui_ctx,
             event_handler,
@@ -100,6 +150,7 @@ impl UIManager {
             painter,
         );
         self.draw_windows(egui_ctx, cpu, event_handler, colors);
+        self.library_window.draw(egui_ctx, event_handler);
         self.finish_frame(egui_ctx, window, state, painter);
     }
 
@@ -135,6 +186,200 @@ impl UIManager {
                 );
             });
 
+        if let Some(header) = &event_handler.gbs_header {
+            let mut track = None;
+
+            egui_sdl2_gl::egui::Window::new("GBS Player")
+                .open(&mut event_handler.gbs_player_opened)
+                .resizable(false)
+                .show(egui_ctx, |ui| {
+                    ui.heading(&header.title);
+                    ui.label(&header.author);
+                    ui.label(&header.copyright);
+                    ui.separator();
+
+                    let song = event_handler.gbs_song;
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(song > 0, egui_sdl2_gl::egui::Button::new("<"))
+                            .clicked()
+                        {
+                            track = Some(song - 1);
+                        }
+
+                        egui_sdl2_gl::egui::ComboBox::from_id_source("gbs_track")
+                            .selected_text(format!("Track {} / {}", song + 1, header.song_count))
+                            .show_ui(ui, |ui| {
+                                for index in 0..header.song_count {
+                                    if ui
+                                        .selectable_label(
+                                            index == song,
+                                            format!("Track {}", index + 1),
+                                        )
+                                        .clicked()
+                                    {
+                                        track = Some(index);
+                                    }
+                                }
+                            });
+
+                        if ui
+                            .add_enabled(
+                                song + 1 < header.song_count,
+                                egui_sdl2_gl::egui::Button::new(">"),
+                            )
+                            .clicked()
+                        {
+                            track = Some(song + 1);
+                        }
+
+                        if ui.button("Restart").clicked() {
+                            track = Some(song);
+                        }
+
+                        let elapsed = event_handler.gbs_elapsed as u32;
+                        ui.label(format!("{:02}:{:02}", elapsed / 60, elapsed % 60));
+                    });
+
+                    ui.separator();
+                    let oscilloscope = &cpu.memory_bus.apu.oscilloscope;
+                    for (index, label) in ["CH1", "CH2", "CH3", "CH4"].iter().enumerate() {
+                        ui.label(*label);
+                        draw_trace(ui, oscilloscope, Trace::Channel(index), Color32::GREEN);
+                    }
+                });
+
+            // Every track starts from a freshly initialized machine
+            if track.is_some() {
+                event_handler.gbs_track = track;
+                event_handler.file_path = event_handler.rom_path.clone();
+            }
+        }
+
+        egui_sdl2_gl::egui::Window::new("Oscilloscope")
+            .open(&mut event_handler.show_oscilloscope)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                let oscilloscope = &cpu.memory_bus.apu.oscilloscope;
+
+                for (label, trace) in [
+                    ("CH1 Square", Trace::Channel(0)),
+                    ("CH2 Square", Trace::Channel(1)),
+                    ("CH3 Wave", Trace::Channel(2)),
+                    ("CH4 Noise", Trace::Channel(3)),
+                ] {
+                    ui.label(label);
+                    draw_trace(ui, oscilloscope, trace, Color32::GREEN);
+                }
+
+                ui.separator();
+                ui.label("Master");
+                draw_trace(ui, oscilloscope, Trace::Left, Color32::LIGHT_BLUE);
+                draw_trace(ui, oscilloscope, Trace::Right, Color32::LIGHT_RED);
+            });
+
+        egui_sdl2_gl::egui::Window::new("Audio Mixer")
+            .open(&mut event_handler.mixer_opened)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                let mixer = &mut cpu.memory_bus.apu.mixer;
+                let levels = mixer.levels();
+
+                Grid::new("audio_mixer_grid")
+                    .num_columns(6)
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label("Mute");
+                        ui.label("Solo");
+                        ui.label("Gain");
+                        ui.label("Pan");
+                        ui.label("Level");
+                        ui.end_row();
+
+                        for (i, control) in mixer.controls.iter_mut().enumerate() {
+                            ui.label(format!("CH{}", i + 1));
+                            ui.checkbox(&mut control.muted, "");
+                            ui.checkbox(&mut control.solo, "");
+                            ui.add(
+                                egui_sdl2_gl::egui::Slider::new(&mut control.gain, 0.0..=2.0)
+                                    .fixed_decimals(2),
+                            );
+                            draw_pan_control(ui, &mut control.pan);
+                            draw_level_meter(ui, levels[i]);
+                            ui.end_row();
+                        }
+                    });
+
+                if ui.button("Reset").clicked() {
+                    mixer.controls = Default::default();
+                }
+            });
+
+        egui_sdl2_gl::egui::Window::new("Audio Settings")
+            .open(&mut event_handler.audio_settings_opened)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                // Every change reopens the device
+                let settings = &mut event_handler.audio_settings;
+
+                Grid::new("audio_settings_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Device");
+                        egui_sdl2_gl::egui::ComboBox::from_id_source("audio_device")
+                            .selected_text(settings.device.as_deref().unwrap_or("Default"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut settings.device, None, "Default");
+                                for device in &event_handler.audio_devices {
+                                    ui.selectable_value(
+                                        &mut settings.device,
+                                        Some(device.clone()),
+                                        device,
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Sample Rate");
+                        egui_sdl2_gl::egui::ComboBox::from_id_source("audio_sample_rate")
+                            .selected_text(format!("{} Hz", settings.sample_rate))
+                            .show_ui(ui, |ui| {
+                                for sample_rate in SAMPLE_RATES {
+                                    ui.selectable_value(
+                                        &mut settings.sample_rate,
+                                        sample_rate,
+                                        format!("{} Hz", sample_rate),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        let latency = |buffer_size: u16, sample_rate: u32| {
+                            format!(
+                                "{} samples ({:.1} ms)",
+                                buffer_size,
+                                buffer_size as f32 * 1000.0 / sample_rate as f32
+                            )
+                        };
+
+                        ui.label("Buffer Size");
+                        egui_sdl2_gl::egui::ComboBox::from_id_source("audio_buffer_size")
+                            .selected_text(latency(settings.buffer_size, settings.sample_rate))
+                            .show_ui(ui, |ui| {
+                                for buffer_size in BUFFER_SIZES {
+                                    ui.selectable_value(
+                                        &mut settings.buffer_size,
+                                        buffer_size,
+                                        latency(buffer_size, settings.sample_rate),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    });
+            });
+
         egui_sdl2_gl::egui::Window::new("Keybindings")
             .open(&mut event_handler.keybindings_opened)
             .show(egui_ctx, |ui| {
@@ -178,116 +423,100 @@ impl UIManager {
         egui_sdl2_gl::egui::Window::new("Color Scheme")
             .open(&mut event_handler.color_scheme_opened)
             .show(egui_ctx, |ui| {
-                Grid::new("color_scheme_grid")
-                    .num_columns(2)
-                    .spacing([10.0, 10.0])
-                    .show(ui, |ui| {
-                        if let Ok(mut borrowed_colors) = colors.try_borrow_mut() {
-                            color_picker_row(ui, "Black:", &mut borrowed_colors.black);
-                            color_picker_row(ui, "Dark:", &mut borrowed_colors.dark);
-                            color_picker_row(ui, "Light:", &mut borrowed_colors.light);
-                            color_picker_row(ui, "White:", &mut borrowed_colors.white);
-
-                            ui.label("Presets");
+                if let Ok(mut borrowed_colors) = colors.try_borrow_mut() {
+                    Grid::new("color_scheme_grid")
+                        .num_columns(4)
+                        .spacing([10.0, 10.0])
+                        .show(ui, |ui| {
+                            ui.label("");
+                            ui.label("BG");
+                            ui.label("OBJ0");
+                            ui.label("OBJ1");
                             ui.end_row();
 
-                            let presets = [
-                                (
-                                    "Default",
-                                    (8, 24, 32),
-                                    (52, 104, 86),
-                                    (136, 192, 112),
-                                    (224, 248, 208),
-                                ),
-                                (
-                                    "Green",
-                                    (24, 16, 16),
-                                    (72, 160, 88),
-                                    (160, 208, 128),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Red",
-                                    (24, 16, 16),
-                                    (208, 80, 48),
-                                    (248, 160, 80),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Cyan",
-                                    (24, 16, 16),
-                                    (112, 152, 200),
-                                    (168, 200, 232),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Yellow",
-                                    (24, 16, 16),
-                                    (208, 160, 0),
-                                    (248, 224, 112),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Brown",
-                                    (24, 16, 16),
-                                    (168, 112, 72),
-                                    (224, 160, 120),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Gray",
-                                    (24, 16, 16),
-                                    (120, 120, 144),
-                                    (208, 168, 176),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Purple",
-                                    (24, 16, 16),
-                                    (168, 120, 184),
-                                    (216, 176, 192),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Blue",
-                                    (24, 16, 16),
-                                    (88, 120, 184),
-                                    (144, 160, 216),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Pink",
-                                    (24, 16, 16),
-                                    (224, 120, 168),
-                                    (240, 176, 192),
-                                    (248, 232, 248),
-                                ),
-                                (
-                                    "Mew",
-                                    (24, 16, 16),
-                                    (128, 112, 152),
-                                    (240, 176, 136),
-                                    (248, 232, 248),
-                                ),
-                            ];
-
-                            for chunk in presets.chunks(3) {
-                                for &(name, black, dark, light, white) in chunk {
-                                    if ui.button(name).clicked() {
-                                        borrowed_colors.black =
-                                            Color32::from_rgb(black.0, black.1, black.2);
-                                        borrowed_colors.dark =
-                                            Color32::from_rgb(dark.0, dark.1, dark.2);
-                                        borrowed_colors.light =
-                                            Color32::from_rgb(light.0, light.1, light.2);
-                                        borrowed_colors.white =
-                                            Color32::from_rgb(white.0, white.1, white.2);
+                            let Colors { bg, obj0, obj1, .. } = &mut *borrowed_colors;
+                            color_picker_row(
+                                ui,
+                                "Black:",
+                                [&mut bg.black, &mut obj0.black, &mut obj1.black],
+                            );
+                            color_picker_row(
+                                ui,
+                                "Dark:",
+                                [&mut bg.dark, &mut obj0.dark, &mut obj1.dark],
+                            );
+                            color_picker_row(
+                                ui,
+                                "Light:",
+                                [&mut bg.light, &mut obj0.light, &mut obj1.light],
+                            );
+                            color_picker_row(
+                                ui,
+                                "White:",
+                                [&mut bg.white, &mut obj0.white, &mut obj1.white],
+                            );
+                        });
+
+                    ui.separator();
+                    ui.label("Presets");
+                    Grid::new("color_scheme_presets")
+                        .spacing([10.0, 10.0])
+                        .show(ui, |ui| {
+                            for chunk in PRESETS.chunks(3) {
+                                for (name, palette) in chunk {
+                                    if ui.button(*name).clicked() {
+                                        borrowed_colors.set_all(*palette);
                                     }
                                 }
                                 ui.end_row();
                             }
+                        });
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .checkbox(&mut borrowed_colors.cgb_colorization, "CGB Colorization")
+                            .changed()
+                            && borrowed_colors.cgb_colorization
+                        {
+                            borrowed_colors.apply_cgb_palettes();
+                        }
+
+                        ui.menu_button("Button Combos", |ui| {
+                            for (name, palettes) in KEY_COMBOS {
+                                if ui.button(name).clicked() {
+                                    borrowed_colors.set_palettes(palettes);
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Import...").clicked() {
+                            let file = FileDialog::new()
+                                .add_filter("Palette", &["pal", "gpl", "hex", "txt"])
+                                .set_directory("../")
+                                .pick_file();
+
+                            if let Some(file) = file {
+                                match palette_file::import(&file) {
+                                    Ok(palettes) => borrowed_colors.set_palettes(palettes),
+                                    Err(e) => eprintln!("Error importing palette: {e}."),
+                                }
+                            }
+                        }
+
+                        if let Some(rom_path) = &event_handler.rom_path {
+                            if ui.button("Save for this ROM").clicked() {
+                                match borrowed_colors.save_for_rom(rom_path) {
+                                    Ok(path) => println!("Palette saved to {}.", path.display()),
+                                    Err(e) => eprintln!("Error saving palette: {e}."),
+                                }
+                            }
                         }
                     });
+                }
             });
 
         egui_sdl2_gl::egui::Window::new("About")
@@ -307,17 +536,97 @@ impl UIManager {
                 );
             });
 
-        fn color_picker_row(ui: &mut Ui, label: &str, color: &mut Color32) {
+        fn color_picker_row(ui: &mut Ui, label: &str, colors: [&mut Color32; 3]) {
             ui.with_layout(
                 egui_sdl2_gl::egui::Layout::left_to_right(Align::Center),
                 |ui| {
                     ui.label(label);
                 },
             );
-            ui.color_edit_button_srgba(color);
+            for color in colors {
+                ui.color_edit_button_srgba(color);
+            }
             ui.end_row();
         }
 
+        egui_sdl2_gl::egui::Window::new("ROM Info")
+            .open(&mut event_handler.rom_info_opened)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                let header = &cpu.memory_bus.cartridge.header;
+                let size = |bytes: Option<usize>| match bytes {
+                    Some(bytes) => format!("{} KiB", bytes / 1024),
+                    None => "None".to_owned(),
+                };
+                let checksum = |valid: bool, value: String| {
+                    if valid {
+                        value
+                    } else {
+                        format!("{} (mismatch)", value)
+                    }
+                };
+
+                Grid::new("rom_info_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 4.0])
+                    .show(ui, |ui| {
+                        let rows = [
+                            ("Title", header.title.clone()),
+                            (
+                                "Manufacturer",
+                                header.manufacturer_code.clone().unwrap_or("-".to_owned()),
+                            ),
+                            ("Licensee", header.licensee().to_owned()),
+                            (
+                                "Old Licensee Code",
+                                format!("0x{:02X}", header.old_licensee_code),
+                            ),
+                            ("New Licensee Code", header.new_licensee_code.clone()),
+                            ("CGB", header.cgb_support().to_owned()),
+                            (
+                                "SGB",
+                                if header.sgb_support() { "Yes" } else { "No" }.to_owned(),
+                            ),
+                            (
+                                "Type",
+                                format!(
+                                    "{} (0x{:02X})",
+                                    header.cartridge_type_name(),
+                                    header.cartridge_type
+                                ),
+                            ),
+                            ("ROM Size", size(header.rom_size_bytes())),
+                            ("RAM Size", size(header.ram_size_bytes())),
+                            ("Destination", header.destination().to_owned()),
+                            ("Version", header.version.to_string()),
+                            (
+                                "Header Checksum",
+                                checksum(
+                                    header.header_checksum_valid(),
+                                    format!("0x{:02X}", header.header_checksum),
+                                ),
+                            ),
+                            (
+                                "Global Checksum",
+                                checksum(
+                                    header.global_checksum_valid(),
+                                    format!("0x{:04X}", header.global_checksum),
+                                ),
+                            ),
+                        ];
+
+                        for (label, value) in rows {
+                            ui.label(label);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+
+                for warning in header.warnings() {
+                    ui.colored_label(Color32::YELLOW, warning);
+                }
+            });
+
         egui_sdl2_gl::egui::Window::new("CPU Status")
             .open(&mut event_handler.cpu_status_opened)
             .show(egui_ctx, |ui| {
@@ -385,6 +694,46 @@ impl UIManager {
         window.gl_swap_window();
     }
 
+    fn copy_screenshot(&mut self, event_handler: &mut EventHandler, cpu: &Cpu) {
+        event_handler.screenshot_requested = false;
+        event_handler.screenshot_to_clipboard = false;
+
+        let image = match screenshot_image(
+            &cpu.memory_bus.ppu,
+            screenshot_scale(event_handler),
+            event_handler.screenshot_grayscale,
+        ) {
+            Ok(image) => image,
+            Err(e) => {
+                eprintln!("Error copying screenshot to clipboard: {e}.");
+                return;
+            }
+        };
+
+        if self.clipboard.is_none() {
+            match Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    eprintln!("Error copying screenshot to clipboard: {e}.");
+                    return;
+                }
+            }
+        }
+
+        let image = ImageData {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            bytes: Cow::Owned(image.into_raw()),
+        };
+
+        if let Some(clipboard) = &mut self.clipboard {
+            match clipboard.set_image(image) {
+                Ok(_) => println!("Screenshot copied to clipboard."),
+                Err(e) => eprintln!("Error copying screenshot to clipboard: {e}."),
+            }
+        }
+    }
+
     pub fn update_window_size(&mut self, window: &mut Window, event_handler: &mut EventHandler) {
         if self.current_view != self.previous_view
             || event_handler.window_scale != event_handler.previous_scale
@@ -430,6 +779,126 @@ impl UIManager {
     }
 }
 
+fn draw_trace(ui: &mut Ui, oscilloscope: &Oscilloscope, trace: Trace, color: Color32) {
+    // Around 15ms of history, which fits one period of notes down to 64 Hz
+    const TRACE_LENGTH: usize = 1024;
+
+    let (response, painter) =
+        ui.allocate_painter(Vec2::new(300.0, 40.0), egui_sdl2_gl::egui::Sense::hover());
+    let rect = response.rect;
+
+    painter.rect_filled(rect, 0.0, Color32::from_gray(20));
+    painter.line_segment(
+        [rect.left_center(), rect.right_center()],
+        Stroke::new(0.5, Color32::DARK_GRAY),
+    );
+
+    let mut samples = oscilloscope.triggered(trace, TRACE_LENGTH);
+
+    // Channels range from 0.0 to 1.0, the master output is centered around
+    // its average to drop the DC offset of the DACs
+    let range = match trace {
+        Trace::Channel(_) => 0.0..=1.0,
+        Trace::Left | Trace::Right => {
+            let average = samples.iter().sum::<f32>() / samples.len() as f32;
+            samples.iter_mut().for_each(|sample| *sample -= average);
+            -1.0..=1.0
+        }
+    };
+
+    let points = samples
+        .iter()
+        .enumerate()
+        .map(|(i, &sample)| {
+            let x = egui_sdl2_gl::egui::remap(
+                i as f32,
+                0.0..=(samples.len() - 1) as f32,
+                rect.left()..=rect.right(),
+            );
+            let y = egui_sdl2_gl::egui::remap(
+                sample,
+                range.clone(),
+                (rect.bottom() - 1.0)..=(rect.top() + 1.0),
+            );
+            Pos2::new(x, y.clamp(rect.top(), rect.bottom()))
+        })
+        .collect();
+
+    painter.add(egui_sdl2_gl::egui::Shape::line(
+        points,
+        Stroke::new(1.0, color),
+    ));
+}
+
+// Unchecked follows the game's NR51 panning, checked overrides it
+fn draw_pan_control(ui: &mut Ui, pan: &mut Option<f32>) {
+    ui.horizontal(|ui| {
+        let mut overridden = pan.is_some();
+        if ui.checkbox(&mut overridden, "").changed() {
+            *pan = if overridden { Some(0.0) } else { None };
+        }
+
+        match pan {
+            Some(value) => {
+                ui.add(egui_sdl2_gl::egui::Slider::new(value, -1.0..=1.0).fixed_decimals(2));
+            }
+            None => {
+                ui.label("NR51");
+            }
+        }
+    });
+}
+
+fn draw_level_meter(ui: &mut Ui, level: f32) {
+    let (response, painter) =
+        ui.allocate_painter(Vec2::new(100.0, 12.0), egui_sdl2_gl::egui::Sense::hover());
+    let rect = response.rect;
+    let level = level.clamp(0.0, 1.0);
+
+    let color = match level {
+        level if level > 0.9 => Color32::RED,
+        level if level > 0.7 => Color32::YELLOW,
+        _ => Color32::GREEN,
+    };
+
+    painter.rect_filled(rect, 0.0, Color32::from_gray(30));
+    painter.rect_filled(
+        Rect::from_min_size(rect.min, Vec2::new(rect.width() * level, rect.height())),
+        0.0,
+        color,
+    );
+}
+
+fn take_screenshot(event_handler: &mut EventHandler, cpu: &Cpu) {
+    event_handler.screenshot_requested = false;
+
+    let rom_path = match &event_handler.rom_path {
+        Some(rom_path) => rom_path,
+        None => return,
+    };
+
+    let path = event_handler.paths.screenshot_path(rom_path);
+    if let Err(e) = save_screenshot(
+        &cpu.memory_bus.ppu,
+        &path,
+        screenshot_scale(event_handler),
+        event_handler.screenshot_grayscale,
+    ) {
+        eprintln!("Error saving screenshot: {e}.");
+        return;
+    }
+
+    println!("Screenshot saved to {}.", path.display());
+}
+
+fn screenshot_scale(event_handler: &EventHandler) -> u32 {
+    if event_handler.screenshot_scaled {
+        event_handler.window_scale
+    } else {
+        1
+    }
+}
+
 pub fn draw_wave(
     ui: &mut egui_sdl2_gl::egui::Ui,
     wave_ram: &[u8; 32],
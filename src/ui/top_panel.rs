@@ -5,10 +5,20 @@
  * @date    September 13, 2024
  */
 
+use std::path::Path;
+
 use egui_sdl2_gl::egui::{menu, Context, TopBottomPanel, Ui};
 use rfd::FileDialog;
 
-use crate::{event_handler::EventHandler, State, View};
+use crate::{
+    apu::time_stretch::FastForwardAudio,
+    cartridge::save_format::{self, SaveFormat, SaveTransfer},
+    event_handler::EventHandler,
+    export::{export_camera_photos, VramExport},
+    recorder::RecordingFormat,
+    ui::filters::{Ghosting, Scaler, ScalingMode},
+    State, View,
+};
 
 pub struct TopPanel {
     pub menu_bar_height: f32,
@@ -40,7 +50,7 @@ impl TopPanel {
                                 event_handler.volume = 0;
 
                                 let file = FileDialog::new()
-                                    .add_filter("gb", &["gb"])
+                                    .add_filter("gb", &["gb", "gbc", "gbs", "zip", "gz"])
                                     .set_directory("../")
                                     .pick_file();
 
@@ -51,6 +61,173 @@ impl TopPanel {
 
                                 event_handler.volume = event_handler.last_volume;
                             }
+
+                            ui.menu_button("Recent ROMs                >", |ui| {
+                                let recent = event_handler.library.recent();
+                                if recent.is_empty() {
+                                    ui.label("No ROMs played yet");
+                                }
+
+                                for rom_path in recent {
+                                    let file_name = Path::new(&rom_path)
+                                        .file_name()
+                                        .map(|name| name.to_string_lossy().into_owned())
+                                        .unwrap_or_default();
+
+                                    if ui.button(file_name).on_hover_text(&rom_path).clicked() {
+                                        ui.close_menu();
+                                        event_handler.file_path = Some(rom_path);
+                                    }
+                                }
+
+                                ui.separator();
+                                if ui.button("Clear").clicked() {
+                                    ui.close_menu();
+                                    if let Err(e) = event_handler.library.clear_recent() {
+                                        eprintln!("Error saving ROM library: {e}.");
+                                    }
+                                }
+                            });
+
+                            if ui.button("ROM Library").clicked() {
+                                ui.close_menu();
+                                event_handler.library_opened = !event_handler.library_opened;
+                            }
+
+                            ui.menu_button("Game Boy Camera          >", |ui| {
+                                if ui.button("Load Image").clicked() {
+                                    ui.close_menu();
+                                    pick_camera_image(event_handler);
+                                }
+
+                                if ui.button("Test Pattern").clicked() {
+                                    ui.close_menu();
+                                    event_handler.camera_image = None;
+                                    event_handler.camera_image_changed = true;
+                                }
+
+                                ui.separator();
+                                if ui.button("Export Photos from .sav").clicked() {
+                                    ui.close_menu();
+                                    pick_camera_save();
+                                }
+                            });
+
+                            match current_state {
+                                State::Splash => ui.set_enabled(false),
+                                State::Play => {}
+                            }
+
+                            ui.menu_button("Save File                      >", |ui| {
+                                if ui.button("Import (.sav/.srm)").clicked() {
+                                    ui.close_menu();
+                                    pick_save_import(event_handler);
+                                }
+
+                                ui.separator();
+                                for format in SaveFormat::ALL {
+                                    if ui.button(format.name()).clicked() {
+                                        ui.close_menu();
+                                        pick_save_export(event_handler, format);
+                                    }
+                                }
+                            });
+
+                            ui.menu_button("Export VRAM                 >", |ui| {
+                                if ui.button("PNG Images").clicked() {
+                                    ui.close_menu();
+                                    pick_export_directory(event_handler, VramExport::Images);
+                                }
+
+                                if ui.button("Raw (.2bpp/.tilemap)").clicked() {
+                                    ui.close_menu();
+                                    pick_export_directory(event_handler, VramExport::Raw);
+                                }
+                            });
+
+                            ui.menu_button("Screenshot                    >", |ui| {
+                                if ui.button("Save (F12)").clicked() {
+                                    event_handler.screenshot_requested = true;
+                                    ui.close_menu();
+                                }
+
+                                if ui.button("Copy to Clipboard").clicked() {
+                                    event_handler.screenshot_requested = true;
+                                    event_handler.screenshot_to_clipboard = true;
+                                    ui.close_menu();
+                                }
+
+                                ui.separator();
+                                ui.checkbox(&mut event_handler.screenshot_scaled, "Window Scale");
+                                ui.checkbox(
+                                    &mut event_handler.screenshot_grayscale,
+                                    "Grayscale Shades",
+                                );
+                            });
+
+                            ui.menu_button("Record Video                >", |ui| {
+                                let recording = event_handler.recording.is_some();
+
+                                ui.add_enabled_ui(!recording, |ui| {
+                                    if ui.button("APNG (video only)").clicked() {
+                                        event_handler.recording = Some(RecordingFormat::Apng);
+                                        ui.close_menu();
+                                    }
+
+                                    if ui.button("AVI (video + audio)").clicked() {
+                                        event_handler.recording = Some(RecordingFormat::Avi);
+                                        ui.close_menu();
+                                    }
+                                });
+
+                                ui.add_enabled_ui(recording, |ui| {
+                                    if ui.button("Stop").clicked() {
+                                        event_handler.recording = None;
+                                        ui.close_menu();
+                                    }
+                                });
+                            });
+
+                            ui.menu_button("Record Audio                >", |ui| {
+                                let recording = event_handler.audio_recording;
+
+                                ui.add_enabled_ui(!recording, |ui| {
+                                    ui.checkbox(
+                                        &mut event_handler.record_stems,
+                                        "Channel stems (CH1-CH4)",
+                                    );
+
+                                    if ui.button("WAV").clicked() {
+                                        event_handler.audio_recording = true;
+                                        ui.close_menu();
+                                    }
+                                });
+
+                                ui.add_enabled_ui(recording, |ui| {
+                                    if ui.button("Stop").clicked() {
+                                        event_handler.audio_recording = false;
+                                        ui.close_menu();
+                                    }
+                                });
+
+                                ui.separator();
+
+                                let logging = event_handler.vgm_logging;
+
+                                ui.add_enabled_ui(!logging, |ui| {
+                                    if ui.button("VGM (register log)").clicked() {
+                                        event_handler.vgm_logging = true;
+                                        ui.close_menu();
+                                    }
+                                });
+
+                                ui.add_enabled_ui(logging, |ui| {
+                                    if ui.button("Stop VGM").clicked() {
+                                        event_handler.vgm_logging = false;
+                                        ui.close_menu();
+                                    }
+                                });
+                            });
                         });
 
                         ui.menu_button("View", |ui| {
@@ -98,6 +275,52 @@ impl TopPanel {
                                 };
                             });
 
+                            ui.menu_button("Filters                           >", |ui| {
+                                ui.label("Scaler");
+                                ui.radio_value(&mut event_handler.scaler, Scaler::None, "None");
+                                ui.radio_value(
+                                    &mut event_handler.scaler,
+                                    Scaler::Scale2x,
+                                    "Scale2x",
+                                );
+                                ui.radio_value(
+                                    &mut event_handler.scaler,
+                                    Scaler::Scale3x,
+                                    "Scale3x",
+                                );
+                                ui.radio_value(&mut event_handler.scaler, Scaler::Hq2x, "HQ2x");
+
+                                ui.separator();
+                                ui.label("Ghosting");
+                                ui.radio_value(&mut event_handler.ghosting, Ghosting::Off, "Off");
+                                ui.radio_value(
+                                    &mut event_handler.ghosting,
+                                    Ghosting::Blend,
+                                    "Frame Blending",
+                                );
+                                ui.radio_value(
+                                    &mut event_handler.ghosting,
+                                    Ghosting::Lcd,
+                                    "LCD Response",
+                                );
+
+                                ui.separator();
+                                ui.checkbox(&mut event_handler.lcd_grid, "LCD Pixel Grid");
+
+                                ui.separator();
+                                ui.label("Scaling");
+                                ui.radio_value(
+                                    &mut event_handler.scaling_mode,
+                                    ScalingMode::Integer,
+                                    "Integer",
+                                );
+                                ui.radio_value(
+                                    &mut event_handler.scaling_mode,
+                                    ScalingMode::Aspect,
+                                    "Aspect Correct",
+                                );
+                            });
+
                             match current_state {
                                 State::Splash => ui.set_enabled(false),
                                 State::Play => {}
@@ -143,8 +366,35 @@ impl TopPanel {
                                 {
                                     ui.close_menu();
                                 };
+                                if ui
+                                    .checkbox(&mut event_handler.show_oscilloscope, "Oscilloscope")
+                                    .clicked()
+                                {
+                                    ui.close_menu();
+                                };
                             });
 
+                            if ui
+                                .add_enabled(
+                                    event_handler.gbs_header.is_some(),
+                                    egui_sdl2_gl::egui::Button::new("GBS Player"),
+                                )
+                                .clicked()
+                            {
+                                event_handler.gbs_player_opened = true;
+                                ui.close_menu();
+                            };
+
+                            if ui.button("Audio Mixer").clicked() {
+                                event_handler.mixer_opened = !event_handler.mixer_opened;
+                                ui.close_menu();
+                            };
+
+                            if ui.button("ROM Info").clicked() {
+                                event_handler.rom_info_opened = !event_handler.rom_info_opened;
+                                ui.close_menu();
+                            };
+
                             if ui.button("CPU Status").clicked() {
                                 event_handler.cpu_status_opened = true;
                                 ui.close_menu();
@@ -163,6 +413,12 @@ impl TopPanel {
                                 ui.close_menu();
                             }
 
+                            if ui.button("Audio").clicked() {
+                                event_handler.audio_settings_opened =
+                                    !event_handler.audio_settings_opened;
+                                ui.close_menu();
+                            }
+
                             if ui.button("Color Scheme").clicked() {
                                 event_handler.color_scheme_opened =
                                     !event_handler.color_scheme_opened;
@@ -187,7 +443,25 @@ impl TopPanel {
                                     )
                                     .prefix("Speed: ")
                                     .suffix("x"),
-                                )
+                                );
+
+                                ui.separator();
+                                ui.label("Audio");
+                                ui.radio_value(
+                                    &mut event_handler.fast_forward_audio,
+                                    FastForwardAudio::TimeStretch,
+                                    "Time Stretch",
+                                );
+                                ui.radio_value(
+                                    &mut event_handler.fast_forward_audio,
+                                    FastForwardAudio::DropBlocks,
+                                    "Drop Blocks",
+                                );
+                                ui.radio_value(
+                                    &mut event_handler.fast_forward_audio,
+                                    FastForwardAudio::Pitched,
+                                    "Pitched",
+                                );
                             });
                         });
                         ui.menu_button("Help", |ui| {
@@ -211,6 +485,29 @@ impl TopPanel {
                         |ui| {
                             ui.add_space(6.0);
                             ui.label(format!("FPS: {:.2}", fps));
+                            ui.separator();
+                            ui.label(format!(
+                                "Buffer: {:.0}% ({:+.3}%)",
+                                event_handler.audio_buffer_fill * 100.0,
+                                (event_handler.rate_adjustment - 1.0) * 100.0
+                            ))
+                            .on_hover_text("Audio buffer fill and resampling rate adjustment");
+
+                            if let Some(seconds) = event_handler
+                                .recording_seconds
+                                .or(event_handler.audio_recording_seconds)
+                                .or(event_handler.vgm_seconds)
+                            {
+                                ui.separator();
+                                ui.colored_label(
+                                    egui_sdl2_gl::egui::Color32::RED,
+                                    format!(
+                                        "REC {:02}:{:02}",
+                                        seconds as u32 / 60,
+                                        seconds as u32 % 60
+                                    ),
+                                );
+                            }
 
                             ui.style_mut().spacing.slider_width = 75.0;
                             ui.separator();
@@ -247,3 +544,63 @@ impl TopPanel {
             });
     }
 }
+
+fn pick_camera_image(event_handler: &mut EventHandler) {
+    let file = FileDialog::new()
+        .add_filter("image", &["png", "jpg", "jpeg", "bmp", "gif"])
+        .set_directory("../")
+        .pick_file();
+
+    if let Some(file) = file {
+        event_handler.camera_image = Some(file);
+        event_handler.camera_image_changed = true;
+    }
+}
+
+fn pick_camera_save() {
+    let file = FileDialog::new()
+        .add_filter("sav", &["sav"])
+        .set_directory("../")
+        .pick_file();
+
+    if let Some(file) = file {
+        match export_camera_photos(&file) {
+            Ok(_) => println!("Camera photos exported next to {}.", file.display()),
+            Err(e) => eprintln!("Error exporting camera photos: {e}."),
+        }
+    }
+}
+
+fn pick_save_import(event_handler: &mut EventHandler) {
+    let file = FileDialog::new()
+        .add_filter("save", &["sav", "srm"])
+        .set_directory("../")
+        .pick_file();
+
+    if let Some(file) = file {
+        event_handler.save_transfer = Some((SaveTransfer::Import, file));
+    }
+}
+
+fn pick_save_export(event_handler: &mut EventHandler, format: SaveFormat) {
+    let file = FileDialog::new()
+        .add_filter(format.extension(), &[format.extension()])
+        .set_directory("../")
+        .set_file_name(save_format::export_file_name(
+            event_handler.rom_path.as_deref(),
+            format,
+        ))
+        .save_file();
+
+    if let Some(file) = file {
+        event_handler.save_transfer = Some((SaveTransfer::Export(format), file));
+    }
+}
+
+fn pick_export_directory(event_handler: &mut EventHandler, export: VramExport) {
+    let directory = FileDialog::new().set_directory("../").pick_folder();
+
+    if let Some(directory) = directory {
+        event_handler.vram_export = Some((export, directory));
+    }
+}